@@ -0,0 +1,864 @@
+use std::collections::BTreeMap;
+
+use chip8_base::SymbolMap;
+
+use crate::{AssembleError, Token, expression, token::{parse_number, tokenize}};
+
+/// Where programs are loaded
+pub const START: usize = 0x200;
+/// Highest address XO-CHIP can reach
+pub const END: usize = 0x10000;
+/// Stops a macro that expands itself from running forever
+const MAX_MACRO_EXPANSIONS: usize = 100_000;
+
+/// Assembled bytes, to be loaded at `START`, and the names used for addresses in the source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Program {
+    pub bytes: Vec<u8>,
+    pub symbols: SymbolMap,
+}
+
+/// Assemble Octo source
+pub fn assemble(source: &str) -> Result<Program, AssembleError> {
+    Assembler::new(tokenize(source)).run()
+}
+
+/// How a label's address is written once it is known
+#[derive(Debug, Clone, Copy)]
+enum FixupKind {
+    /// The low 12 bits of an instruction
+    Instruction,
+    /// A full 16-bit word
+    Word,
+    /// The high byte of the address with a nibble above it, as `:unpack` loads into v0
+    HighByte(u8),
+    LowByte,
+}
+
+struct Fixup {
+    address: usize,
+    kind: FixupKind,
+    token: Token,
+}
+
+struct Macro {
+    arguments: Vec<String>,
+    body: Vec<Token>,
+}
+
+/// Open `if ... begin`, `else` and `loop` blocks
+enum Block {
+    /// Address of the jump past the `if` body
+    If(usize),
+    /// Address of the jump past the `else` body
+    Else(usize),
+    /// Start of the loop, and the jumps `while` uses to leave it
+    Loop(usize, Vec<usize>),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Operand {
+    Register(u8),
+    Value(u8),
+}
+
+/// When the statement after an `if` runs
+#[derive(Debug, Clone, Copy)]
+enum Condition {
+    Equal(u8, Operand),
+    NotEqual(u8, Operand),
+    Key(u8),
+    NotKey(u8),
+    Less(u8, Operand),
+    Greater(u8, Operand),
+    LessOrEqual(u8, Operand),
+    GreaterOrEqual(u8, Operand),
+}
+
+impl Condition {
+    fn negate(self) -> Condition {
+        match self {
+            Condition::Equal(x, operand) => Condition::NotEqual(x, operand),
+            Condition::NotEqual(x, operand) => Condition::Equal(x, operand),
+            Condition::Key(x) => Condition::NotKey(x),
+            Condition::NotKey(x) => Condition::Key(x),
+            Condition::Less(x, operand) => Condition::GreaterOrEqual(x, operand),
+            Condition::Greater(x, operand) => Condition::LessOrEqual(x, operand),
+            Condition::LessOrEqual(x, operand) => Condition::Greater(x, operand),
+            Condition::GreaterOrEqual(x, operand) => Condition::Less(x, operand),
+        }
+    }
+}
+
+struct Assembler {
+    tokens: Vec<Token>,
+    position: usize,
+
+    /// Memory from `START` on
+    rom: Vec<u8>,
+    here: usize,
+
+    labels: BTreeMap<String, usize>,
+    constants: BTreeMap<String, f64>,
+    aliases: BTreeMap<String, u8>,
+    macros: BTreeMap<String, Macro>,
+    macro_expansions: usize,
+
+    fixups: Vec<Fixup>,
+    blocks: Vec<(Block, Token)>,
+    breakpoints: Vec<(String, usize)>,
+
+    /// `main` came before anything else, so the program doesn't need to start by jumping to it
+    main_first: bool,
+}
+
+impl Assembler {
+    fn new(tokens: Vec<Token>) -> Assembler {
+        Assembler {
+            tokens,
+            position: 0,
+
+            // Room for the jump to main
+            rom: vec![0, 0],
+            here: START + 2,
+
+            labels: BTreeMap::new(),
+            constants: BTreeMap::new(),
+            aliases: BTreeMap::new(),
+            macros: BTreeMap::new(),
+            macro_expansions: 0,
+
+            fixups: vec![],
+            blocks: vec![],
+            breakpoints: vec![],
+
+            main_first: false,
+        }
+    }
+
+    fn run(mut self) -> Result<Program, AssembleError> {
+        while self.position < self.tokens.len() {
+            self.statement()?;
+        }
+
+        if let Some((_, token)) = self.blocks.last() {
+            return Err(AssembleError::new(token, format!("'{}' is never closed", token.text)));
+        }
+
+        let main = Token {
+            text: "main".to_string(),
+            line: 1,
+            column: 1,
+        };
+        if !self.labels.contains_key(&main.text) {
+            return Err(AssembleError::new(&main, "The program has no 'main' label"));
+        }
+        if !self.main_first {
+            self.rom[0] = 0x10;
+            self.fixups.push(Fixup {
+                address: START,
+                kind: FixupKind::Instruction,
+                token: main,
+            });
+        }
+
+        for fixup in std::mem::take(&mut self.fixups) {
+            let Some(address) = self.labels.get(&fixup.token.text).copied() else {
+                return Err(AssembleError::new(&fixup.token, format!("Undefined label '{}'", fixup.token.text)));
+            };
+            self.write_address(&fixup, address)?;
+        }
+
+        let mut symbols = SymbolMap::new();
+        for (name, address) in self.labels.iter() {
+            symbols.insert_label(name, *address);
+        }
+        for (name, address) in self.breakpoints.iter() {
+            symbols.insert_breakpoint(name, *address);
+        }
+
+        Ok(Program {
+            bytes: self.rom,
+            symbols,
+        })
+    }
+
+    fn write_address(&mut self, fixup: &Fixup, address: usize) -> Result<(), AssembleError> {
+        let offset = fixup.address - START;
+
+        match fixup.kind {
+            FixupKind::Instruction => {
+                let address = Assembler::fit_address(&fixup.token, address as i64, 12)?;
+                self.rom[offset] = (self.rom[offset] & 0xf0) | (address >> 8) as u8;
+                self.rom[offset + 1] = address as u8;
+            },
+            FixupKind::Word => {
+                let address = Assembler::fit_address(&fixup.token, address as i64, 16)?;
+                self.rom[offset] = (address >> 8) as u8;
+                self.rom[offset + 1] = address as u8;
+            },
+            FixupKind::HighByte(nibble) => {
+                let address = Assembler::fit_address(&fixup.token, address as i64, Assembler::unpack_bits(nibble))?;
+                self.rom[offset] = (nibble << 4) | (address >> 8) as u8;
+            },
+            FixupKind::LowByte => self.rom[offset] = address as u8,
+        }
+
+        Ok(())
+    }
+
+    fn next(&mut self) -> Result<Token, AssembleError> {
+        let Some(token) = self.tokens.get(self.position).cloned() else {
+            let last = self.tokens.last().cloned().unwrap_or(Token { text: "".to_string(), line: 1, column: 1 });
+            return Err(AssembleError::new(&last, "Unexpected end of source"));
+        };
+        self.position += 1;
+
+        Ok(token)
+    }
+
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.position).map(|token| token.text.as_str())
+    }
+
+    fn expect(&mut self, text: &str) -> Result<Token, AssembleError> {
+        let token = self.next()?;
+        if token.text != text {
+            return Err(AssembleError::new(&token, format!("Expected '{}' but found '{}'", text, token.text)));
+        }
+
+        Ok(token)
+    }
+
+    fn emit(&mut self, token: &Token, byte: u8) -> Result<(), AssembleError> {
+        if self.here >= END {
+            return Err(AssembleError::new(token, "The program is larger than memory"));
+        }
+
+        let offset = self.here - START;
+        if offset >= self.rom.len() {
+            self.rom.resize(offset + 1, 0);
+        }
+        self.rom[offset] = byte;
+        self.here += 1;
+
+        Ok(())
+    }
+
+    fn emit_instruction(&mut self, token: &Token, instruction: u16) -> Result<(), AssembleError> {
+        self.emit(token, (instruction >> 8) as u8)?;
+        self.emit(token, instruction as u8)
+    }
+
+    /// An instruction with an address in its low 12 bits, filled in later if the label isn't defined yet
+    fn emit_address_instruction(&mut self, prefix: u16, token: &Token) -> Result<(), AssembleError> {
+        match self.known_value(&token.text) {
+            Some(value) => {
+                let value = Assembler::fit_address(token, value, 12)?;
+                self.emit_instruction(token, prefix | value)
+            },
+            None => {
+                self.fixups.push(Fixup {
+                    address: self.here,
+                    kind: FixupKind::Instruction,
+                    token: token.clone(),
+                });
+                self.emit_instruction(token, prefix)
+            },
+        }
+    }
+
+    fn define_label(&mut self, token: &Token, address: usize) -> Result<(), AssembleError> {
+        if self.labels.contains_key(&token.text) || self.constants.contains_key(&token.text) {
+            return Err(AssembleError::new(token, format!("'{}' is already defined", token.text)));
+        }
+        self.labels.insert(token.text.clone(), address);
+
+        Ok(())
+    }
+
+    /// A number, constant or label that is already defined
+    fn known_value(&self, text: &str) -> Option<i64> {
+        if let Some(value) = parse_number(text) {
+            return Some(value as i64);
+        }
+        if let Some(value) = self.constants.get(text) {
+            return Some(*value as i64);
+        }
+        self.labels.get(text).map(|address| *address as i64)
+    }
+
+    fn value(&self, token: &Token) -> Result<i64, AssembleError> {
+        self.known_value(&token.text).ok_or_else(|| AssembleError::new(token, format!("Undefined name '{}'", token.text)))
+    }
+
+    fn byte(&self, token: &Token) -> Result<u8, AssembleError> {
+        Assembler::fit_byte(token, self.value(token)?)
+    }
+
+    /// `value` as a signed or unsigned byte, an error at `token` when it doesn't fit
+    fn fit_byte(token: &Token, value: i64) -> Result<u8, AssembleError> {
+        if !(-128..=255).contains(&value) {
+            return Err(AssembleError::new(token, format!("{} does not fit in a byte", value)));
+        }
+
+        Ok(value as u8)
+    }
+
+    /// `value` as an address of `bits` bits, an error at `token` when it doesn't fit
+    fn fit_address(token: &Token, value: i64, bits: u32) -> Result<u16, AssembleError> {
+        if !(0..1 << bits).contains(&value) {
+            return Err(AssembleError::new(token, format!("Address {:#x} does not fit in {} bits", value, bits)));
+        }
+
+        Ok(value as u16)
+    }
+
+    fn nibble(&self, token: &Token) -> Result<u8, AssembleError> {
+        let value = self.value(token)?;
+        if !(0..=15).contains(&value) {
+            return Err(AssembleError::new(token, format!("{} does not fit in a nibble", value)));
+        }
+
+        Ok(value as u8)
+    }
+
+    fn register_index(&self, text: &str) -> Option<u8> {
+        if let Some(register) = self.aliases.get(text) {
+            return Some(*register);
+        }
+
+        let mut characters = text.chars();
+        match (characters.next(), characters.next(), characters.next()) {
+            (Some('v'), Some(digit), None) | (Some('V'), Some(digit), None) => digit.to_digit(16).map(|digit| digit as u8),
+            _ => None,
+        }
+    }
+
+    fn register(&self, token: &Token) -> Result<u8, AssembleError> {
+        self.register_index(&token.text).ok_or_else(|| AssembleError::new(token, format!("Expected a register but found '{}'", token.text)))
+    }
+
+    fn operand(&self, token: &Token) -> Result<Operand, AssembleError> {
+        match self.register_index(&token.text) {
+            Some(register) => Ok(Operand::Register(register)),
+            None => Ok(Operand::Value(self.byte(token)?)),
+        }
+    }
+
+    /// Tokens between `{` and its matching `}`
+    fn braced(&mut self) -> Result<(Token, Vec<Token>), AssembleError> {
+        let open = self.expect("{")?;
+
+        let mut depth = 1;
+        let mut result = vec![];
+        loop {
+            let token = self.next()?;
+            match token.text.as_str() {
+                "{" => depth += 1,
+                "}" => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok((open, result));
+                    }
+                },
+                _ => {},
+            }
+            result.push(token);
+        }
+    }
+
+    fn calculate(&self, open: &Token, tokens: &[Token]) -> Result<f64, AssembleError> {
+        let lookup = |name: &str| -> Option<f64> {
+            if name == "HERE" {
+                return Some(self.here as f64);
+            }
+            self.constants.get(name).copied().or_else(|| self.labels.get(name).map(|address| *address as f64))
+        };
+
+        expression::evaluate(open, tokens, &lookup)
+    }
+
+    fn statement(&mut self) -> Result<(), AssembleError> {
+        let token = self.next()?;
+
+        match token.text.as_str() {
+            ":" => {
+                let name = self.next()?;
+                if name.text == "main" && self.here == START + 2 && self.labels.is_empty() && self.fixups.is_empty() {
+                    self.rom.clear();
+                    self.here = START;
+                    self.main_first = true;
+                }
+                self.define_label(&name, self.here)
+            },
+            ":alias" => {
+                let name = self.next()?;
+                let register = self.next()?;
+                let register = self.register(&register)?;
+                self.aliases.insert(name.text, register);
+                Ok(())
+            },
+            ":const" => {
+                let name = self.next()?;
+                let value = self.next()?;
+                let value = self.value(&value)?;
+                self.define_constant(&name, value as f64)
+            },
+            ":calc" => {
+                let name = self.next()?;
+                let (open, tokens) = self.braced()?;
+                let value = self.calculate(&open, &tokens)?;
+                self.define_constant(&name, value)
+            },
+            ":byte" => {
+                let value = {
+                    if self.peek() == Some("{") {
+                        let (open, tokens) = self.braced()?;
+                        Assembler::fit_byte(&open, self.calculate(&open, &tokens)? as i64)?
+                    } else {
+                        let value = self.next()?;
+                        self.byte(&value)?
+                    }
+                };
+                self.emit(&token, value)
+            },
+            ":pointer" => {
+                let target = self.next()?;
+                match self.known_value(&target.text) {
+                    Some(value) => {
+                        let value = Assembler::fit_address(&target, value, 16)?;
+                        self.emit_instruction(&token, value)
+                    },
+                    None => {
+                        self.fixups.push(Fixup {
+                            address: self.here,
+                            kind: FixupKind::Word,
+                            token: target,
+                        });
+                        self.emit_instruction(&token, 0)
+                    },
+                }
+            },
+            ":org" => {
+                let address = self.next()?;
+                let value = self.value(&address)?;
+                if !(START as i64..END as i64).contains(&value) {
+                    return Err(AssembleError::new(&address, format!("Can't assemble at {:#x}", value)));
+                }
+                self.here = value as usize;
+                Ok(())
+            },
+            ":macro" => self.define_macro(),
+            ":call" => {
+                let target = self.next()?;
+                self.emit_address_instruction(0x2000, &target)
+            },
+            ":next" => {
+                let name = self.next()?;
+                self.define_label(&name, self.here + 1)
+            },
+            ":unpack" => self.unpack(&token),
+            ":breakpoint" => {
+                let name = self.next()?;
+                self.breakpoints.push((name.text, self.here));
+                Ok(())
+            },
+            ":monitor" => {
+                self.next()?;
+                self.next()?;
+                Ok(())
+            },
+            "clear" => self.emit_instruction(&token, 0x00e0),
+            "return" | ";" => self.emit_instruction(&token, 0x00ee),
+            "scroll-right" => self.emit_instruction(&token, 0x00fb),
+            "scroll-left" => self.emit_instruction(&token, 0x00fc),
+            "exit" => self.emit_instruction(&token, 0x00fd),
+            "lores" => self.emit_instruction(&token, 0x00fe),
+            "hires" => self.emit_instruction(&token, 0x00ff),
+            "scroll-down" | "scroll-up" => {
+                let rows = self.next()?;
+                let rows = self.nibble(&rows)? as u16;
+                self.emit_instruction(&token, if token.text == "scroll-down" { 0x00c0 } else { 0x00d0 } | rows)
+            },
+            "jump" | "jump0" | "native" => {
+                let target = self.next()?;
+                let prefix = match token.text.as_str() {
+                    "jump" => 0x1000,
+                    "jump0" => 0xb000,
+                    _ => 0x0000,
+                };
+                self.emit_address_instruction(prefix, &target)
+            },
+            "audio" => self.emit_instruction(&token, 0xf002),
+            "plane" => {
+                let planes = self.next()?;
+                let planes = self.nibble(&planes)? as u16;
+                self.emit_instruction(&token, 0xf001 | (planes << 8))
+            },
+            "bcd" | "saveflags" | "loadflags" => {
+                let x = self.next()?;
+                let x = self.register(&x)? as u16;
+                let low = match token.text.as_str() {
+                    "bcd" => 0x33,
+                    "saveflags" => 0x75,
+                    _ => 0x85,
+                };
+                self.emit_instruction(&token, 0xf000 | (x << 8) | low)
+            },
+            "save" | "load" => {
+                let x = self.next()?;
+                let x = self.register(&x)? as u16;
+                if self.peek() == Some("-") {
+                    self.next()?;
+                    let y = self.next()?;
+                    let y = self.register(&y)? as u16;
+                    let n = if token.text == "save" { 0x2 } else { 0x3 };
+                    return self.emit_instruction(&token, 0x5000 | (x << 8) | (y << 4) | n);
+                }
+                self.emit_instruction(&token, 0xf000 | (x << 8) | if token.text == "save" { 0x55 } else { 0x65 })
+            },
+            "sprite" => {
+                let x = self.next()?;
+                let x = self.register(&x)? as u16;
+                let y = self.next()?;
+                let y = self.register(&y)? as u16;
+                let rows = self.next()?;
+                let rows = self.nibble(&rows)? as u16;
+                self.emit_instruction(&token, 0xd000 | (x << 8) | (y << 4) | rows)
+            },
+            "delay" | "buzzer" | "pitch" => {
+                self.expect(":=")?;
+                let x = self.next()?;
+                let x = self.register(&x)? as u16;
+                let low = match token.text.as_str() {
+                    "delay" => 0x15,
+                    "buzzer" => 0x18,
+                    _ => 0x3a,
+                };
+                self.emit_instruction(&token, 0xf000 | (x << 8) | low)
+            },
+            "i" => self.index_statement(&token),
+            "if" => self.if_statement(&token),
+            "else" => {
+                let Some((Block::If(jump), _)) = self.blocks.pop() else {
+                    return Err(AssembleError::new(&token, "'else' without 'if ... begin'"));
+                };
+                let else_jump = self.here;
+                self.emit_instruction(&token, 0x1000)?;
+                self.patch_jump(jump, self.here);
+                self.blocks.push((Block::Else(else_jump), token));
+                Ok(())
+            },
+            "end" => {
+                match self.blocks.pop() {
+                    Some((Block::If(jump), _)) | Some((Block::Else(jump), _)) => {
+                        self.patch_jump(jump, self.here);
+                        Ok(())
+                    },
+                    _ => Err(AssembleError::new(&token, "'end' without 'begin'")),
+                }
+            },
+            "loop" => {
+                self.blocks.push((Block::Loop(self.here, vec![]), token));
+                Ok(())
+            },
+            "while" => {
+                let condition = self.condition()?;
+                self.emit_condition(&token, condition.negate())?;
+                let exit = self.here;
+                self.emit_instruction(&token, 0x1000)?;
+
+                let Some((Block::Loop(_, exits), _)) = self.blocks.iter_mut().rev().find(|(block, _)| matches!(block, Block::Loop(_, _))) else {
+                    return Err(AssembleError::new(&token, "'while' outside of a loop"));
+                };
+                exits.push(exit);
+                Ok(())
+            },
+            "again" => {
+                let Some((Block::Loop(start, exits), _)) = self.blocks.pop() else {
+                    return Err(AssembleError::new(&token, "'again' without 'loop'"));
+                };
+                if start > 0xfff {
+                    return Err(AssembleError::new(&token, "The loop is out of reach of a jump"));
+                }
+                self.emit_instruction(&token, 0x1000 | start as u16)?;
+                for exit in exits {
+                    self.patch_jump(exit, self.here);
+                }
+                Ok(())
+            },
+            text if self.register_index(text).is_some() => self.register_statement(&token),
+            text if self.macros.contains_key(text) => self.expand_macro(&token),
+            text if text.starts_with(':') => Err(AssembleError::new(&token, format!("Unsupported directive '{}'", text))),
+            text => match self.known_value(text) {
+                Some(_) if self.labels.contains_key(text) => self.emit_address_instruction(0x2000, &token),
+                Some(_) => {
+                    let value = self.byte(&token)?;
+                    self.emit(&token, value)
+                },
+                // Anything else calls a label, possibly one defined later
+                None => self.emit_address_instruction(0x2000, &token),
+            },
+        }
+    }
+
+    fn define_constant(&mut self, name: &Token, value: f64) -> Result<(), AssembleError> {
+        if self.labels.contains_key(&name.text) {
+            return Err(AssembleError::new(name, format!("'{}' is already defined", name.text)));
+        }
+        self.constants.insert(name.text.clone(), value);
+
+        Ok(())
+    }
+
+    /// `:macro name arguments... { body }`
+    fn define_macro(&mut self) -> Result<(), AssembleError> {
+        let name = self.next()?;
+
+        let mut arguments = vec![];
+        while self.peek().is_some_and(|text| text != "{") {
+            arguments.push(self.next()?.text);
+        }
+        let (_, body) = self.braced()?;
+
+        self.macros.insert(name.text, Macro { arguments, body });
+        Ok(())
+    }
+
+    /// Replace the macro's name and arguments with its body
+    fn expand_macro(&mut self, token: &Token) -> Result<(), AssembleError> {
+        self.macro_expansions += 1;
+        if self.macro_expansions > MAX_MACRO_EXPANSIONS {
+            return Err(AssembleError::new(token, format!("Too many macro expansions, '{}' may expand itself forever", token.text)));
+        }
+
+        let (argument_names, body) = match self.macros.get(&token.text) {
+            Some(definition) => (definition.arguments.clone(), definition.body.clone()),
+            None => return Err(AssembleError::new(token, format!("Undefined macro '{}'", token.text))),
+        };
+
+        let mut values: BTreeMap<String, String> = BTreeMap::new();
+        for name in argument_names {
+            let value = self.next()?;
+            values.insert(name, value.text);
+        }
+
+        let expanded: Vec<Token> = body.into_iter().map(|mut body_token| {
+            if let Some(value) = values.get(&body_token.text) {
+                body_token.text = value.clone();
+            }
+            body_token
+        }).collect();
+        self.tokens.splice(self.position..self.position, expanded);
+
+        Ok(())
+    }
+
+    /// `:unpack nibble label` or `:unpack long label`, loading the address into v0 and v1
+    fn unpack(&mut self, token: &Token) -> Result<(), AssembleError> {
+        let nibble = self.next()?;
+        let nibble = if nibble.text == "long" { 0 } else { self.nibble(&nibble)? };
+        let target = self.next()?;
+
+        match self.known_value(&target.text) {
+            Some(address) => {
+                let address = Assembler::fit_address(&target, address, Assembler::unpack_bits(nibble))?;
+                self.emit_instruction(token, 0x6000 | ((nibble as u16) << 4) | ((address >> 8) & 0xff))?;
+                self.emit_instruction(token, 0x6100 | (address & 0xff))
+            },
+            None => {
+                self.fixups.push(Fixup {
+                    address: self.here + 1,
+                    kind: FixupKind::HighByte(nibble),
+                    token: target.clone(),
+                });
+                self.emit_instruction(token, 0x6000)?;
+                self.fixups.push(Fixup {
+                    address: self.here + 1,
+                    kind: FixupKind::LowByte,
+                    token: target,
+                });
+                self.emit_instruction(token, 0x6100)
+            },
+        }
+    }
+
+    /// A nibble shares v0 with the address's high byte, leaving 12 bits for the address
+    fn unpack_bits(nibble: u8) -> u32 {
+        if nibble == 0 { 16 } else { 12 }
+    }
+
+    fn patch_jump(&mut self, jump: usize, target: usize) {
+        let offset = jump - START;
+        self.rom[offset] = 0x10 | ((target >> 8) & 0x0f) as u8;
+        self.rom[offset + 1] = target as u8;
+    }
+
+    /// `i := address`, `i := long address`, `i := hex vx`, `i := bighex vx` or `i += vx`
+    fn index_statement(&mut self, token: &Token) -> Result<(), AssembleError> {
+        let operator = self.next()?;
+        match operator.text.as_str() {
+            "+=" => {
+                let x = self.next()?;
+                let x = self.register(&x)? as u16;
+                self.emit_instruction(token, 0xf01e | (x << 8))
+            },
+            ":=" => {
+                let value = self.next()?;
+                match value.text.as_str() {
+                    "hex" | "bighex" => {
+                        let x = self.next()?;
+                        let x = self.register(&x)? as u16;
+                        self.emit_instruction(token, 0xf000 | (x << 8) | if value.text == "hex" { 0x29 } else { 0x30 })
+                    },
+                    "long" => {
+                        let target = self.next()?;
+                        self.emit_instruction(token, 0xf000)?;
+                        match self.known_value(&target.text) {
+                            Some(address) => {
+                                let address = Assembler::fit_address(&target, address, 16)?;
+                                self.emit_instruction(token, address)
+                            },
+                            None => {
+                                self.fixups.push(Fixup {
+                                    address: self.here,
+                                    kind: FixupKind::Word,
+                                    token: target,
+                                });
+                                self.emit_instruction(token, 0)
+                            },
+                        }
+                    },
+                    _ => self.emit_address_instruction(0xa000, &value),
+                }
+            },
+            _ => Err(AssembleError::new(&operator, format!("Expected ':=' or '+=' but found '{}'", operator.text))),
+        }
+    }
+
+    /// Statements starting with a register, such as `v1 += 2` or `v3 := random 0xff`
+    fn register_statement(&mut self, token: &Token) -> Result<(), AssembleError> {
+        let x = self.register(token)? as u16;
+        let operator = self.next()?;
+        let source = self.next()?;
+
+        let register_operation = |n: u16| -> Result<u16, AssembleError> {
+            let y = self.register(&source)? as u16;
+            Ok(0x8000 | (x << 8) | (y << 4) | n)
+        };
+
+        let instruction = match (operator.text.as_str(), source.text.as_str()) {
+            (":=", "random") => {
+                let mask = self.next()?;
+                0xc000 | (x << 8) | self.byte(&mask)? as u16
+            },
+            (":=", "key") => 0xf00a | (x << 8),
+            (":=", "delay") => 0xf007 | (x << 8),
+            (":=", _) => match self.operand(&source)? {
+                Operand::Register(y) => 0x8000 | (x << 8) | ((y as u16) << 4),
+                Operand::Value(value) => 0x6000 | (x << 8) | value as u16,
+            },
+            ("+=", _) => match self.operand(&source)? {
+                Operand::Register(y) => 0x8004 | (x << 8) | ((y as u16) << 4),
+                Operand::Value(value) => 0x7000 | (x << 8) | value as u16,
+            },
+            ("-=", _) => match self.operand(&source)? {
+                Operand::Register(y) => 0x8005 | (x << 8) | ((y as u16) << 4),
+                Operand::Value(value) => 0x7000 | (x << 8) | value.wrapping_neg() as u16,
+            },
+            ("|=", _) => register_operation(0x1)?,
+            ("&=", _) => register_operation(0x2)?,
+            ("^=", _) => register_operation(0x3)?,
+            (">>=", _) => register_operation(0x6)?,
+            ("=-", _) => register_operation(0x7)?,
+            ("<<=", _) => register_operation(0xe)?,
+            _ => return Err(AssembleError::new(&operator, format!("Unknown operator '{}'", operator.text))),
+        };
+
+        self.emit_instruction(token, instruction)
+    }
+
+    /// `vx == operand`, `vx != operand`, `vx < operand` and so on, `vx key` or `vx -key`
+    fn condition(&mut self) -> Result<Condition, AssembleError> {
+        let x = self.next()?;
+        let x = self.register(&x)?;
+        let operator = self.next()?;
+
+        match operator.text.as_str() {
+            "key" => return Ok(Condition::Key(x)),
+            "-key" => return Ok(Condition::NotKey(x)),
+            _ => {},
+        }
+
+        let operand = self.next()?;
+        let operand = self.operand(&operand)?;
+        match operator.text.as_str() {
+            "==" => Ok(Condition::Equal(x, operand)),
+            "!=" => Ok(Condition::NotEqual(x, operand)),
+            "<" => Ok(Condition::Less(x, operand)),
+            ">" => Ok(Condition::Greater(x, operand)),
+            "<=" => Ok(Condition::LessOrEqual(x, operand)),
+            ">=" => Ok(Condition::GreaterOrEqual(x, operand)),
+            _ => Err(AssembleError::new(&operator, format!("Unknown comparison '{}'", operator.text))),
+        }
+    }
+
+    /// Instructions that run the next one only when `condition` holds
+    fn emit_condition(&mut self, token: &Token, condition: Condition) -> Result<(), AssembleError> {
+        let skip = |x: u8, operand: Operand, equal: bool| -> u16 {
+            let x = (x as u16) << 8;
+            match (operand, equal) {
+                (Operand::Value(value), true) => 0x4000 | x | value as u16,
+                (Operand::Value(value), false) => 0x3000 | x | value as u16,
+                (Operand::Register(y), true) => 0x9000 | x | ((y as u16) << 4),
+                (Operand::Register(y), false) => 0x5000 | x | ((y as u16) << 4),
+            }
+        };
+
+        match condition {
+            Condition::Equal(x, operand) => self.emit_instruction(token, skip(x, operand, true)),
+            Condition::NotEqual(x, operand) => self.emit_instruction(token, skip(x, operand, false)),
+            Condition::Key(x) => self.emit_instruction(token, 0xe0a1 | ((x as u16) << 8)),
+            Condition::NotKey(x) => self.emit_instruction(token, 0xe09e | ((x as u16) << 8)),
+            // Compare through VF: load the operand into it, subtract, and test the borrow flag
+            Condition::Less(x, operand) | Condition::Greater(x, operand) | Condition::LessOrEqual(x, operand) | Condition::GreaterOrEqual(x, operand) => {
+                match operand {
+                    Operand::Register(y) => self.emit_instruction(token, 0x8f00 | ((y as u16) << 4))?,
+                    Operand::Value(value) => self.emit_instruction(token, 0x6f00 | value as u16)?,
+                }
+
+                let x = (x as u16) << 4;
+                match condition {
+                    // VF = 1 when x >= operand
+                    Condition::Less(_, _) | Condition::GreaterOrEqual(_, _) => self.emit_instruction(token, 0x8f07 | x)?,
+                    // VF = 1 when operand >= x
+                    _ => self.emit_instruction(token, 0x8f05 | x)?,
+                }
+
+                match condition {
+                    Condition::Less(_, _) | Condition::Greater(_, _) => self.emit_instruction(token, 0x4f00),
+                    _ => self.emit_instruction(token, 0x3f00),
+                }
+            },
+        }
+    }
+
+    /// `if condition then statement` or `if condition begin ... [else ...] end`
+    fn if_statement(&mut self, token: &Token) -> Result<(), AssembleError> {
+        let condition = self.condition()?;
+        let keyword = self.next()?;
+
+        match keyword.text.as_str() {
+            "then" => self.emit_condition(token, condition),
+            "begin" => {
+                self.emit_condition(token, condition.negate())?;
+                let jump = self.here;
+                self.emit_instruction(token, 0x1000)?;
+                self.blocks.push((Block::If(jump), token.clone()));
+                Ok(())
+            },
+            _ => Err(AssembleError::new(&keyword, format!("Expected 'then' or 'begin' but found '{}'", keyword.text))),
+        }
+    }
+}
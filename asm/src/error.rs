@@ -0,0 +1,29 @@
+use std::fmt;
+
+use crate::Token;
+
+/// Why source could not be assembled, and where
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssembleError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl AssembleError {
+    pub fn new(token: &Token, message: impl Into<String>) -> AssembleError {
+        AssembleError {
+            line: token.line,
+            column: token.column,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for AssembleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for AssembleError {}
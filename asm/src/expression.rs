@@ -0,0 +1,122 @@
+use crate::{AssembleError, Token, token::parse_number};
+
+const BINARY_OPERATORS: [&str; 19] = [
+    "+", "-", "*", "/", "%", "&", "|", "^", "<<", ">>", "pow", "min", "max", "<", ">", "<=", ">=", "==", "!=",
+];
+const UNARY_OPERATORS: [&str; 13] = [
+    "-", "~", "!", "sin", "cos", "tan", "exp", "log", "abs", "sqrt", "sign", "ceil", "floor",
+];
+
+/// Evaluate a `:calc` style expression
+///
+/// As in Octo there is no precedence, operators apply right to left unless parenthesized. `start` is the token
+/// before the expression, for reporting an empty one
+pub fn evaluate(start: &Token, tokens: &[Token], lookup: &dyn Fn(&str) -> Option<f64>) -> Result<f64, AssembleError> {
+    let mut parser = Parser {
+        tokens,
+        position: 0,
+        lookup,
+    };
+    let result = parser.expression(start)?;
+    if let Some(extra) = tokens.get(parser.position) {
+        return Err(AssembleError::new(extra, format!("Unexpected '{}' in expression", extra.text)));
+    }
+
+    Ok(result)
+}
+
+struct Parser<'tokens, 'lookup> {
+    tokens: &'tokens [Token],
+    position: usize,
+    lookup: &'lookup dyn Fn(&str) -> Option<f64>,
+}
+
+impl<'tokens, 'lookup> Parser<'tokens, 'lookup> {
+    fn next(&mut self, previous: &Token) -> Result<&'tokens Token, AssembleError> {
+        let Some(token) = self.tokens.get(self.position) else {
+            return Err(AssembleError::new(previous, "Expression ends early"));
+        };
+        self.position += 1;
+
+        Ok(token)
+    }
+
+    fn expression(&mut self, previous: &Token) -> Result<f64, AssembleError> {
+        let left = self.unary(previous)?;
+
+        let Some(operator) = self.tokens.get(self.position) else {
+            return Ok(left);
+        };
+        if !BINARY_OPERATORS.contains(&operator.text.as_str()) {
+            return Ok(left);
+        }
+        self.position += 1;
+        let right = self.expression(operator)?;
+
+        let integer = |value: f64| value as i64;
+        let truth = |value: bool| if value { 1.0 } else { 0.0 };
+        Ok(match operator.text.as_str() {
+            "+" => left + right,
+            "-" => left - right,
+            "*" => left * right,
+            "/" => left / right,
+            "%" => left % right,
+            "&" => (integer(left) & integer(right)) as f64,
+            "|" => (integer(left) | integer(right)) as f64,
+            "^" => (integer(left) ^ integer(right)) as f64,
+            "<<" => (integer(left) << integer(right)) as f64,
+            ">>" => (integer(left) >> integer(right)) as f64,
+            "pow" => left.powf(right),
+            "min" => left.min(right),
+            "max" => left.max(right),
+            "<" => truth(left < right),
+            ">" => truth(left > right),
+            "<=" => truth(left <= right),
+            ">=" => truth(left >= right),
+            "==" => truth(left == right),
+            _ => truth(left != right),
+        })
+    }
+
+    fn unary(&mut self, previous: &Token) -> Result<f64, AssembleError> {
+        let token = self.next(previous)?;
+        let text = token.text.as_str();
+
+        if text == "(" {
+            let result = self.expression(token)?;
+            let close = self.next(token)?;
+            if close.text != ")" {
+                return Err(AssembleError::new(close, "Expected ')'"));
+            }
+            return Ok(result);
+        }
+
+        if UNARY_OPERATORS.contains(&text) {
+            let value = self.unary(token)?;
+            return Ok(match text {
+                "-" => -value,
+                "~" => !(value as i64) as f64,
+                "!" => if value == 0.0 { 1.0 } else { 0.0 },
+                "sin" => value.sin(),
+                "cos" => value.cos(),
+                "tan" => value.tan(),
+                "exp" => value.exp(),
+                "log" => value.ln(),
+                "abs" => value.abs(),
+                "sqrt" => value.sqrt(),
+                "sign" => value.signum(),
+                "ceil" => value.ceil(),
+                _ => value.floor(),
+            });
+        }
+
+        if let Some(value) = parse_number(text) {
+            return Ok(value);
+        }
+        match text {
+            "PI" => Ok(std::f64::consts::PI),
+            "E" => Ok(std::f64::consts::E),
+            name => (self.lookup)(name).ok_or_else(|| AssembleError::new(token, format!("Undefined name '{}'", name))),
+        }
+    }
+}
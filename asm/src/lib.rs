@@ -0,0 +1,7 @@
+pub mod assembler;
+pub use self::assembler::{assemble, Program};
+pub mod error;
+pub use self::error::AssembleError;
+mod expression;
+pub mod token;
+pub use self::token::Token;
@@ -0,0 +1,39 @@
+use std::{env, fs, path::Path, process};
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 2 {
+        println!("Usage: {} <source.8o> [output.ch8]", args[0]);
+        process::exit(1);
+    }
+
+    let source_file_name = args[1].as_str();
+    let output_file_name = match args.get(2) {
+        Some(name) => Path::new(name).to_path_buf(),
+        None => Path::new(source_file_name).with_extension("ch8"),
+    };
+
+    let source = match fs::read_to_string(source_file_name) {
+        Ok(source) => source,
+        Err(error) => {
+            println!("Error: while loading file {}: {}", source_file_name, error);
+            process::exit(1);
+        },
+    };
+
+    let program = match chip8_asm::assemble(&source) {
+        Ok(program) => program,
+        Err(error) => {
+            println!("{}:{}:{}: {}", source_file_name, error.line, error.column, error.message);
+            process::exit(1);
+        },
+    };
+
+    // The symbol map goes alongside the program, where the console's debugger looks for it
+    let symbols_file_name = output_file_name.with_extension("sym");
+    let result = fs::write(&output_file_name, &program.bytes).and_then(|_| fs::write(&symbols_file_name, program.symbols.to_string()));
+    if let Err(error) = result {
+        println!("Error: while saving {}: {}", output_file_name.display(), error);
+        process::exit(1);
+    }
+}
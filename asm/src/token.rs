@@ -0,0 +1,68 @@
+/// A whitespace separated word of source, with where it starts for error messages
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub text: String,
+    /// 1-based
+    pub line: usize,
+    /// 1-based, in characters
+    pub column: usize,
+}
+
+/// Split source into tokens, dropping `#` comments
+pub fn tokenize(source: &str) -> Vec<Token> {
+    let mut result = vec![];
+
+    for (line_index, line) in source.lines().enumerate() {
+        let mut current: Option<Token> = None;
+
+        for (column_index, character) in line.chars().enumerate() {
+            if character == '#' && current.is_none() {
+                break;
+            }
+
+            if character.is_whitespace() {
+                if let Some(token) = current.take() {
+                    result.push(token);
+                }
+                continue;
+            }
+
+            match current.as_mut() {
+                Some(token) => token.text.push(character),
+                None => current = Some(Token {
+                    text: character.to_string(),
+                    line: line_index + 1,
+                    column: column_index + 1,
+                }),
+            }
+        }
+
+        if let Some(token) = current.take() {
+            result.push(token);
+        }
+    }
+
+    result
+}
+
+/// Decimal, 0x hex or 0b binary, optionally negative, or a decimal fraction
+pub fn parse_number(text: &str) -> Option<f64> {
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, text),
+    };
+
+    let value = {
+        if let Some(hex) = digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")) {
+            i64::from_str_radix(hex, 16).ok()? as f64
+        } else if let Some(binary) = digits.strip_prefix("0b").or_else(|| digits.strip_prefix("0B")) {
+            i64::from_str_radix(binary, 2).ok()? as f64
+        } else if digits.starts_with(|character: char| character.is_ascii_digit()) {
+            digits.parse::<f64>().ok()?
+        } else {
+            return None;
+        }
+    };
+
+    Some(if negative { -value } else { value })
+}
@@ -0,0 +1,157 @@
+#[cfg(test)]
+mod assembler_tests {
+    use std::fs;
+
+    use chip8_asm::{AssembleError, assemble};
+    use chip8_base::{Disassembly, disassembler::DEFAULT_START};
+
+    #[test]
+    fn test_opcode_test() {
+        let program = assemble(include_str!("../../programs/test_opcode.8o")).unwrap();
+
+        assert_eq!(program.bytes, include_bytes!("../../programs/test_opcode.ch8").to_vec());
+        assert_eq!(program.symbols.address("main"), Some(0x24e));
+    }
+
+    #[test]
+    fn main_test() {
+        // main first needs no jump to it
+        assert_eq!(assemble(": main clear").unwrap().bytes, vec![0x00, 0xe0]);
+        assert_eq!(assemble(": sub ; : main sub").unwrap().bytes, vec![0x12, 0x04, 0x00, 0xee, 0x22, 0x02]);
+        assert_eq!(assemble(": sub ;").unwrap_err().message, "The program has no 'main' label");
+    }
+
+    #[test]
+    fn error_test() {
+        let error = assemble(": main\n  v0 := 1\n  v1 += v17\n").unwrap_err();
+        assert_eq!(error, AssembleError { line: 3, column: 9, message: "Undefined name 'v17'".to_string() });
+
+        let error = assemble(": main\n\tjump nowhere").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 7));
+        assert_eq!(error.to_string(), "line 2, column 7: Undefined label 'nowhere'");
+
+        assert_eq!(assemble(": main loop").unwrap_err().message, "'loop' is never closed");
+    }
+
+    #[test]
+    fn range_test() {
+        assert_eq!(assemble(": main :byte { 300 }").unwrap_err().message, "300 does not fit in a byte");
+        assert_eq!(assemble(": main :byte { 0 - 1 }").unwrap().bytes, vec![0xff]);
+
+        assert_eq!(assemble(": main i := long 0x12345").unwrap_err().message, "Address 0x12345 does not fit in 16 bits");
+        assert_eq!(assemble(": main :pointer 0x10000").unwrap_err().message, "Address 0x10000 does not fit in 16 bits");
+        assert_eq!(assemble(": main :pointer 0xffff").unwrap().bytes, vec![0xff, 0xff]);
+
+        // The nibble leaves 12 bits for the address, whether it's known yet or not
+        assert_eq!(assemble(": main :unpack 0xa 0x1000").unwrap_err().message, "Address 0x1000 does not fit in 12 bits");
+        let error = assemble(": main :unpack 0xa data :org 0x1000 : data").unwrap_err();
+        assert_eq!((error.column, error.message.as_str()), (20, "Address 0x1000 does not fit in 12 bits"));
+        assert_eq!(assemble(": main :unpack long data :org 0x1000 : data").unwrap().bytes[0..4], [0x60, 0x10, 0x61, 0x00]);
+    }
+
+    #[test]
+    fn constants_test() {
+        let source = "
+            :alias x v3
+            :const SIZE 8
+            :calc DOUBLE { ( SIZE * 2 ) + 1 }
+            : main
+                x := DOUBLE
+                :byte { SIZE - 1 }
+        ";
+
+        assert_eq!(assemble(source).unwrap().bytes, vec![0x63, 0x11, 0x07]);
+    }
+
+    #[test]
+    fn macro_test() {
+        let source = "
+            :macro swap a b { vf := a a := b b := vf }
+            : main
+                swap v1 v2
+        ";
+
+        assert_eq!(assemble(source).unwrap().bytes, vec![0x8f, 0x10, 0x81, 0x20, 0x82, 0xf0]);
+    }
+
+    #[test]
+    fn control_flow_test() {
+        let source = "
+            : main
+                loop
+                    v0 += 1
+                    if v0 == 5 then v1 := 0
+                    if v1 key begin
+                        v2 := 1
+                    else
+                        v2 := 2
+                    end
+                    while v0 < 10
+                again
+        ";
+
+        assert_eq!(assemble(source).unwrap().bytes, vec![
+            0x70, 0x01, // 0x200: v0 += 1
+            0x40, 0x05, // 0x202: skip unless v0 == 5
+            0x61, 0x00, // 0x204
+            0xe1, 0x9e, // 0x206: skip if v1 isn't pressed
+            0x12, 0x0e, // 0x208: to else
+            0x62, 0x01, // 0x20a
+            0x12, 0x10, // 0x20c: to end
+            0x62, 0x02, // 0x20e
+            0x6f, 0x0a, // 0x210: vf := 10
+            0x8f, 0x07, // 0x212: vf =- v0
+            0x3f, 0x00, // 0x214: skip if v0 < 10
+            0x12, 0x1a, // 0x216: leave the loop
+            0x12, 0x00, // 0x218: again
+        ]);
+    }
+
+    #[test]
+    fn extended_instructions_test() {
+        let source = "
+            : main
+                hires
+                scroll-down 4
+                plane 3
+                audio
+                i := long data
+                save v2 - v5
+                load v1
+                sprite v0 v1 0
+                :unpack 0xa data
+                pitch := v4
+            : data
+        ";
+
+        assert_eq!(assemble(source).unwrap().bytes, vec![
+            0x00, 0xff, 0x00, 0xc4, 0xf3, 0x01, 0xf0, 0x02, 0xf0, 0x00, 0x02, 0x18,
+            0x52, 0x52, 0xf1, 0x65, 0xd0, 0x10, 0x60, 0xa2, 0x61, 0x18, 0xf4, 0x3a,
+        ]);
+    }
+
+    #[test]
+    fn symbols_test() {
+        let program = assemble(": main :breakpoint start clear : draw ;").unwrap();
+
+        assert_eq!(program.symbols.address("main"), Some(0x200));
+        assert_eq!(program.symbols.address("draw"), Some(0x202));
+        assert_eq!(program.symbols.breakpoints().collect::<Vec<_>>(), vec![("start", 0x200)]);
+    }
+
+    #[test]
+    fn disassembly_round_trip_test() {
+        for entry in fs::read_dir("../programs").unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_none_or(|extension| extension != "ch8") {
+                continue;
+            }
+
+            let program = fs::read(&path).unwrap();
+            let source = Disassembly::new(&program, DEFAULT_START).octo();
+            let assembled = assemble(&source).unwrap_or_else(|error| panic!("{:?}: {}", path, error));
+
+            assert_eq!(assembled.bytes, program, "{:?} should assemble back to itself", path);
+        }
+    }
+}
@@ -0,0 +1,93 @@
+//! Run every program in `programs/` as fast as possible, with and without the decoded instruction cache
+//!
+//! `cargo bench -p chip8_base`
+
+use std::{fs, path::PathBuf, slice::Iter, time::{Duration, Instant}};
+
+use chip8_base::Interpreter;
+
+const PROGRAM_START: usize = 0x200;
+const FRAMES: usize = 300;
+const INSTRUCTIONS_PER_FRAME: usize = 1000;
+
+struct NullRenderer;
+
+impl chip8_traits::Renderer for NullRenderer {
+    fn render(&mut self, _memory: Iter<Vec<u8>>) -> Result<(), chip8_base::Error> {
+        Ok(())
+    }
+}
+
+struct NullKeypad;
+
+impl chip8_traits::Keypad for NullKeypad {
+    fn state(&self) -> [bool; 16] {
+        [false; 16]
+    }
+
+    fn key_state(&self, _key_index: usize) -> bool {
+        false
+    }
+}
+
+/// Deterministic, so both runs of a program take the same path
+struct CountingRandom(u8);
+
+impl chip8_traits::Random for CountingRandom {
+    fn value(&mut self) -> u8 {
+        self.0 = self.0.wrapping_mul(37).wrapping_add(11);
+        self.0
+    }
+}
+
+fn programs() -> Vec<(PathBuf, Vec<u8>)> {
+    let mut result: Vec<(PathBuf, Vec<u8>)> = fs::read_dir("../programs").expect("programs directory")
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|extension| extension == "ch8" || extension == "xo8"))
+        .filter_map(|path| fs::read(&path).ok().map(|program| (path, program)))
+        .collect();
+    result.sort();
+
+    result
+}
+
+/// Instructions executed and the time taken to run `program` for `FRAMES` frames, or until it exits or fails
+fn run(program: &[u8], xo_chip: bool, cache: bool) -> (u64, Duration) {
+    let mut interpreter = Interpreter::new_crate_defaults(NullRenderer, NullKeypad, CountingRandom(0));
+    if xo_chip {
+        interpreter.set_memory_size(chip8_base::memory::XO_CHIP_SIZE);
+        interpreter.set_quirks(chip8_base::Quirks::xo_chip());
+    }
+    interpreter.set_instructions_per_frame(INSTRUCTIONS_PER_FRAME);
+    interpreter.set_instruction_cache_enabled(cache);
+    chip8_traits::Interpreter::load(&mut interpreter, program.to_vec(), PROGRAM_START);
+
+    let start = Instant::now();
+    for _ in 0..FRAMES {
+        if chip8_traits::Interpreter::has_exited(&interpreter) || chip8_traits::Interpreter::update_frame(&mut interpreter).is_err() {
+            break;
+        }
+    }
+
+    (interpreter.instruction_count(), start.elapsed())
+}
+
+fn main() {
+    let mut totals = [(0, Duration::default()); 2];
+
+    for (path, program) in programs() {
+        let xo_chip = path.extension().is_some_and(|extension| extension == "xo8");
+        let (uncached, cached) = (run(&program, xo_chip, false), run(&program, xo_chip, true));
+        assert_eq!(uncached.0, cached.0, "{:?} should run the same instructions with the cache", path);
+
+        for (total, (instructions, elapsed)) in totals.iter_mut().zip([uncached, cached]) {
+            total.0 += instructions;
+            total.1 += elapsed;
+        }
+    }
+
+    let rate = |(instructions, elapsed): (u64, Duration)| instructions as f64 / elapsed.as_secs_f64() / 1_000_000.0;
+    println!("uncached: {} instructions in {:?}, {:.2} M/s", totals[0].0, totals[0].1, rate(totals[0]));
+    println!("cached:   {} instructions in {:?}, {:.2} M/s", totals[1].0, totals[1].1, rate(totals[1]));
+    println!("speedup:  {:.2}x", rate(totals[1]) / rate(totals[0]));
+}
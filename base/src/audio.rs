@@ -0,0 +1,139 @@
+use std::io::{self, Write};
+
+use chip8_traits::interpreter::FRAMES_PER_SECOND;
+
+/// Discards all sound, for front-ends without audio
+#[derive(Default)]
+pub struct NullAudio;
+
+impl chip8_traits::Audio for NullAudio {
+    fn set_playing(&mut self, _playing: bool) -> Result<(), chip8_traits::Error> {
+        Ok(())
+    }
+
+    fn set_pattern(&mut self, _pattern: [u8; 16], _sample_rate: f64) -> Result<(), chip8_traits::Error> {
+        Ok(())
+    }
+}
+
+pub const DEFAULT_OUTPUT_SAMPLE_RATE: u32 = 44100;
+const AMPLITUDE: i16 = i16::MAX / 4;
+const PATTERN_BITS: f64 = 128.0;
+
+/// Renders the buzzer into 16-bit mono PCM samples, one frame at a time, so sound can be checked without speakers
+pub struct PcmAudio {
+    samples: Vec<i16>,
+    output_sample_rate: u32,
+
+    playing: bool,
+    pattern: [u8; 16],
+    pattern_sample_rate: f64,
+    /// Position in the pattern, in bits
+    phase: f64,
+    /// Fraction of a sample carried over between frames
+    remainder: f64,
+}
+
+impl Default for PcmAudio {
+    fn default() -> Self {
+        Self::new(DEFAULT_OUTPUT_SAMPLE_RATE)
+    }
+}
+
+impl PcmAudio {
+    pub fn new(output_sample_rate: u32) -> PcmAudio {
+        PcmAudio {
+            samples: vec![],
+            output_sample_rate,
+
+            playing: false,
+            pattern: crate::sound_timer::DEFAULT_PATTERN,
+            pattern_sample_rate: crate::sound_timer::DEFAULT_SAMPLE_RATE,
+            phase: 0.0,
+            remainder: 0.0,
+        }
+    }
+
+    pub fn samples(&self) -> &[i16] {
+        &self.samples
+    }
+
+    pub fn output_sample_rate(&self) -> u32 {
+        self.output_sample_rate
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    fn pattern_bit(&self) -> bool {
+        let bit = self.phase as usize % PATTERN_BITS as usize;
+        self.pattern[bit / 8] & (0x80 >> (bit % 8)) != 0
+    }
+
+    /// Write the samples so far as a WAV file
+    pub fn write_wav<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let data_size = (self.samples.len() * 2) as u32;
+        let byte_rate = self.output_sample_rate * 2;
+
+        writer.write_all(b"RIFF")?;
+        writer.write_all(&(36 + data_size).to_le_bytes())?;
+        writer.write_all(b"WAVE")?;
+
+        writer.write_all(b"fmt ")?;
+        writer.write_all(&16u32.to_le_bytes())?;
+        writer.write_all(&1u16.to_le_bytes())?; // PCM
+        writer.write_all(&1u16.to_le_bytes())?; // mono
+        writer.write_all(&self.output_sample_rate.to_le_bytes())?;
+        writer.write_all(&byte_rate.to_le_bytes())?;
+        writer.write_all(&2u16.to_le_bytes())?; // block align
+        writer.write_all(&16u16.to_le_bytes())?; // bits per sample
+
+        writer.write_all(b"data")?;
+        writer.write_all(&data_size.to_le_bytes())?;
+        for sample in self.samples.iter() {
+            writer.write_all(&sample.to_le_bytes())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl chip8_traits::Audio for PcmAudio {
+    fn set_playing(&mut self, playing: bool) -> Result<(), chip8_traits::Error> {
+        self.playing = playing;
+        Ok(())
+    }
+
+    fn set_pattern(&mut self, pattern: [u8; 16], sample_rate: f64) -> Result<(), chip8_traits::Error> {
+        self.pattern = pattern;
+        self.pattern_sample_rate = sample_rate;
+        Ok(())
+    }
+
+    fn update_frame(&mut self) -> Result<(), chip8_traits::Error> {
+        let frame_samples = self.output_sample_rate as f64 / FRAMES_PER_SECOND as f64 + self.remainder;
+        let count = frame_samples as usize;
+        self.remainder = frame_samples - count as f64;
+
+        let step = self.pattern_sample_rate / self.output_sample_rate as f64;
+        for _ in 0..count {
+            let sample = {
+                if !self.playing {
+                    0
+                } else if self.pattern_bit() {
+                    AMPLITUDE
+                } else {
+                    -AMPLITUDE
+                }
+            };
+            self.samples.push(sample);
+
+            if self.playing {
+                self.phase = (self.phase + step) % PATTERN_BITS;
+            }
+        }
+
+        Ok(())
+    }
+}
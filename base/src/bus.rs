@@ -1,4 +1,4 @@
-use crate::{DelayTimer, Memory, ProgramCounter, ScreenMemory, SoundTimer, Stack, VariableRegisters, cpu::ExecuteResult};
+use crate::{DelayTimer, Instruction, KeyLatch, Memory, ProgramCounter, Quirks, ScreenMemory, SoundTimer, Stack, VariableRegisters, cpu::ExecuteResult};
 
 // trait Bus {
 //     // fn program_counter(&self) -> &mut ProgramCounter;
@@ -23,23 +23,26 @@ pub struct Bus<'a,
     pub screen_memory: &'a mut ScreenMemory,
     pub variable_registers: &'a mut VariableRegisters,
     pub keypad: &'a Keypad,
+    pub key_latch: &'a mut KeyLatch,
     pub index_register: &'a mut usize,
     pub delay_timer: &'a mut DelayTimer,
     pub sound_timer: &'a mut SoundTimer,
     pub random: &'a mut Random,
+    pub rpl_flags: &'a mut [u8; 16],
+    pub quirks: &'a Quirks,
 }
 
 impl<'a,
     Keypad: chip8_traits::Keypad, 
     Random: chip8_traits::Random
 > Bus<'a, Keypad, Random> {
-    pub fn execute<'b, Instruction: chip8_traits::Instruction>(
-        &'b mut self,
+    pub fn execute(
+        &mut self,
         apply_instruction: bool,
         instruction: Instruction, 
         font_start: usize
-    ) -> ExecuteResult<Instruction> {
-        return crate::cpu::execute(
+    ) -> ExecuteResult {
+        crate::cpu::execute(
             apply_instruction,
             instruction,
             self.program_counter,
@@ -48,11 +51,14 @@ impl<'a,
             self.screen_memory,
             self.variable_registers,
             self.keypad,
+            self.key_latch,
             self.index_register,
             self.delay_timer,
             self.sound_timer,
             self.random,
+            self.rpl_flags,
+            self.quirks,
             font_start
-        );
+        )
     }
 }
\ No newline at end of file
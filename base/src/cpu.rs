@@ -1,14 +1,24 @@
-use crate::{DelayTimer, Memory, ScreenMemory, SoundTimer, ProgramCounter, Stack, VariableRegisters, count16, count8, instruction::{InstructionError}};
+use crate::{DelayTimer, Error, ErrorContext, Instruction, KeyLatch, Memory, Opcode, font::{CHARACTER_SIZE, LARGE_CHARACTER_SIZE, LARGE_FONT_OFFSET}, ScreenMemory, SoundTimer, ProgramCounter, Quirks, Stack, VariableRegisters};
 
+#[derive(Default)]
 pub struct ExecutionState {
-    pub instruction_disassembly: String
+    pub instruction_disassembly: String,
+    /// The program asked to stop running
+    pub exit: bool,
+    /// Nothing more should run until the next frame, after DXYN with the display_wait quirk
+    pub wait_for_frame: bool,
 }
 
-pub type ExecuteResult<Instruction> = std::result::Result<ExecutionState, InstructionError<Instruction>>;
+pub type ExecuteResult = std::result::Result<ExecutionState, Error>;
+
+/// VX or VY past VF, left for the interpreter to give a context
+fn register_error(index: u8) -> Error {
+    Error::RegisterOutOfRange { index, context: ErrorContext::default() }
+}
 
 /// Execute an instruction, or interpret the instruction if apply_instruction == false
+#[allow(clippy::too_many_arguments)]
 pub fn execute<
-    Instruction: chip8_traits::Instruction,
     Keypad: chip8_traits::Keypad, 
     Random: chip8_traits::Random
 > (
@@ -20,365 +30,418 @@ pub fn execute<
     screen_memory: &mut ScreenMemory,
     variable_registers: &mut VariableRegisters,
     keypad: &Keypad,
+    key_latch: &mut KeyLatch,
     index_register: &mut usize,
     delay_timer: &mut DelayTimer,
     sound_timer: &mut SoundTimer,
     random: &mut Random,
+    rpl_flags: &mut [u8; 16],
+    quirks: &Quirks,
     font_start: usize
-) -> ExecuteResult<Instruction> {
-    let count = count8(instruction.w().to_vec());
-
-    match count {
-        0x00 => {
-            let nn = count8(instruction.nn().to_vec());
-            match nn {
-                0xe0 => return clear_screen(apply_instruction, screen_memory),
-                0xee => return pop_stack(apply_instruction, instruction, stack, program_counter),
-                _ => return Err(InstructionError::UnsupportedInstructionError(instruction)) // TODO: 0x0nnn
-            }
-        },
-        0x01 => return jump(apply_instruction, instruction, program_counter),
-        0x02 => return push_stack(apply_instruction, instruction, stack, program_counter),
-        0x03 => return skip_if_equal_value(apply_instruction, instruction, variable_registers, program_counter),
-        0x04 => return skip_if_not_equal_to_value(apply_instruction, instruction, variable_registers, program_counter),
-        0x05 => return skip_if_equal(apply_instruction, instruction, variable_registers, program_counter),
-        0x06 => return set_register(apply_instruction, instruction, variable_registers),
-        0x07 => return add_to_register(apply_instruction, instruction, variable_registers),
-        0x08 => {
-            let n = count8(instruction.n().to_vec());
-            match n {
-                0x00 => return set_x_value_of_y(apply_instruction, instruction, variable_registers),
-                0x01 => return or_x_value_of_y(apply_instruction, instruction, variable_registers),
-                0x02 => return and_x_value_of_y(apply_instruction, instruction, variable_registers),
-                0x03 => return xor_x_value_of_y(apply_instruction, instruction, variable_registers),
-                0x04 => return add_to_x_value_of_y(apply_instruction, instruction, variable_registers),
-                0x05 => return subtract_to_x_value_of_y(apply_instruction, instruction, variable_registers),
-                0x06 => return set_x_right_shifted_y(apply_instruction, instruction, variable_registers),
-                0x07 => return subtract_to_x_value_of_y_reversed(apply_instruction, instruction, variable_registers),
-                0x0e => return set_x_left_shifted_y(apply_instruction, instruction, variable_registers),
-
-                _ => return Err(InstructionError::UnsupportedInstructionError(instruction))
-            }
-        },
-        0x09 => return skip_if_not_equal(apply_instruction, instruction, variable_registers, program_counter),
-        0x0a => return set_index_register(apply_instruction, instruction, index_register),
-        0x0b => return jump_v0(apply_instruction, instruction, program_counter, variable_registers), // TODO: optional BXNN
-        0x0c => return set_register_random(apply_instruction, instruction, variable_registers, random),
-        0x0d => return display(apply_instruction, instruction, index_register, variable_registers, memory, screen_memory),
-        0x0e => {
-            let nn = count8(instruction.nn().to_vec());
-            match nn {
-                0x9e => return skip_if_pressed(apply_instruction, instruction, keypad, variable_registers, program_counter),
-                0xa1 => return skip_if_not_pressed(apply_instruction, instruction, keypad, variable_registers, program_counter),
-                _ => return Err(InstructionError::UnsupportedInstructionError(instruction))
-            }
-        },
-        0x0f => {
-            let nn = count8(instruction.nn().to_vec());
-            match nn {
-                0x07 | 0x15 | 0x18 => return set_timer(apply_instruction, instruction, variable_registers, delay_timer, sound_timer),
-                0x1e => return add_to_index(apply_instruction, instruction, variable_registers, index_register),
-                0x0a => return wait_for_key(apply_instruction, instruction, keypad, variable_registers, program_counter),
-                0x29 => return font_character(apply_instruction, instruction, variable_registers, index_register, font_start),
-                0x33 => return binary_to_decimal(apply_instruction, instruction, variable_registers, memory, index_register),
-                0x55 => return register_to_memory(apply_instruction, instruction, variable_registers, memory, *index_register),
-                0x65 => return memory_to_register(apply_instruction, instruction, variable_registers, memory, *index_register),
-                _ => return Err(InstructionError::UnsupportedInstructionError(instruction))
-            }
-        },
-        _ => return Err(InstructionError::UnsupportedInstructionError(instruction))
+) -> ExecuteResult {
+    let Some(opcode) = instruction.opcode() else {
+        return Err(Error::UnsupportedOpcode(ErrorContext { opcode: instruction.word(), ..ErrorContext::default() })); // TODO: 0x0nnn
+    };
+
+    match opcode {
+        Opcode::ScrollDown { n } => scroll_down(apply_instruction, n, screen_memory),
+        Opcode::ScrollUp { n } => scroll_up(apply_instruction, n, screen_memory),
+        Opcode::Clear => clear_screen(apply_instruction, screen_memory),
+        Opcode::Return => pop_stack(apply_instruction, stack, program_counter, memory),
+        Opcode::ScrollRight => scroll_right(apply_instruction, screen_memory),
+        Opcode::ScrollLeft => scroll_left(apply_instruction, screen_memory),
+        Opcode::Exit => exit(apply_instruction),
+        Opcode::LowResolution => set_high_resolution(apply_instruction, false, screen_memory),
+        Opcode::HighResolution => set_high_resolution(apply_instruction, true, screen_memory),
+        Opcode::Jump { nnn } => jump(apply_instruction, nnn, program_counter),
+        Opcode::Call { nnn } => push_stack(apply_instruction, nnn, stack, program_counter, memory),
+        Opcode::SkipIfEqualImm { x, nn } => skip_if_equal_value(apply_instruction, x, nn, variable_registers, program_counter, memory),
+        Opcode::SkipIfNotEqualImm { x, nn } => skip_if_not_equal_to_value(apply_instruction, x, nn, variable_registers, program_counter, memory),
+        Opcode::SkipIfEqual { x, y } => skip_if_equal(apply_instruction, x, y, variable_registers, program_counter, memory),
+        Opcode::SaveRange { x, y } => register_range_to_memory(apply_instruction, x, y, variable_registers, memory, index_register),
+        Opcode::LoadRange { x, y } => memory_to_register_range(apply_instruction, x, y, variable_registers, memory, index_register),
+        Opcode::SetImm { x, nn } => set_register(apply_instruction, x, nn, variable_registers),
+        Opcode::AddImm { x, nn } => add_to_register(apply_instruction, x, nn, variable_registers),
+        Opcode::Set { x, y } => set_x_value_of_y(apply_instruction, x, y, variable_registers),
+        Opcode::Or { x, y } => or_x_value_of_y(apply_instruction, x, y, variable_registers, quirks),
+        Opcode::And { x, y } => and_x_value_of_y(apply_instruction, x, y, variable_registers, quirks),
+        Opcode::Xor { x, y } => xor_x_value_of_y(apply_instruction, x, y, variable_registers, quirks),
+        Opcode::Add { x, y } => add_to_x_value_of_y(apply_instruction, x, y, variable_registers),
+        Opcode::Sub { x, y } => subtract_to_x_value_of_y(apply_instruction, x, y, variable_registers),
+        Opcode::ShiftRight { x, y } => set_x_right_shifted_y(apply_instruction, x, y, variable_registers, quirks),
+        Opcode::SubReversed { x, y } => subtract_to_x_value_of_y_reversed(apply_instruction, x, y, variable_registers),
+        Opcode::ShiftLeft { x, y } => set_x_left_shifted_y(apply_instruction, x, y, variable_registers, quirks),
+        Opcode::SkipIfNotEqual { x, y } => skip_if_not_equal(apply_instruction, x, y, variable_registers, program_counter, memory),
+        Opcode::SetIndex { nnn } => set_index_register(apply_instruction, nnn, index_register),
+        Opcode::JumpOffset { x, nnn } => jump_v0(apply_instruction, nnn, x, program_counter, variable_registers, quirks),
+        Opcode::Random { x, nn } => set_register_random(apply_instruction, x, nn, variable_registers, random),
+        Opcode::Draw { x, y, n } => display(apply_instruction, x, y, n, index_register, variable_registers, memory, screen_memory, quirks),
+        Opcode::SkipIfKey { x } => skip_if_pressed(apply_instruction, x, keypad, variable_registers, program_counter, memory),
+        Opcode::SkipIfNotKey { x } => skip_if_not_pressed(apply_instruction, x, keypad, variable_registers, program_counter, memory),
+        Opcode::SetIndexLong => set_index_register_long(apply_instruction, instruction.extension(), index_register),
+        Opcode::SelectPlanes { n } => select_planes(apply_instruction, n, screen_memory),
+        Opcode::LoadAudio => load_audio_pattern(apply_instruction, memory, index_register, sound_timer),
+        Opcode::GetDelay { x } => get_delay_timer(apply_instruction, x, variable_registers, delay_timer),
+        Opcode::WaitKey { x } => wait_for_key(apply_instruction, x, keypad, key_latch, variable_registers, program_counter, quirks),
+        Opcode::SetDelay { x } => set_delay_timer(apply_instruction, x, variable_registers, delay_timer),
+        Opcode::SetSound { x } => set_sound_timer(apply_instruction, x, variable_registers, sound_timer),
+        Opcode::AddIndex { x } => add_to_index(apply_instruction, x, variable_registers, index_register, quirks),
+        Opcode::Font { x } => font_character(apply_instruction, x, variable_registers, index_register, font_start),
+        Opcode::LargeFont { x } => large_font_character(apply_instruction, x, variable_registers, index_register, font_start),
+        Opcode::Bcd { x } => binary_to_decimal(apply_instruction, x, variable_registers, memory, index_register),
+        Opcode::SetPitch { x } => set_pitch(apply_instruction, x, variable_registers, sound_timer),
+        Opcode::Save { x } => register_to_memory(apply_instruction, x, variable_registers, memory, index_register, quirks),
+        Opcode::Load { x } => memory_to_register(apply_instruction, x, variable_registers, memory, index_register, quirks),
+        Opcode::SaveFlags { x } => register_to_flags(apply_instruction, x, variable_registers, rpl_flags),
+        Opcode::LoadFlags { x } => flags_to_register(apply_instruction, x, variable_registers, rpl_flags),
     }
 }
 
 /// Skip if value in VX is equal to NN
-fn skip_if_equal_value<
-    Instruction: chip8_traits::Instruction
->(
+fn skip_if_equal_value(
     apply_instruction: bool,
-    instruction: Instruction,
+    x: u8,
+    value: u8,
     variable_registers: &mut VariableRegisters,
-    program_counter: &mut ProgramCounter
-) -> ExecuteResult<Instruction> {
-    let x = count8(instruction.x().to_vec());
-    let value = count8(instruction.nn().to_vec());
-
+    program_counter: &mut ProgramCounter,
+    memory: &Memory
+) -> ExecuteResult {
     if apply_instruction {
         match variable_registers.get(x) {
             Some(register_value) => {
                 if value == register_value {
-                    (program_counter as &mut dyn chip8_traits::ProgramCounter).skip();
+                    (program_counter as &mut dyn chip8_traits::ProgramCounter).skip(memory)?;
                 }
             },
-            None => return Err(InstructionError::InstructionExecuteError(instruction)),
+            None => return Err(register_error(x)),
         }
     }
 
     Ok(ExecutionState {
-        instruction_disassembly: format!("skip if {:#04x} == V{}", value, x)
+        instruction_disassembly: format!("skip if {:#04x} == V{}", value, x),
+        ..ExecutionState::default()
     })
 }
 
 /// Skip if value in VX is not equal to NN
-fn skip_if_not_equal_to_value<
-    Instruction: chip8_traits::Instruction
->(
+fn skip_if_not_equal_to_value(
     apply_instruction: bool,
-    instruction: Instruction,
+    x: u8,
+    value: u8,
     variable_registers: &mut VariableRegisters,
-    program_counter: &mut ProgramCounter
-) -> ExecuteResult<Instruction> {
-    let x = count8(instruction.x().to_vec());
-    let value = count8(instruction.nn().to_vec());
-
+    program_counter: &mut ProgramCounter,
+    memory: &Memory
+) -> ExecuteResult {
     if apply_instruction {
         match variable_registers.get(x) {
             Some(register_value) => {
                 if value != register_value {
-                    (program_counter as &mut dyn chip8_traits::ProgramCounter).skip();
+                    (program_counter as &mut dyn chip8_traits::ProgramCounter).skip(memory)?;
                 }
             },
-            None => return Err(InstructionError::InstructionExecuteError(instruction)),
+            None => return Err(register_error(x)),
         }
     }
 
     Ok(ExecutionState {
-        instruction_disassembly: format!("skip if {:#04x} != V{}", value, x)
+        instruction_disassembly: format!("skip if {:#04x} != V{}", value, x),
+        ..ExecutionState::default()
     })
 }
 
-fn skip_if_equal<
-    Instruction: chip8_traits::Instruction
->(
+fn skip_if_equal(
     apply_instruction: bool,
-    instruction: Instruction,
+    x: u8,
+    y: u8,
     variable_registers: &mut VariableRegisters,
-    program_counter: &mut ProgramCounter
-) -> ExecuteResult<Instruction> {
-    let x = count8(instruction.x().to_vec());
-    let y = count8(instruction.y().to_vec());
-
+    program_counter: &mut ProgramCounter,
+    memory: &Memory
+) -> ExecuteResult {
     if apply_instruction {
         match variable_registers.get(x) {
             Some(x_value) => {
                 match variable_registers.get(y) {
                     Some(y_value) => {
                         if x_value == y_value {
-                            (program_counter as &mut dyn chip8_traits::ProgramCounter).skip();
+                            (program_counter as &mut dyn chip8_traits::ProgramCounter).skip(memory)?;
                         }
                         
                     },
-                    None => return Err(InstructionError::InstructionExecuteError(instruction)),
+                    None => return Err(register_error(y)),
                 }
             },
-            None => return Err(InstructionError::InstructionExecuteError(instruction)),
+            None => return Err(register_error(x)),
         }
     }
 
-    return Ok(ExecutionState {
-        instruction_disassembly: format!("skip if V{} == V{}", x, y)
+    Ok(ExecutionState {
+        instruction_disassembly: format!("skip if V{} == V{}", x, y),
+        ..ExecutionState::default()
     })
 }
 
-fn skip_if_not_equal<
-    Instruction: chip8_traits::Instruction
->(
+fn skip_if_not_equal(
     apply_instruction: bool,
-    instruction: Instruction,
+    x: u8,
+    y: u8,
     variable_registers: &mut VariableRegisters,
-    program_counter: &mut ProgramCounter
-) -> ExecuteResult<Instruction> {
-    let x = count8(instruction.x().to_vec());
-    let y = count8(instruction.y().to_vec());
-
+    program_counter: &mut ProgramCounter,
+    memory: &Memory
+) -> ExecuteResult {
     if apply_instruction {
         match variable_registers.get(x) {
             Some(x_value) => {
                 match variable_registers.get(y) {
                     Some(y_value) => {
                         if x_value != y_value {
-                            (program_counter as &mut dyn chip8_traits::ProgramCounter).skip();
+                            (program_counter as &mut dyn chip8_traits::ProgramCounter).skip(memory)?;
                         }
                         
                     },
-                    None => return Err(InstructionError::InstructionExecuteError(instruction)),
+                    None => return Err(register_error(y)),
                 }
             },
-            None => return Err(InstructionError::InstructionExecuteError(instruction)),
+            None => return Err(register_error(x)),
         }
     }
 
-    return Ok(ExecutionState {
-        instruction_disassembly: format!("skip if V{} == V{}", x, y)
+    Ok(ExecutionState {
+        instruction_disassembly: format!("skip if V{} == V{}", x, y),
+        ..ExecutionState::default()
     })
 }
 
 /// 00E0 - Sets entire screen memory to 0x00
 fn clear_screen<
-    Instruction: chip8_traits::Instruction,
     ScreenMemory: chip8_traits::ScreenMemory
-> (apply_instruction: bool, screen_memory: &mut ScreenMemory) -> ExecuteResult<Instruction> {
+> (apply_instruction: bool, screen_memory: &mut ScreenMemory) -> ExecuteResult {
     if apply_instruction {
         screen_memory.clear();
     }
 
     Ok(ExecutionState {
-        instruction_disassembly: "clear screen".to_string()
+        instruction_disassembly: "clear screen".to_string(),
+        ..ExecutionState::default()
     })
 }
 
-/// Pushes the location after the "Push Stack" instruction into the stack and sets the program counter to the location NNN 
-fn push_stack<
-    Instruction: chip8_traits::Instruction,
-    Stack: chip8_traits::Stack
-> (apply_instruction: bool, instruction: Instruction, stack: &mut Stack, program_counter: &mut ProgramCounter) -> ExecuteResult<Instruction> {
-    let new_position = count16(instruction.nnn().to_vec());
+/// 00CN - Scroll the screen down N rows
+fn scroll_down<
+    ScreenMemory: chip8_traits::ScreenMemory
+> (apply_instruction: bool, n: u8, screen_memory: &mut ScreenMemory) -> ExecuteResult {
+    if apply_instruction {
+        screen_memory.scroll_down(n as usize);
+    }
+
+    Ok(ExecutionState {
+        instruction_disassembly: format!("scroll down {}", n),
+        ..ExecutionState::default()
+    })
+}
+
+/// 00DN - Scroll the screen up N rows
+fn scroll_up<
+    ScreenMemory: chip8_traits::ScreenMemory
+> (apply_instruction: bool, n: u8, screen_memory: &mut ScreenMemory) -> ExecuteResult {
+    if apply_instruction {
+        screen_memory.scroll_up(n as usize);
+    }
+
+    Ok(ExecutionState {
+        instruction_disassembly: format!("scroll up {}", n),
+        ..ExecutionState::default()
+    })
+}
+
+/// 00FB - Scroll the screen right 4 columns
+fn scroll_right<
+    ScreenMemory: chip8_traits::ScreenMemory
+> (apply_instruction: bool, screen_memory: &mut ScreenMemory) -> ExecuteResult {
+    if apply_instruction {
+        screen_memory.scroll_right(4);
+    }
+
+    Ok(ExecutionState {
+        instruction_disassembly: "scroll right 4".to_string(),
+        ..ExecutionState::default()
+    })
+}
+
+/// 00FC - Scroll the screen left 4 columns
+fn scroll_left<
+    ScreenMemory: chip8_traits::ScreenMemory
+> (apply_instruction: bool, screen_memory: &mut ScreenMemory) -> ExecuteResult {
+    if apply_instruction {
+        screen_memory.scroll_left(4);
+    }
+
+    Ok(ExecutionState {
+        instruction_disassembly: "scroll left 4".to_string(),
+        ..ExecutionState::default()
+    })
+}
+
+/// 00FD - Stop running the program
+fn exit(apply_instruction: bool) -> ExecuteResult {
+    Ok(ExecutionState {
+        instruction_disassembly: "exit".to_string(),
+        exit: apply_instruction,
+        ..ExecutionState::default()
+    })
+}
 
+/// 00FE / 00FF - Switch to the low (64x32) or high (128x64) resolution
+fn set_high_resolution<
+    ScreenMemory: chip8_traits::ScreenMemory
+> (apply_instruction: bool, high_resolution: bool, screen_memory: &mut ScreenMemory) -> ExecuteResult {
     if apply_instruction {
-        stack.push((program_counter as &mut dyn chip8_traits::ProgramCounter).get_position());
+        screen_memory.set_high_resolution(high_resolution);
+    }
+
+    Ok(ExecutionState {
+        instruction_disassembly: {
+            if high_resolution {
+                "high resolution".to_string()
+            } else {
+                "low resolution".to_string()
+            }
+        },
+        ..ExecutionState::default()
+    })
+}
+
+/// Pushes the location after the "Push Stack" instruction into the stack and sets the program counter to the location NNN 
+fn push_stack(apply_instruction: bool, new_position: u16, stack: &mut Stack, program_counter: &mut ProgramCounter, memory: &mut Memory) -> ExecuteResult {
+    if apply_instruction {
+        let return_address = (program_counter as &mut dyn chip8_traits::ProgramCounter).get_position();
+        chip8_traits::Stack::push(stack, return_address)?;
+        if let Some(location) = stack.location(stack.len() - 1) {
+            (memory as &mut dyn chip8_traits::Memory).set(location, (return_address >> 8) as u8)?;
+            (memory as &mut dyn chip8_traits::Memory).set(location + 1, return_address as u8)?;
+        }
         (program_counter as &mut dyn chip8_traits::ProgramCounter).set_position(new_position as usize);
     }
 
     Ok(ExecutionState {
-        instruction_disassembly: format!("push stack and jump to {:#06x}", new_position) 
+        instruction_disassembly: format!("push stack and jump to {:#06x}", new_position),
+        ..ExecutionState::default()
     })
 }
 
 /// Sets the program counter to the location on the top of the stack and removes it from the stack
-fn pop_stack<
-    Instruction: chip8_traits::Instruction,
-    Stack: chip8_traits::Stack
-> (apply_instruction: bool, instruction: Instruction, stack: &mut Stack, program_counter: &mut ProgramCounter) -> ExecuteResult<Instruction> {
-    if apply_instruction {
-        let result = stack.pop();
-        match result {
-            Some(value) => {
-                (program_counter as &mut dyn chip8_traits::ProgramCounter).set_position(value);
-            }
-            None => return Err(InstructionError::InstructionExecuteError(instruction))
+fn pop_stack(apply_instruction: bool, stack: &mut Stack, program_counter: &mut ProgramCounter, memory: &Memory) -> ExecuteResult {
+    if apply_instruction {
+        let location = stack.len().checked_sub(1).and_then(|level| stack.location(level));
+        let mut value = chip8_traits::Stack::pop(stack)?;
+        // The program may have changed the return address where it's kept in memory
+        if let Some(location) = location {
+            let memory = memory as &dyn chip8_traits::Memory;
+            value = (memory.get(location)? as usize) << 8 | memory.get(location + 1)? as usize;
         }
+        (program_counter as &mut dyn chip8_traits::ProgramCounter).set_position(value);
     }
 
     Ok(ExecutionState {
-        instruction_disassembly: format!("pop stack") 
+        instruction_disassembly: "pop stack".to_string(),
+        ..ExecutionState::default()
     })
 }
 
 /// Set program counter to NNN
-fn jump<
-    Instruction: chip8_traits::Instruction, 
->(apply_instruction: bool, instruction: Instruction, program_counter: &mut ProgramCounter) -> ExecuteResult<Instruction>  
+fn jump(apply_instruction: bool, nnn: u16, program_counter: &mut ProgramCounter) -> ExecuteResult  
 {
-    let nnn = count16(instruction.nnn().to_vec());
     if apply_instruction {
         (program_counter as &mut dyn chip8_traits::ProgramCounter).set_position(nnn as usize);
     }
 
     Ok(ExecutionState {
-        instruction_disassembly: format!("jump to {:#06x}", nnn) 
+        instruction_disassembly: format!("jump to {:#06x}", nnn),
+        ..ExecutionState::default()
     })
 } 
 
 /// Set to VX value NN
-fn set_register<
-    Instruction: chip8_traits::Instruction
->(apply_instruction: bool, instruction: Instruction, variable_registers: &mut VariableRegisters) -> ExecuteResult<Instruction> {
-    let index = count8(instruction.x().to_vec());
-    let value = count8(instruction.nn().to_vec());
-
+fn set_register(apply_instruction: bool, index: u8, value: u8, variable_registers: &mut VariableRegisters) -> ExecuteResult {
     if apply_instruction {
         match variable_registers.set(index, value) {
             Ok(_) => {},
-            Err(_) => return Err(InstructionError::InstructionExecuteError(instruction))
+            Err(_) => return Err(register_error(index))
         }
     }
 
     Ok(ExecutionState {
-        instruction_disassembly: format!("V{} = {:#04x} / {}", index, value, value) 
+        instruction_disassembly: format!("V{} = {:#04x} / {}", index, value, value),
+        ..ExecutionState::default()
     })
 }
 
 /// Add to VX value NN
-fn add_to_register<
-    Instruction: chip8_traits::Instruction
->(apply_instruction: bool, instruction: Instruction, variable_registers: &mut VariableRegisters) -> ExecuteResult<Instruction> {
-    let x = count8(instruction.x().to_vec());
-    let value = count8(instruction.nn().to_vec());
-
+fn add_to_register(apply_instruction: bool, x: u8, value: u8, variable_registers: &mut VariableRegisters) -> ExecuteResult {
     if apply_instruction {
         match variable_registers.get(x) {
             Some(x_value) => {
                 let new_value = x_value.wrapping_add(value);
                 match variable_registers.set(x, new_value) {
                     Ok(_) => {},
-                    Err(_) => return Err(InstructionError::InstructionExecuteError(instruction))
+                    Err(_) => return Err(register_error(x))
                 }
             },
-            None => return Err(InstructionError::InstructionExecuteError(instruction))
+            None => return Err(register_error(x))
         }
     }
     Ok(ExecutionState {
-        instruction_disassembly: format!("V{} += {:#04x} / {} wrapped", x, value, value) 
+        instruction_disassembly: format!("V{} += {:#04x} / {} wrapped", x, value, value),
+        ..ExecutionState::default()
     })
 }
 
-fn set_x_value_of_y<
-    Instruction: chip8_traits::Instruction
-> (apply_instruction: bool, instruction: Instruction, variable_registers: &mut VariableRegisters) -> ExecuteResult<Instruction> {
-    let x = count8(instruction.x().to_vec());
-    let y = count8(instruction.y().to_vec());
-
+fn set_x_value_of_y(apply_instruction: bool, x: u8, y: u8, variable_registers: &mut VariableRegisters) -> ExecuteResult {
     if apply_instruction {
 
         match variable_registers.get(y) {
             Some(y_value) => {
                 match variable_registers.set(x, y_value) {
                     Ok(_) => {},
-                    Err(_) => return Err(InstructionError::InstructionExecuteError(instruction))
+                    Err(_) => return Err(register_error(x))
                 }
             },
-            None => return Err(InstructionError::InstructionExecuteError(instruction))
+            None => return Err(register_error(y))
         }
     }
 
     Ok(ExecutionState {
-        instruction_disassembly: format!("V{} = V{}", x, y) 
+        instruction_disassembly: format!("V{} = V{}", x, y),
+        ..ExecutionState::default()
     })
 }
 
-fn or_x_value_of_y<
-    Instruction: chip8_traits::Instruction
->(apply_instruction: bool, instruction: Instruction, variable_registers: &mut VariableRegisters) -> ExecuteResult<Instruction> {
-    let x = count8(instruction.x().to_vec());
-    let y = count8(instruction.y().to_vec());
-
+fn or_x_value_of_y(apply_instruction: bool, x: u8, y: u8, variable_registers: &mut VariableRegisters, quirks: &Quirks) -> ExecuteResult {
     if apply_instruction {
-        guard!(let Some(y_value) = variable_registers.get(y) else {
-            return Err(InstructionError::InstructionExecuteError(instruction));
-        });
+        let Some(y_value) = variable_registers.get(y) else {
+            return Err(register_error(y));
+        };
     
-        guard!(let Some(x_value) = variable_registers.get(x) else {
-            return Err(InstructionError::InstructionExecuteError(instruction));
-        });
+        let Some(x_value) = variable_registers.get(x) else {
+            return Err(register_error(x));
+        };
         let new_value = x_value | y_value;
     
         match variable_registers.set(x, new_value) {
             Ok(_) => {},
-            Err(_) => return Err(InstructionError::InstructionExecuteError(instruction))
+            Err(_) => return Err(register_error(x))
         }
+
+        reset_flag(variable_registers, quirks)?;
     }
     
     Ok(ExecutionState {
-        instruction_disassembly: format!("V{} = V{} OR V{}", x, x, y) 
+        instruction_disassembly: format!("V{} = V{} OR V{}", x, x, y),
+        ..ExecutionState::default()
     })
 }
 
 
-fn and_x_value_of_y<
-    Instruction: chip8_traits::Instruction
->(apply_instruction: bool, instruction: Instruction, variable_registers: &mut VariableRegisters) -> ExecuteResult<Instruction> {
-    let x = count8(instruction.x().to_vec());
-    let y = count8(instruction.y().to_vec());
-
+fn and_x_value_of_y(apply_instruction: bool, x: u8, y: u8, variable_registers: &mut VariableRegisters, quirks: &Quirks) -> ExecuteResult {
     if apply_instruction {
         match variable_registers.get(y) {
             Some(y_value) => {
@@ -388,55 +451,60 @@ fn and_x_value_of_y<
             
                         match variable_registers.set(x, new_value) {
                             Ok(_) => {},
-                            Err(_) => return Err(InstructionError::InstructionExecuteError(instruction))
+                            Err(_) => return Err(register_error(x))
                         }
+
+                        reset_flag(variable_registers, quirks)?;
                     },
-                    None => return Err(InstructionError::InstructionExecuteError(instruction))
+                    None => return Err(register_error(x))
                 }
             },
-            None => return Err(InstructionError::InstructionExecuteError(instruction))
+            None => return Err(register_error(y))
         }
     }
 
     Ok(ExecutionState {
-        instruction_disassembly: format!("V{} = V{} AND V{}", x, x, y) 
+        instruction_disassembly: format!("V{} = V{} AND V{}", x, x, y),
+        ..ExecutionState::default()
     })
 }
 
-fn xor_x_value_of_y<
-    Instruction: chip8_traits::Instruction
->(apply_instruction: bool, instruction: Instruction, variable_registers: &mut VariableRegisters) -> ExecuteResult<Instruction> {
-    let x = count8(instruction.x().to_vec());
-    let y = count8(instruction.y().to_vec());
-
+fn xor_x_value_of_y(apply_instruction: bool, x: u8, y: u8, variable_registers: &mut VariableRegisters, quirks: &Quirks) -> ExecuteResult {
     if apply_instruction {
-        guard!(let Some(x_value) = variable_registers.get(x) else {
-            return Err(InstructionError::InstructionExecuteError(instruction));
-        });
+        let Some(x_value) = variable_registers.get(x) else {
+            return Err(register_error(x));
+        };
     
-        guard!(let Some(y_value) = variable_registers.get(y) else {
-            return Err(InstructionError::InstructionExecuteError(instruction));
-        });
+        let Some(y_value) = variable_registers.get(y) else {
+            return Err(register_error(y));
+        };
     
         let new_value = x_value ^ y_value;
             
         match variable_registers.set(x, new_value) {
             Ok(_) => {},
-            Err(_) => return Err(InstructionError::InstructionExecuteError(instruction))
+            Err(_) => return Err(register_error(x))
         }
+
+        reset_flag(variable_registers, quirks)?;
     }
 
     Ok(ExecutionState {
-        instruction_disassembly: format!("V{} = V{} XOR V{}", x, x, y) 
+        instruction_disassembly: format!("V{} = V{} XOR V{}", x, x, y),
+        ..ExecutionState::default()
     })
 }
 
-fn add_to_x_value_of_y<
-    Instruction: chip8_traits::Instruction
->(apply_instruction: bool, instruction: Instruction, variable_registers: &mut VariableRegisters) -> ExecuteResult<Instruction> {
-    let x = count8(instruction.x().to_vec());
-    let y = count8(instruction.y().to_vec());
+/// Reset VF after a logical operation when the vf_reset quirk is enabled
+fn reset_flag(variable_registers: &mut VariableRegisters, quirks: &Quirks) -> Result<(), Error> {
+    if quirks.vf_reset && variable_registers.set(0x0f, 0).is_err() {
+        return Err(register_error(0x0f));
+    }
 
+    Ok(())
+}
+
+fn add_to_x_value_of_y(apply_instruction: bool, x: u8, y: u8, variable_registers: &mut VariableRegisters) -> ExecuteResult {
     if apply_instruction {
         match variable_registers.get(y) {
             Some(y_value) => {
@@ -448,36 +516,37 @@ fn add_to_x_value_of_y<
                                 Ok(_) => {
                                     match variable_registers.set(0x0f, 1) {
                                         Ok(_) => {},
-                                        Err(_) => return Err(InstructionError::InstructionExecuteError(instruction))
+                                        Err(_) => return Err(register_error(0x0f))
                                     }
                                 },
-                                Err(_) => return Err(InstructionError::InstructionExecuteError(instruction))
+                                Err(_) => return Err(register_error(x))
                             }
                         } else {
-                            match variable_registers.set(0x0f, 0) {
-                                Ok(_) => {},
-                                Err(_) => return Err(InstructionError::InstructionExecuteError(instruction))
+                            match variable_registers.set(x, result.0) {
+                                Ok(_) => {
+                                    match variable_registers.set(0x0f, 0) {
+                                        Ok(_) => {},
+                                        Err(_) => return Err(register_error(0x0f))
+                                    }
+                                },
+                                Err(_) => return Err(register_error(x))
                             }
                         }
                     },
-                    None => return Err(InstructionError::InstructionExecuteError(instruction))
+                    None => return Err(register_error(x))
                 }
             },
-            None => return Err(InstructionError::InstructionExecuteError(instruction))
+            None => return Err(register_error(y))
         }
     }
 
     Ok(ExecutionState {
-        instruction_disassembly: format!("V{} += V{}, overflow in VF", x, y) 
+        instruction_disassembly: format!("V{} += V{}, overflow in VF", x, y),
+        ..ExecutionState::default()
     })
 }
 
-fn subtract_to_x_value_of_y<
-    Instruction: chip8_traits::Instruction
->(apply_instruction: bool, instruction: Instruction, variable_registers: &mut VariableRegisters) -> ExecuteResult<Instruction> {
-    let x = count8(instruction.x().to_vec());
-    let y = count8(instruction.y().to_vec());
-
+fn subtract_to_x_value_of_y(apply_instruction: bool, x: u8, y: u8, variable_registers: &mut VariableRegisters) -> ExecuteResult {
     if apply_instruction {
         match variable_registers.get(y) {
             Some(y_value) => {
@@ -489,40 +558,49 @@ fn subtract_to_x_value_of_y<
                                 Ok(_) => {
                                     match variable_registers.set(0x0f, 0) {
                                         Ok(_) => {},
-                                        Err(_) => return Err(InstructionError::InstructionExecuteError(instruction))
+                                        Err(_) => return Err(register_error(0x0f))
                                     }
                                 },
-                                Err(_) => return Err(InstructionError::InstructionExecuteError(instruction))
+                                Err(_) => return Err(register_error(x))
                             }
                         } else {
-                            match variable_registers.set(0x0f, 1) {
-                                Ok(_) => {},
-                                Err(_) => return Err(InstructionError::InstructionExecuteError(instruction))
+                            match variable_registers.set(x, result.0) {
+                                Ok(_) => {
+                                    match variable_registers.set(0x0f, 1) {
+                                        Ok(_) => {},
+                                        Err(_) => return Err(register_error(0x0f))
+                                    }
+                                },
+                                Err(_) => return Err(register_error(x))
                             }
                         }
                     },
-                    None => return Err(InstructionError::InstructionExecuteError(instruction))
+                    None => return Err(register_error(x))
                 }
             },
-            None => return Err(InstructionError::InstructionExecuteError(instruction))
+            None => return Err(register_error(y))
         }
     }
 
     Ok(ExecutionState {
-        instruction_disassembly: format!("V{} -= V{}, underflow in VF", x, y) 
+        instruction_disassembly: format!("V{} -= V{}, underflow in VF", x, y),
+        ..ExecutionState::default()
     })
 }
 
-fn set_x_right_shifted_y<
-    Instruction: chip8_traits::Instruction
->(apply_instruction: bool, instruction: Instruction, variable_registers: &mut VariableRegisters) -> ExecuteResult<Instruction> {
-    let x = count8(instruction.x().to_vec());
-    let y = count8(instruction.y().to_vec());
+fn set_x_right_shifted_y(apply_instruction: bool, x: u8, y: u8, variable_registers: &mut VariableRegisters, quirks: &Quirks) -> ExecuteResult {
+    let y = {
+        if quirks.shift {
+            x
+        } else {
+            y
+        }
+    };
 
     if apply_instruction {
-        guard!(let Some(y_value) = variable_registers.get(y) else {
-            return Err(InstructionError::InstructionExecuteError(instruction));
-        });
+        let Some(y_value) = variable_registers.get(y) else {
+            return Err(register_error(y));
+        };
     
         let flag = {
             if y_value & 0x01 > 0 {
@@ -533,29 +611,21 @@ fn set_x_right_shifted_y<
         };
     
         let y_value = y_value >> 1;
-        // TODO: clarify or make an option to assign back shifted to original
-        // if let Err(_) = variable_registers.set(y, y_value) {
-        //     return Err(InstructionError::InstructionExecuteError(instruction));
-        // }
-        if let Err(_) = variable_registers.set(x, y_value) {
-            return Err(InstructionError::InstructionExecuteError(instruction));
+        if variable_registers.set(x, y_value).is_err() {
+            return Err(register_error(x));
         }
-        if let Err(_) = variable_registers.set(0x0f, flag) {
-            return Err(InstructionError::InstructionExecuteError(instruction));
+        if variable_registers.set(0x0f, flag).is_err() {
+            return Err(register_error(0x0f));
         }
     }
 
     Ok(ExecutionState {
-        instruction_disassembly: format!("V{} = V{} >> 1, overflow in VF", x, y) 
+        instruction_disassembly: format!("V{} = V{} >> 1, overflow in VF", x, y),
+        ..ExecutionState::default()
     })
 }
 
-fn subtract_to_x_value_of_y_reversed<
-    Instruction: chip8_traits::Instruction
->(apply_instruction: bool, instruction: Instruction, variable_registers: &mut VariableRegisters) -> ExecuteResult<Instruction> {
-    let x = count8(instruction.x().to_vec());
-    let y = count8(instruction.y().to_vec());
-
+fn subtract_to_x_value_of_y_reversed(apply_instruction: bool, x: u8, y: u8, variable_registers: &mut VariableRegisters) -> ExecuteResult {
     if apply_instruction {
         match variable_registers.get(y) {
             Some(y_value) => {
@@ -567,48 +637,52 @@ fn subtract_to_x_value_of_y_reversed<
                                 Ok(_) => {
                                     match variable_registers.set(0x0f, 0) {
                                         Ok(_) => {},
-                                        Err(_) => return Err(InstructionError::InstructionExecuteError(instruction))
+                                        Err(_) => return Err(register_error(0x0f))
                                     }
                                 },
-                                Err(_) => return Err(InstructionError::InstructionExecuteError(instruction))
+                                Err(_) => return Err(register_error(x))
                             }
                         } else {
-                            match variable_registers.set(x, y_value) {
+                            match variable_registers.set(x, result.0) {
                                 Ok(_) => {
                                     match variable_registers.set(0x0f, 1) {
                                         Ok(_) => {},
-                                        Err(_) => return Err(InstructionError::InstructionExecuteError(instruction))
+                                        Err(_) => return Err(register_error(0x0f))
                                     }
                                 },
-                                Err(_) => return Err(InstructionError::InstructionExecuteError(instruction))
+                                Err(_) => return Err(register_error(x))
                             }
                         }
                     },
-                    None => return Err(InstructionError::InstructionExecuteError(instruction))
+                    None => return Err(register_error(x))
                 }
             },
-            None => return Err(InstructionError::InstructionExecuteError(instruction))
+            None => return Err(register_error(y))
         }
     }
 
     Ok(ExecutionState {
-        instruction_disassembly: format!("V{} = V{} - V{}, underflow in VF", x, y, x) 
+        instruction_disassembly: format!("V{} = V{} - V{}, underflow in VF", x, y, x),
+        ..ExecutionState::default()
     })
 }
 
-fn set_x_left_shifted_y<
-    Instruction: chip8_traits::Instruction
->(apply_instruction: bool, instruction: Instruction, variable_registers: &mut VariableRegisters) -> ExecuteResult<Instruction> {
-    let x = count8(instruction.x().to_vec());
-    let y = count8(instruction.y().to_vec());
+fn set_x_left_shifted_y(apply_instruction: bool, x: u8, y: u8, variable_registers: &mut VariableRegisters, quirks: &Quirks) -> ExecuteResult {
+    let y = {
+        if quirks.shift {
+            x
+        } else {
+            y
+        }
+    };
 
     if apply_instruction {
-        guard!(let Some(y_value) = variable_registers.get(y) else {
-            return Err(InstructionError::InstructionExecuteError(instruction));
-        });
+        let Some(y_value) = variable_registers.get(y) else {
+            return Err(register_error(y));
+        };
     
         let flag = {
-            if y_value & 0x0f > 0 {
+            if y_value & 0x80 > 0 {
                 1
             } else {
                 0
@@ -616,353 +690,518 @@ fn set_x_left_shifted_y<
         };
     
         let y_value = y_value << 1;
-        // TODO: clarify or make an option to assign back shifted to original
-        // if let Err(_) = variable_registers.set(y, y_value) {
-        //     return Err(InstructionError::InstructionExecuteError(instruction));
-        // }
-        if let Err(_) = variable_registers.set(x, y_value) {
-            return Err(InstructionError::InstructionExecuteError(instruction));
+        if variable_registers.set(x, y_value).is_err() {
+            return Err(register_error(x));
         }
-        if let Err(_) = variable_registers.set(0x0f, flag) {
-            return Err(InstructionError::InstructionExecuteError(instruction));
+        if variable_registers.set(0x0f, flag).is_err() {
+            return Err(register_error(0x0f));
         }
     }
 
     Ok(ExecutionState {
-        instruction_disassembly: format!("V{} = V{} << 1, overflow in VF", x, y) 
+        instruction_disassembly: format!("V{} = V{} << 1, overflow in VF", x, y),
+        ..ExecutionState::default()
     })
 }
 
-fn set_index_register<
-    Instruction: chip8_traits::Instruction
->(apply_instruction: bool, instruction: Instruction, index_register: &mut usize) -> ExecuteResult<Instruction> {
-    let value = count16(chip8_traits::Instruction::nnn(&instruction).to_vec());
+fn set_index_register(apply_instruction: bool, value: u16, index_register: &mut usize) -> ExecuteResult {
     if apply_instruction {
         (*index_register) = value as usize;
     }
 
     Ok(ExecutionState {
-        instruction_disassembly: format!("I = {:#06x}", value) 
+        instruction_disassembly: format!("I = {:#06x}", value),
+        ..ExecutionState::default()
     })
 }
 
-fn jump_v0<
-    Instruction: chip8_traits::Instruction
->(apply_instruction: bool, instruction: Instruction, program_counter: &mut ProgramCounter, variable_registers: &VariableRegisters) -> ExecuteResult<Instruction> {
-    let value = count16(chip8_traits::Instruction::nnn(&instruction).to_vec());
+/// F000 NNNN - Set the index register to the 16-bit address in the following word
+fn set_index_register_long(apply_instruction: bool, value: u16, index_register: &mut usize) -> ExecuteResult {
+    if apply_instruction {
+        (*index_register) = value as usize;
+    }
+
+    Ok(ExecutionState {
+        instruction_disassembly: format!("I = {:#06x}", value),
+        ..ExecutionState::default()
+    })
+}
+
+/// Set program counter to NNN + V0, or XNN + VX with the jump quirk
+fn jump_v0(apply_instruction: bool, value: u16, x: u8, program_counter: &mut ProgramCounter, variable_registers: &VariableRegisters, quirks: &Quirks) -> ExecuteResult {
+    let x = {
+        if quirks.jump {
+            x
+        } else {
+            0
+        }
+    };
 
     if apply_instruction {
-        guard!(let Some(x_value) = variable_registers.get(0) else {
-            return Err(InstructionError::InstructionExecuteError(instruction));
-        });
+        let Some(x_value) = variable_registers.get(x) else {
+            return Err(register_error(x));
+        };
     
         (program_counter as &mut dyn chip8_traits::ProgramCounter).set_position(value as usize + x_value as usize);
     }
 
     Ok(ExecutionState {
-        instruction_disassembly: format!("jump V0 + {:#06x}", {value}) 
+        instruction_disassembly: format!("jump V{} + {:#06x}", x, {value}),
+        ..ExecutionState::default()
     })
 }
 
 fn set_register_random<
-    Instruction: chip8_traits::Instruction,
     Random: chip8_traits::Random
->(apply_instruction: bool, instruction: Instruction, variable_registers: &mut VariableRegisters, random: &mut Random)-> ExecuteResult<Instruction> {
-    let x = count8(instruction.x().to_vec());
-    let value = count8(instruction.nn().to_vec());
-
+>(apply_instruction: bool, x: u8, value: u8, variable_registers: &mut VariableRegisters, random: &mut Random)-> ExecuteResult {
     if apply_instruction {
         let random_value = random.value();
-        if let Err(_) = variable_registers.set(x, random_value & value) {
-            return Err(InstructionError::InstructionExecuteError(instruction));
+        if variable_registers.set(x, random_value & value).is_err() {
+            return Err(register_error(x));
         }
     }
 
     Ok(ExecutionState {
-        instruction_disassembly: format!("V{} = random AND {:#04x}", x, value) 
+        instruction_disassembly: format!("V{} = random AND {:#04x}", x, value),
+        ..ExecutionState::default()
     })
 }
 
-fn display<
-    Instruction: chip8_traits::Instruction
->(apply_instruction: bool, instruction: Instruction, index_register: &usize, variable_registers: &mut VariableRegisters, memory: &Memory, screen_memory: &mut ScreenMemory) -> ExecuteResult<Instruction> {
-    let vx = count8(instruction.x().to_vec());
-    let vy = count8(instruction.y().to_vec());
-    let n = count8(chip8_traits::Instruction::n(&instruction).to_vec());
-    
+#[allow(clippy::too_many_arguments)]
+fn display(apply_instruction: bool, vx: u8, vy: u8, n: u8, index_register: &usize, variable_registers: &mut VariableRegisters, memory: &Memory, screen_memory: &mut ScreenMemory, quirks: &Quirks) -> ExecuteResult {
     if apply_instruction {
-        guard!(let Ok(_) = variable_registers.set(0x0f, 0) 
-        else {
-            return Err(InstructionError::InstructionExecuteError(instruction));
-        });
+        let Ok(_) = variable_registers.set(0x0f, 0) else {
+            return Err(register_error(0x0f));
+        };
     
-        guard!(let Some(x_value) = variable_registers.get(vx) else {
-            return Err(InstructionError::InstructionExecuteError(instruction));
-        });
+        let Some(x_value) = variable_registers.get(vx) else {
+            return Err(register_error(vx));
+        };
     
-        guard!(let Some(y_value) = variable_registers.get(vy) else {
-            return Err(InstructionError::InstructionExecuteError(instruction));
-        });
+        let Some(y_value) = variable_registers.get(vy) else {
+            return Err(register_error(vy));
+        };
     
-        if chip8_traits::ScreenMemory::display(screen_memory,
-            x_value, 
-            y_value, 
-            chip8_traits::Memory::get_iter(memory, *index_register), 
-            n) {
-                guard!(let Ok(_) = variable_registers.set(0x0f, 1) else {
-                    return Err(InstructionError::InstructionExecuteError(instruction));
-                });
-        } else {
-            guard!(let Ok(_) = variable_registers.set(0x0f, 0) else {
-                return Err(InstructionError::InstructionExecuteError(instruction));
-            });
-        }
+        // A 16x16 sprite for DXY0, with the sprite for each selected plane following the last
+        let plane_count = chip8_traits::ScreenMemory::selected_planes(screen_memory).count_ones() as usize;
+        let sprite_length = if n == 0 { 32 } else { n as usize } * plane_count;
+        let sprite = chip8_traits::Memory::get_range(memory, *index_register, sprite_length)?;
+
+        let collision = {
+            if n == 0 {
+                chip8_traits::ScreenMemory::display_large(screen_memory,
+                    x_value,
+                    y_value,
+                    sprite.iter(),
+                    quirks.clipping)
+            } else {
+                chip8_traits::ScreenMemory::display(screen_memory,
+                    x_value, 
+                    y_value, 
+                    sprite.iter(), 
+                    n,
+                    quirks.clipping)
+            }
+        };
+        let flag = {
+            if quirks.collision_rows && chip8_traits::ScreenMemory::is_high_resolution(screen_memory) {
+                (collision.rows + collision.clipped_rows) as u8
+            } else {
+                collision.any() as u8
+            }
+        };
+        let Ok(_) = variable_registers.set(0x0f, flag) else {
+            return Err(register_error(0x0f));
+        };
     }
 
     Ok(ExecutionState {
-        instruction_disassembly: format!("display (V{}, V{}) -> 5x{:#04x}, flip in VF", vx, vy, n) 
+        instruction_disassembly: {
+            if n == 0 {
+                format!("display (V{}, V{}) -> 16x16, flip in VF", vx, vy)
+            } else {
+                format!("display (V{}, V{}) -> 8x{:#04x}, flip in VF", vx, vy, n)
+            }
+        },
+        wait_for_frame: apply_instruction && quirks.display_wait,
+        ..ExecutionState::default()
     })
 }
 
 fn skip_if_pressed<
-    Instruction: chip8_traits::Instruction,
     Keypad: chip8_traits::Keypad, 
 >(
     apply_instruction: bool, 
-    instruction: Instruction, 
+    x: u8,
     keypad: &Keypad, 
     variable_registers: &VariableRegisters, 
-    program_counter: &mut ProgramCounter
-) -> ExecuteResult<Instruction> {
-    let x = count8(instruction.x().to_vec());
-    
+    program_counter: &mut ProgramCounter,
+    memory: &Memory
+) -> ExecuteResult {
     if apply_instruction {
-        guard!(let Some(x_value) = variable_registers.get(x) else {
-            return Err(InstructionError::InstructionExecuteError(instruction));
-        });
+        let Some(x_value) = variable_registers.get(x) else {
+            return Err(register_error(x));
+        };
     
         if keypad.key_state(x_value as usize) {
-            (program_counter as &mut dyn chip8_traits::ProgramCounter).skip();
+            (program_counter as &mut dyn chip8_traits::ProgramCounter).skip(memory)?;
         }
     }
 
     Ok(ExecutionState {
-        instruction_disassembly: format!("skip if key V{} down", x) 
+        instruction_disassembly: format!("skip if key V{} down", x),
+        ..ExecutionState::default()
     })
 }
 
 fn skip_if_not_pressed<
-    Instruction: chip8_traits::Instruction,
     Keypad: chip8_traits::Keypad, 
 >(
     apply_instruction: bool, 
-    instruction: Instruction, 
+    x: u8,
     keypad: &Keypad, 
     variable_registers: &VariableRegisters, 
-    program_counter: &mut ProgramCounter
-) -> ExecuteResult<Instruction> {
-    let x = count8(instruction.x().to_vec());
-    
+    program_counter: &mut ProgramCounter,
+    memory: &Memory
+) -> ExecuteResult {
     if apply_instruction {
-        guard!(let Some(x_value) = variable_registers.get(x) else {
-            return Err(InstructionError::InstructionExecuteError(instruction));
-        });
+        let Some(x_value) = variable_registers.get(x) else {
+            return Err(register_error(x));
+        };
     
-        if keypad.key_state(x_value as usize) == false {
-            (program_counter as &mut dyn chip8_traits::ProgramCounter).skip();
+        if !keypad.key_state(x_value as usize) {
+            (program_counter as &mut dyn chip8_traits::ProgramCounter).skip(memory)?;
         }    
     }
 
     Ok(ExecutionState {
-        instruction_disassembly: format!("skip if key V{} up", x) 
+        instruction_disassembly: format!("skip if key V{} up", x),
+        ..ExecutionState::default()
     })
 }
 
-fn set_timer<
-    Instruction: chip8_traits::Instruction
->(apply_instruction: bool, instruction: Instruction, variable_registers: &mut VariableRegisters, delay_timer: &mut DelayTimer, sound_timer: &mut SoundTimer) -> ExecuteResult<Instruction> {
-    let x = count8(instruction.x().to_vec());
+/// FX07 - Set VX to the delay timer
+fn get_delay_timer(apply_instruction: bool, x: u8, variable_registers: &mut VariableRegisters, delay_timer: &mut DelayTimer) -> ExecuteResult {
+    if apply_instruction {
+        let value = (delay_timer as &mut dyn chip8_traits::Timer).get();
 
-    match count8(instruction.nn().to_vec()) {
-        0x07 => {
-            if apply_instruction {
-                let value = (delay_timer as &mut dyn chip8_traits::Timer).get();
+        if variable_registers.set(x, value).is_err() {
+            return Err(register_error(x));
+        }
+    }
 
-                if let Err(_) = variable_registers.set(x, value) {
-                    return Err(InstructionError::InstructionExecuteError(instruction));
-                }
-            }
+    Ok(ExecutionState {
+        instruction_disassembly: format!("V{} = delay timer", x),
+        ..ExecutionState::default()
+    })
+}
 
-            Ok(ExecutionState {
-                instruction_disassembly: format!("V{} = delay timer", x) 
-            })
-        },
-        0x15 => {
-            if apply_instruction {
-                guard!(let Some(value) = variable_registers.get(x) else {
-                    return Err(InstructionError::InstructionExecuteError(instruction));
-                });
+/// FX15 - Set the delay timer to VX
+fn set_delay_timer(apply_instruction: bool, x: u8, variable_registers: &VariableRegisters, delay_timer: &mut DelayTimer) -> ExecuteResult {
+    if apply_instruction {
+        let Some(value) = variable_registers.get(x) else {
+            return Err(register_error(x));
+        };
 
-                (delay_timer as &mut dyn chip8_traits::Timer).set(value);
-            }
+        (delay_timer as &mut dyn chip8_traits::Timer).set(value);
+    }
 
-            Ok(ExecutionState {
-                instruction_disassembly: format!("set delay timer V{}", x) 
-            })
-        },
-        0x18 => {
-            if apply_instruction {
-                guard!(let Some(value) = variable_registers.get(x) else {
-                    return Err(InstructionError::InstructionExecuteError(instruction));
-                });
+    Ok(ExecutionState {
+        instruction_disassembly: format!("set delay timer V{}", x),
+        ..ExecutionState::default()
+    })
+}
 
-                (sound_timer as &mut dyn chip8_traits::Timer).set(value);
-            }
+/// FX18 - Set the sound timer to VX
+fn set_sound_timer(apply_instruction: bool, x: u8, variable_registers: &VariableRegisters, sound_timer: &mut SoundTimer) -> ExecuteResult {
+    if apply_instruction {
+        let Some(value) = variable_registers.get(x) else {
+            return Err(register_error(x));
+        };
 
-            Ok(ExecutionState {
-                instruction_disassembly: format!("set sound timer V{}", x) 
-            })
-        }
-        _ => return Err(InstructionError::InstructionExecuteError(instruction))
+        (sound_timer as &mut dyn chip8_traits::Timer).set(value);
     }
-}
 
-fn add_to_index<
-    Instruction: chip8_traits::Instruction
->(apply_instruction: bool, instruction: Instruction, variable_registers: &VariableRegisters, index_register: &mut usize) -> ExecuteResult<Instruction> {
-    let x = count8(instruction.x().to_vec());
+    Ok(ExecutionState {
+        instruction_disassembly: format!("set sound timer V{}", x),
+        ..ExecutionState::default()
+    })
+}
 
+fn add_to_index(apply_instruction: bool, x: u8, variable_registers: &mut VariableRegisters, index_register: &mut usize, quirks: &Quirks) -> ExecuteResult {
     if apply_instruction {
-        guard!(let Some(x_value) = variable_registers.get(x) else {
-            return Err(InstructionError::InstructionExecuteError(instruction));
-        });
-        // TODO: Amiga intepreter marks overflow option
+        let Some(x_value) = variable_registers.get(x) else {
+            return Err(register_error(x));
+        };
         (*index_register) += x_value as usize;
+
+        if quirks.add_index_overflow {
+            let flag = {
+                if *index_register > 0x0fff {
+                    1
+                } else {
+                    0
+                }
+            };
+            if variable_registers.set(0x0f, flag).is_err() {
+                return Err(register_error(0x0f));
+            }
+        }
     }
 
     Ok(ExecutionState {
-        instruction_disassembly: format!("I += V{}", x) 
+        instruction_disassembly: format!("I += V{}", x),
+        ..ExecutionState::default()
     })
 }
 
-fn wait_for_key<
-    Instruction: chip8_traits::Instruction
->(apply_instruction: bool, instruction: Instruction, keypad: &dyn chip8_traits::Keypad, variable_registers: &mut VariableRegisters, program_counter: &mut ProgramCounter) -> ExecuteResult<Instruction> {
-    let x = count8(instruction.x().to_vec());
-
+/// FX0A - Wait for a key and store it in VX, on release with the key_release quirk
+fn wait_for_key(
+    apply_instruction: bool,
+    x: u8,
+    keypad: &dyn chip8_traits::Keypad,
+    key_latch: &mut KeyLatch,
+    variable_registers: &mut VariableRegisters,
+    program_counter: &mut ProgramCounter,
+    quirks: &Quirks
+) -> ExecuteResult {
     if apply_instruction {
-        let keypad_state = keypad.state();
-        
-        let mut key_down = false;
-        for index in 0..keypad_state.len() {
-            if keypad_state[index] {
-                let result = variable_registers.set(x, index as u8);
-                if let Err(_) = result {
-                    return Err(InstructionError::InstructionExecuteError(instruction));
-                }
-
-                key_down = true;
-                break;
-            }
-        }
+        let key = if quirks.key_release {
+            key_latch.arm();
+            key_latch.take_released()
+        } else {
+            keypad.state().iter().position(|state| *state).map(|index| index as u8)
+        };
 
-        if !key_down {
-            (program_counter as &mut dyn chip8_traits::ProgramCounter).go_back();
+        match key {
+            Some(key) => variable_registers.set(x, key).map_err(|_| register_error(x))?,
+            None => (program_counter as &mut dyn chip8_traits::ProgramCounter).go_back(),
         }
     }
 
-
     Ok(ExecutionState {
-        instruction_disassembly: format!("wait for any key down, set key to V{}", x) 
+        instruction_disassembly: if quirks.key_release {
+            format!("wait for any key up, set key to V{}", x)
+        } else {
+            format!("wait for any key down, set key to V{}", x)
+        },
+        ..ExecutionState::default()
     })
 }
 
-fn font_character<
-    Instruction: chip8_traits::Instruction
->(apply_instruction: bool, instruction: Instruction, variable_registers: &VariableRegisters, index_register: &mut usize, font_start: usize) -> ExecuteResult<Instruction> {
-    let x = count8(instruction.x().to_vec());
-
+fn font_character(apply_instruction: bool, x: u8, variable_registers: &VariableRegisters, index_register: &mut usize, font_start: usize) -> ExecuteResult {
     if apply_instruction {
-        guard!(let Some(x_value) = variable_registers.get(x) else {
-            return Err(InstructionError::InstructionExecuteError(instruction));
-        });
+        let Some(x_value) = variable_registers.get(x) else {
+            return Err(register_error(x));
+        };
     
-        // TODO: find better place to keep this '5' size
-        (*index_register) = font_start + (x_value * 5) as usize;    
+        (*index_register) = font_start + (x_value & 0x0f) as usize * CHARACTER_SIZE;
     }
 
     Ok(ExecutionState {
-        instruction_disassembly: format!("I = font_start + V{}", x) 
+        instruction_disassembly: format!("I = font_start + V{}", x),
+        ..ExecutionState::default()
     })
 }
 
-fn binary_to_decimal<
-    Instruction: chip8_traits::Instruction
->(apply_instruction: bool, instruction: Instruction, variable_registers: &VariableRegisters, memory: &mut Memory, index_register: &usize) -> ExecuteResult<Instruction> {
-    let x = count8(instruction.x().to_vec());
+/// FX30 - Point I at the large font character for the value in VX
+fn large_font_character(apply_instruction: bool, x: u8, variable_registers: &VariableRegisters, index_register: &mut usize, font_start: usize) -> ExecuteResult {
+    if apply_instruction {
+        let Some(x_value) = variable_registers.get(x) else {
+            return Err(register_error(x));
+        };
+
+        (*index_register) = font_start + LARGE_FONT_OFFSET + (x_value & 0x0f) as usize * LARGE_CHARACTER_SIZE;
+    }
+
+    Ok(ExecutionState {
+        instruction_disassembly: format!("I = large_font_start + V{}", x),
+        ..ExecutionState::default()
+    })
+}
 
+fn binary_to_decimal(apply_instruction: bool, x: u8, variable_registers: &VariableRegisters, memory: &mut Memory, index_register: &usize) -> ExecuteResult {
     if apply_instruction {
-        guard!(let Some(x_value) = variable_registers.get(x) else {
-            return Err(InstructionError::InstructionExecuteError(instruction));
-        });
+        let Some(x_value) = variable_registers.get(x) else {
+            return Err(register_error(x));
+        };
     
-        (memory as &mut dyn chip8_traits::Memory).set(*index_register, x_value / 100);
-        (memory as &mut dyn chip8_traits::Memory).set(*index_register + 1, x_value % 100 / 10);
-        (memory as &mut dyn chip8_traits::Memory).set(*index_register + 2, x_value % 10);
+        (memory as &mut dyn chip8_traits::Memory).set(*index_register, x_value / 100)?;
+        (memory as &mut dyn chip8_traits::Memory).set(*index_register + 1, x_value % 100 / 10)?;
+        (memory as &mut dyn chip8_traits::Memory).set(*index_register + 2, x_value % 10)?;
     }
 
     Ok(ExecutionState {
-        instruction_disassembly: format!("Memory[I..I+2] = V{}", x) 
+        instruction_disassembly: format!("Memory[I..I+2] = V{}", x),
+        ..ExecutionState::default()
     })
 }
 
-fn register_to_memory<
-    Instruction: chip8_traits::Instruction
->(apply_instruction: bool, instruction: Instruction, variable_registers: &VariableRegisters, memory: &mut Memory, index_register: usize) -> ExecuteResult<Instruction> {
-    let x = count8(instruction.x().to_vec());
-    // TODO: option to incriment I while working
-
+fn register_to_memory(apply_instruction: bool, x: u8, variable_registers: &VariableRegisters, memory: &mut Memory, index_register: &mut usize, quirks: &Quirks) -> ExecuteResult {
     if apply_instruction {
         for offset in 0..=x {
-            guard!(let Some(offset_value) = variable_registers.get(offset) else {
-                return Err(InstructionError::InstructionExecuteError(instruction));
-            });
-            // if let Err(error) = 
-            (memory as &mut dyn chip8_traits::Memory).set(index_register + offset as usize, offset_value);
-            // {
-            //     return Err(InstructionError::InstructionExecuteError(instruction));
-            // }
+            let Some(offset_value) = variable_registers.get(offset) else {
+                return Err(register_error(offset));
+            };
+            (memory as &mut dyn chip8_traits::Memory).set(*index_register + offset as usize, offset_value)?;
+        }
+
+        if !quirks.load_store {
+            (*index_register) += x as usize + 1;
         }
     }
 
     Ok(ExecutionState {
-        instruction_disassembly: format!("Memory[I..I + {}] = V0..V{}", x, x) 
+        instruction_disassembly: format!("Memory[I..I + {}] = V0..V{}", x, x),
+        ..ExecutionState::default()
     })
 }
 
-fn memory_to_register<
-    Instruction: chip8_traits::Instruction
->(apply_instruction: bool, instruction: Instruction, variable_registers: &mut VariableRegisters, memory: &Memory, index_register: usize) -> ExecuteResult<Instruction> {
-    let x = count8(instruction.x().to_vec());
-    // TODO: option to incriment I while working
-
+fn memory_to_register(apply_instruction: bool, x: u8, variable_registers: &mut VariableRegisters, memory: &Memory, index_register: &mut usize, quirks: &Quirks) -> ExecuteResult {
     if apply_instruction {
         for offset in 0..=x {
-            // guard!(let Some(offset_value) = variable_registers.get(offset) else {
-            //     return Err(InstructionError::InstructionExecuteError(instruction));
-            // });
+            // let Some(offset_value) = variable_registers.get(offset) else {
+            //     return Err(register_error(offset));
+            // };
     
             // TODO: WTFFFFFFF WITH NEEDINGTHIS CAST
-            let offset_value = (memory as &dyn chip8_traits::Memory).get(index_register + offset as usize);
+            let offset_value = (memory as &dyn chip8_traits::Memory).get(*index_register + offset as usize)?;
     
-            guard!(let Ok(_) = variable_registers.set(offset, offset_value) else {
-                return Err(InstructionError::InstructionExecuteError(instruction));
-            });
+            let Ok(_) = variable_registers.set(offset, offset_value) else {
+                return Err(register_error(offset));
+            };
+        }
+
+        if !quirks.load_store {
+            (*index_register) += x as usize + 1;
+        }
+    }
+
+    Ok(ExecutionState {
+        instruction_disassembly: format!("V0..V{} = Memory[I..I + {}]", x, x),
+        ..ExecutionState::default()
+    })
+}
+
+/// FX75 - Save V0 through VX to the persistent RPL user flags
+fn register_to_flags(apply_instruction: bool, x: u8, variable_registers: &VariableRegisters, rpl_flags: &mut [u8; 16]) -> ExecuteResult {
+    if apply_instruction {
+        for offset in 0..=x {
+            let Some(offset_value) = variable_registers.get(offset) else {
+                return Err(register_error(offset));
+            };
+            rpl_flags[offset as usize] = offset_value;
+        }
+    }
+
+    Ok(ExecutionState {
+        instruction_disassembly: format!("flags[0..{}] = V0..V{}", x, x),
+        ..ExecutionState::default()
+    })
+}
+
+/// FX85 - Restore V0 through VX from the persistent RPL user flags
+fn flags_to_register(apply_instruction: bool, x: u8, variable_registers: &mut VariableRegisters, rpl_flags: &[u8; 16]) -> ExecuteResult {
+    if apply_instruction {
+        for offset in 0..=x {
+            let Ok(_) = variable_registers.set(offset, rpl_flags[offset as usize]) else {
+                return Err(register_error(offset));
+            };
         }
     }
 
     Ok(ExecutionState {
-        instruction_disassembly: format!("V0..V{} = Memory[I..I + {}]", x, x) 
+        instruction_disassembly: format!("V0..V{} = flags[0..{}]", x, x),
+        ..ExecutionState::default()
     })
 }
 
+
+/// The registers VX through VY, in either direction
+fn register_range(x: u8, y: u8) -> Vec<u8> {
+    if x <= y {
+        (x..=y).collect()
+    } else {
+        (y..=x).rev().collect()
+    }
+}
+
+/// 5XY2 - Save VX through VY to memory starting at I, leaving I unchanged
+fn register_range_to_memory(apply_instruction: bool, x: u8, y: u8, variable_registers: &VariableRegisters, memory: &mut Memory, index_register: &usize) -> ExecuteResult {
+    if apply_instruction {
+        for (offset, register) in register_range(x, y).into_iter().enumerate() {
+            let Some(register_value) = variable_registers.get(register) else {
+                return Err(register_error(register));
+            };
+            (memory as &mut dyn chip8_traits::Memory).set(*index_register + offset, register_value)?;
+        }
+    }
+
+    Ok(ExecutionState {
+        instruction_disassembly: format!("Memory[I..] = V{}..V{}", x, y),
+        ..ExecutionState::default()
+    })
+}
+
+/// 5XY3 - Load VX through VY from memory starting at I, leaving I unchanged
+fn memory_to_register_range(apply_instruction: bool, x: u8, y: u8, variable_registers: &mut VariableRegisters, memory: &Memory, index_register: &usize) -> ExecuteResult {
+    if apply_instruction {
+        for (offset, register) in register_range(x, y).into_iter().enumerate() {
+            let value = (memory as &dyn chip8_traits::Memory).get(*index_register + offset)?;
+            let Ok(_) = variable_registers.set(register, value) else {
+                return Err(register_error(register));
+            };
+        }
+    }
+
+    Ok(ExecutionState {
+        instruction_disassembly: format!("V{}..V{} = Memory[I..]", x, y),
+        ..ExecutionState::default()
+    })
+}
+
+/// FN01 - Select the bitplanes drawn, cleared and scrolled by later instructions, N being a mask
+fn select_planes<
+    ScreenMemory: chip8_traits::ScreenMemory
+>(apply_instruction: bool, planes: u8, screen_memory: &mut ScreenMemory) -> ExecuteResult {
+    if apply_instruction {
+        screen_memory.select_planes(planes);
+    }
+
+    Ok(ExecutionState {
+        instruction_disassembly: format!("planes {}", planes),
+        ..ExecutionState::default()
+    })
+}
+
+/// F002 - Load the 16 byte audio pattern buffer from memory at I
+fn load_audio_pattern(apply_instruction: bool, memory: &Memory, index_register: &usize, sound_timer: &mut SoundTimer) -> ExecuteResult {
+    if apply_instruction {
+        let mut pattern = [0; 16];
+        for (offset, value) in pattern.iter_mut().enumerate() {
+            *value = (memory as &dyn chip8_traits::Memory).get(*index_register + offset)?;
+        }
+        sound_timer.set_pattern(pattern);
+    }
+
+    Ok(ExecutionState {
+        instruction_disassembly: "audio pattern = Memory[I..I + 16]".to_string(),
+        ..ExecutionState::default()
+    })
+}
+
+/// FX3A - Set the audio pattern playback pitch to VX
+fn set_pitch(apply_instruction: bool, x: u8, variable_registers: &VariableRegisters, sound_timer: &mut SoundTimer) -> ExecuteResult {
+    if apply_instruction {
+        let Some(x_value) = variable_registers.get(x) else {
+            return Err(register_error(x));
+        };
+
+        sound_timer.set_pitch(x_value);
+    }
+
+    Ok(ExecutionState {
+        instruction_disassembly: format!("pitch = V{}", x),
+        ..ExecutionState::default()
+    })
+}
\ No newline at end of file
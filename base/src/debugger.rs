@@ -0,0 +1,518 @@
+use std::{fmt, ops::Range};
+
+use crate::{Access, Error, Interpreter, Opcode, SymbolMap};
+
+/// Most instructions step over / step out will run before giving up, about 18 seconds at the default speed
+pub const STEP_INSTRUCTION_LIMIT: u64 = 16_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operand {
+    Register(u8),
+    IndexRegister,
+    DelayTimer,
+    SoundTimer,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Comparison {
+    const SYMBOLS: [(&'static str, Comparison); 6] = [
+        ("==", Comparison::Equal),
+        ("!=", Comparison::NotEqual),
+        ("<=", Comparison::LessOrEqual),
+        (">=", Comparison::GreaterOrEqual),
+        ("<", Comparison::Less),
+        (">", Comparison::Greater),
+    ];
+
+    fn symbol(&self) -> &'static str {
+        Comparison::SYMBOLS.iter().find(|(_, comparison)| comparison == self).map_or("", |(symbol, _)| symbol)
+    }
+
+    fn compare(&self, left: usize, right: usize) -> bool {
+        match self {
+            Comparison::Equal => left == right,
+            Comparison::NotEqual => left != right,
+            Comparison::Less => left < right,
+            Comparison::LessOrEqual => left <= right,
+            Comparison::Greater => left > right,
+            Comparison::GreaterOrEqual => left >= right,
+        }
+    }
+}
+
+/// A comparison of a register against a constant, such as `V3 == 0x10`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Condition {
+    pub operand: Operand,
+    pub comparison: Comparison,
+    pub value: usize,
+}
+
+impl Condition {
+    /// Parse `<operand> <comparison> <value>`, the operand being V0 - VF, I, DT or ST and the value decimal or 0x prefixed hex
+    pub fn parse(text: &str) -> Result<Condition, String> {
+        let text = text.trim();
+
+        let Some((symbol, comparison)) = Comparison::SYMBOLS.iter().find(|(symbol, _)| text.contains(symbol)) else {
+            return Err(format!("No comparison in condition '{}'", text));
+        };
+        let mut parts = text.splitn(2, symbol);
+        let operand_text = parts.next().unwrap_or("").trim().to_ascii_uppercase();
+        let value_text = parts.next().unwrap_or("").trim();
+
+        let operand = match operand_text.as_str() {
+            "I" => Operand::IndexRegister,
+            "DT" => Operand::DelayTimer,
+            "ST" => Operand::SoundTimer,
+            register if register.len() == 2 && register.starts_with('V') => {
+                match u8::from_str_radix(&register[1..], 16) {
+                    Ok(index) => Operand::Register(index),
+                    Err(_) => return Err(format!("Unknown register '{}'", register)),
+                }
+            },
+            other => return Err(format!("Unknown operand '{}'", other)),
+        };
+
+        let value = {
+            if let Some(hex) = value_text.strip_prefix("0x").or_else(|| value_text.strip_prefix("0X")) {
+                usize::from_str_radix(hex, 16)
+            } else {
+                value_text.parse::<usize>()
+            }
+        };
+        let Ok(value) = value else {
+            return Err(format!("Invalid value '{}'", value_text));
+        };
+
+        Ok(Condition {
+            operand,
+            comparison: *comparison,
+            value,
+        })
+    }
+
+    pub fn evaluate<Renderer, Keypad, Random>(&self, interpreter: &Interpreter<Renderer, Keypad, Random>) -> bool
+    where Renderer: chip8_traits::Renderer,
+        Keypad: chip8_traits::Keypad,
+        Random: chip8_traits::Random {
+        let left = match self.operand {
+            Operand::Register(index) => interpreter.variable_registers().get(index).unwrap_or(0) as usize,
+            Operand::IndexRegister => interpreter.index_register(),
+            Operand::DelayTimer => chip8_traits::Timer::get(interpreter.delay_timer()) as usize,
+            Operand::SoundTimer => chip8_traits::Timer::get(interpreter.sound_timer()) as usize,
+        };
+
+        self.comparison.compare(left, self.value)
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.operand {
+            Operand::Register(index) => write!(f, "V{:X}", index)?,
+            Operand::IndexRegister => write!(f, "I")?,
+            Operand::DelayTimer => write!(f, "DT")?,
+            Operand::SoundTimer => write!(f, "ST")?,
+        }
+        write!(f, " {} {:#x}", self.comparison.symbol(), self.value)
+    }
+}
+
+/// Stop before the instruction at `address`, only when `condition` holds if there is one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Breakpoint {
+    pub address: usize,
+    pub condition: Option<Condition>,
+}
+
+/// Stop after any instruction that reads or writes memory in `range`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Watchpoint {
+    pub range: Range<usize>,
+    pub access: Access,
+}
+
+/// Stop before any instruction matching a pattern such as `DXYN`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpcodeBreakpoint {
+    pub value: u16,
+    pub mask: u16,
+}
+
+impl OpcodeBreakpoint {
+    /// Parse four characters, hex digits matching exactly and anything else matching any nibble
+    pub fn parse(pattern: &str) -> Result<OpcodeBreakpoint, String> {
+        if pattern.chars().count() != 4 {
+            return Err(format!("Opcode pattern '{}' should be 4 characters", pattern));
+        }
+
+        let mut value = 0;
+        let mut mask = 0;
+        for character in pattern.chars() {
+            value <<= 4;
+            mask <<= 4;
+            if let Some(digit) = character.to_digit(16) {
+                value |= digit as u16;
+                mask |= 0xf;
+            }
+        }
+
+        Ok(OpcodeBreakpoint {
+            value,
+            mask,
+        })
+    }
+
+    pub fn matches(&self, opcode: u16) -> bool {
+        opcode & self.mask == self.value
+    }
+}
+
+/// Why execution stopped
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StopReason {
+    Breakpoint { address: usize },
+    /// The instruction at `address` accessed `location`, stopping once it finished
+    Watchpoint { address: usize, location: usize, access: Access },
+    Opcode { address: usize, opcode: u16 },
+    /// A step finished
+    Step,
+    /// Step over / step out ran `STEP_INSTRUCTION_LIMIT` instructions without finishing
+    StepLimit,
+    Exited,
+    Error(Error),
+}
+
+/// A call the program hasn't returned from yet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CallFrame {
+    /// Address of the 2NNN that made the call
+    pub call_address: usize,
+    /// Where the call went, None when the instruction there has since changed
+    pub target: Option<usize>,
+    pub return_address: usize,
+}
+
+/// Runs an interpreter while watching for breakpoints and watchpoints
+pub struct Debugger<Renderer, Keypad, Random>
+where Renderer: chip8_traits::Renderer,
+    Keypad: chip8_traits::Keypad,
+    Random: chip8_traits::Random {
+    interpreter: Interpreter<Renderer, Keypad, Random>,
+
+    breakpoints: Vec<Breakpoint>,
+    watchpoints: Vec<Watchpoint>,
+    opcode_breakpoints: Vec<OpcodeBreakpoint>,
+
+    symbols: SymbolMap,
+
+    /// Instructions run so far in the current frame
+    frame_position: usize,
+    /// Instruction count of the last stop, so resuming doesn't stop on the same instruction again
+    stopped_at: Option<u64>,
+}
+
+impl<Renderer, Keypad, Random> Debugger<Renderer, Keypad, Random>
+where Renderer: chip8_traits::Renderer,
+    Keypad: chip8_traits::Keypad,
+    Random: chip8_traits::Random {
+    pub fn new(interpreter: Interpreter<Renderer, Keypad, Random>) -> Debugger<Renderer, Keypad, Random> {
+        Debugger {
+            interpreter,
+
+            breakpoints: vec![],
+            watchpoints: vec![],
+            opcode_breakpoints: vec![],
+
+            symbols: SymbolMap::new(),
+
+            frame_position: 0,
+            stopped_at: None,
+        }
+    }
+
+    pub fn interpreter(&self) -> &Interpreter<Renderer, Keypad, Random> {
+        &self.interpreter
+    }
+
+    /// Changing the program or state directly should be followed by `reset_frame`
+    pub fn interpreter_mut(&mut self) -> &mut Interpreter<Renderer, Keypad, Random> {
+        &mut self.interpreter
+    }
+
+    /// Start counting a new frame, after loading a program or state
+    pub fn reset_frame(&mut self) {
+        self.frame_position = 0;
+        self.stopped_at = None;
+    }
+
+    pub fn symbols(&self) -> &SymbolMap {
+        &self.symbols
+    }
+
+    /// Use the labels of the program's source, and stop at the breakpoints it marks
+    pub fn set_symbols(&mut self, symbols: SymbolMap) {
+        for (_, address) in symbols.breakpoints() {
+            self.add_breakpoint(Breakpoint { address, condition: None });
+        }
+        self.symbols = symbols;
+    }
+
+    /// The address of a label, or of a decimal or 0x prefixed hex address
+    pub fn resolve(&self, text: &str) -> Option<usize> {
+        if let Some(address) = self.symbols.address(text) {
+            return Some(address);
+        }
+
+        match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+            Some(hex) => usize::from_str_radix(hex, 16).ok(),
+            None => text.parse::<usize>().ok(),
+        }
+    }
+
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+        self.breakpoints.push(breakpoint);
+    }
+
+    /// Remove every breakpoint at `address`, returning whether there were any
+    pub fn remove_breakpoint(&mut self, address: usize) -> bool {
+        let count = self.breakpoints.len();
+        self.breakpoints.retain(|breakpoint| breakpoint.address != address);
+        self.breakpoints.len() != count
+    }
+
+    pub fn clear_breakpoints(&mut self) {
+        self.breakpoints.clear();
+    }
+
+    pub fn watchpoints(&self) -> &[Watchpoint] {
+        &self.watchpoints
+    }
+
+    pub fn add_watchpoint(&mut self, watchpoint: Watchpoint) {
+        self.watchpoints.push(watchpoint);
+    }
+
+    pub fn remove_watchpoint(&mut self, index: usize) -> Option<Watchpoint> {
+        if index < self.watchpoints.len() {
+            Some(self.watchpoints.remove(index))
+        } else {
+            None
+        }
+    }
+
+    pub fn clear_watchpoints(&mut self) {
+        self.watchpoints.clear();
+    }
+
+    pub fn opcode_breakpoints(&self) -> &[OpcodeBreakpoint] {
+        &self.opcode_breakpoints
+    }
+
+    pub fn add_opcode_breakpoint(&mut self, breakpoint: OpcodeBreakpoint) {
+        self.opcode_breakpoints.push(breakpoint);
+    }
+
+    pub fn remove_opcode_breakpoint(&mut self, index: usize) -> Option<OpcodeBreakpoint> {
+        if index < self.opcode_breakpoints.len() {
+            Some(self.opcode_breakpoints.remove(index))
+        } else {
+            None
+        }
+    }
+
+    pub fn clear_opcode_breakpoints(&mut self) {
+        self.opcode_breakpoints.clear();
+    }
+
+    /// The opcode at the program counter
+    pub fn current_opcode(&self) -> u16 {
+        self.opcode_at(self.interpreter.dump_program_counter())
+    }
+
+    fn opcode_at(&self, location: usize) -> u16 {
+        let memory = self.interpreter.memory();
+        let byte = |location| chip8_traits::Memory::get(memory, location).unwrap_or(0) as u16;
+
+        (byte(location) << 8) | byte(location + 1)
+    }
+
+    /// Calls not yet returned from, oldest first
+    pub fn call_stack(&self) -> Vec<CallFrame> {
+        self.interpreter.call_stack().into_iter().map(|return_address| {
+            let call_address = return_address.saturating_sub(2);
+            let target = match Opcode::decode(self.opcode_at(call_address)) {
+                Some(Opcode::Call { nnn }) => Some(nnn as usize),
+                _ => None,
+            };
+
+            CallFrame { call_address, target, return_address }
+        }).collect()
+    }
+
+    fn stack_depth(&self) -> usize {
+        self.interpreter.stack().values().len()
+    }
+
+    /// Why the instruction at the program counter should not run yet, if it shouldn't
+    fn check(&self) -> Option<StopReason> {
+        let address = self.interpreter.dump_program_counter();
+
+        let breakpoint_hit = self.breakpoints.iter().any(|breakpoint| {
+            breakpoint.address == address && breakpoint.condition.is_none_or(|condition| condition.evaluate(&self.interpreter))
+        });
+        if breakpoint_hit {
+            return Some(StopReason::Breakpoint { address });
+        }
+
+        let opcode = self.current_opcode();
+        if self.opcode_breakpoints.iter().any(|breakpoint| breakpoint.matches(opcode)) {
+            return Some(StopReason::Opcode { address, opcode });
+        }
+
+        None
+    }
+
+    /// Run one instruction, finishing the frame if it was the last one in it
+    ///
+    /// Returns the watchpoint stop if the instruction touched watched memory
+    fn execute(&mut self) -> Result<Option<StopReason>, Error> {
+        let address = self.interpreter.dump_program_counter();
+        self.interpreter.memory_mut().set_watching(!self.watchpoints.is_empty());
+        chip8_traits::Interpreter::update(&mut self.interpreter)?;
+        let watch_stop = self.watch_stop(address);
+
+        self.frame_position += 1;
+        if self.frame_position >= self.interpreter.instructions_per_frame() || self.interpreter.is_waiting_for_frame() {
+            self.finish_frame()?;
+        }
+
+        Ok(watch_stop)
+    }
+
+    /// The first access to watched memory by the instruction at `address`, which just ran
+    fn watch_stop(&mut self, address: usize) -> Option<StopReason> {
+        let accesses = self.interpreter.memory_mut().take_accesses();
+
+        accesses.into_iter().find(|(location, access)| {
+            self.watchpoints.iter().any(|watchpoint| watchpoint.access.includes(*access) && watchpoint.range.contains(location))
+        }).map(|(location, access)| StopReason::Watchpoint { address, location, access })
+    }
+
+    fn finish_frame(&mut self) -> Result<(), Error> {
+        self.frame_position = 0;
+        self.interpreter.end_frame()
+    }
+
+    /// Stop if there is a reason to, unless execution already stopped on this instruction
+    fn check_stop(&mut self) -> Option<StopReason> {
+        let instruction_count = self.interpreter.instruction_count();
+        if self.stopped_at == Some(instruction_count) {
+            return None;
+        }
+
+        let reason = self.check()?;
+        self.stopped_at = Some(instruction_count);
+        Some(reason)
+    }
+
+    /// Run the rest of the current frame, returning why it stopped early if it did
+    pub fn run_frame(&mut self) -> Option<StopReason> {
+        loop {
+            if chip8_traits::Interpreter::has_exited(&self.interpreter) {
+                if let Err(error) = self.finish_frame() {
+                    return Some(StopReason::Error(error));
+                }
+                return Some(StopReason::Exited);
+            }
+
+            if let Some(reason) = self.check_stop() {
+                return Some(reason);
+            }
+
+            match self.execute() {
+                Ok(Some(reason)) => return Some(reason),
+                Ok(None) => {},
+                Err(error) => return Some(StopReason::Error(error)),
+            }
+
+            if self.frame_position == 0 {
+                return None;
+            }
+        }
+    }
+
+    /// Run a single instruction, following calls into subroutines
+    pub fn step_into(&mut self) -> StopReason {
+        if chip8_traits::Interpreter::has_exited(&self.interpreter) {
+            return StopReason::Exited;
+        }
+
+        let result = self.execute();
+        self.stopped_at = Some(self.interpreter.instruction_count());
+
+        match result {
+            Ok(watch_stop) => watch_stop.unwrap_or(StopReason::Step),
+            Err(error) => StopReason::Error(error),
+        }
+    }
+
+    /// Run a single instruction, running a 2NNN call through to its return
+    pub fn step_over(&mut self) -> StopReason {
+        if !matches!(Opcode::decode(self.current_opcode()), Some(Opcode::Call { .. })) {
+            return self.step_into();
+        }
+
+        let return_address = self.interpreter.dump_program_counter() + 2;
+        let depth = self.stack_depth();
+        self.run_until(|debugger| debugger.interpreter.dump_program_counter() == return_address && debugger.stack_depth() == depth)
+    }
+
+    /// Run until the current subroutine returns
+    pub fn step_out(&mut self) -> StopReason {
+        let depth = self.stack_depth();
+        if depth == 0 {
+            return self.step_into();
+        }
+
+        self.run_until(|debugger| debugger.stack_depth() < depth)
+    }
+
+    fn run_until<Done: Fn(&Self) -> bool>(&mut self, done: Done) -> StopReason {
+        for _ in 0..STEP_INSTRUCTION_LIMIT {
+            if chip8_traits::Interpreter::has_exited(&self.interpreter) {
+                return StopReason::Exited;
+            }
+
+            if let Some(reason) = self.check_stop() {
+                return reason;
+            }
+
+            match self.execute() {
+                Ok(Some(reason)) => return reason,
+                Ok(None) => {},
+                Err(error) => return StopReason::Error(error),
+            }
+
+            if done(self) {
+                self.stopped_at = Some(self.interpreter.instruction_count());
+                return StopReason::Step;
+            }
+        }
+
+        StopReason::StepLimit
+    }
+}
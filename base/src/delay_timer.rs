@@ -2,6 +2,12 @@ pub struct DelayTimer {
     value: u8
 }
 
+impl Default for DelayTimer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl DelayTimer {
     pub fn new() -> DelayTimer {
         DelayTimer {
@@ -23,7 +29,7 @@ impl chip8_traits::Timer for DelayTimer {
         self.value = value;
     }
 
-    fn update(&mut self) -> Result<(), String> {
+    fn update(&mut self) -> Result<(), chip8_traits::Error> {
         if self.value > 0 {
             self.value -= 1;
         }
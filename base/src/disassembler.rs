@@ -0,0 +1,444 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use crate::Opcode;
+
+/// Where programs are loaded and start running
+pub const DEFAULT_START: usize = 0x200;
+/// Most bytes of data shown on one line of a listing
+pub const DATA_BYTES_PER_LINE: usize = 8;
+
+/// How execution continues after an instruction
+enum Flow {
+    Next,
+    Stop,
+    Jump(usize),
+    Call(usize),
+    /// Either the next instruction or the one after it
+    Skip,
+    /// BNNN, jumping to NNN plus an offset, usually into a table of jumps
+    Table(usize),
+}
+
+/// What refers to a labelled address, ordered so the strongest reference names the label
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LabelKind {
+    /// Loaded into I, so usually sprite data
+    Data,
+    Jump,
+    Subroutine,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Line {
+    Instruction { address: usize, bytes: Vec<u8> },
+    Data { address: usize, bytes: Vec<u8> },
+}
+
+impl Line {
+    pub fn address(&self) -> usize {
+        match self {
+            Line::Instruction { address, .. } | Line::Data { address, .. } => *address,
+        }
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        match self {
+            Line::Instruction { bytes, .. } | Line::Data { bytes, .. } => bytes,
+        }
+    }
+}
+
+/// A program split into code and data by following every path execution can take from its start
+pub struct Disassembly {
+    start: usize,
+    program: Vec<u8>,
+
+    /// Address and width of every instruction reached
+    instructions: BTreeMap<usize, usize>,
+    labels: BTreeMap<usize, LabelKind>,
+}
+
+impl Disassembly {
+    /// Analyse `program` as loaded at `start`, usually `DEFAULT_START`
+    pub fn new(program: &[u8], start: usize) -> Disassembly {
+        let mut result = Disassembly {
+            start,
+            program: program.to_vec(),
+
+            instructions: BTreeMap::new(),
+            labels: BTreeMap::new(),
+        };
+        result.trace();
+
+        result
+    }
+
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    pub fn end(&self) -> usize {
+        self.start + self.program.len()
+    }
+
+    fn contains(&self, address: usize) -> bool {
+        address >= self.start && address < self.end()
+    }
+
+    fn opcode(&self, address: usize) -> Option<u16> {
+        if !self.contains(address) || !self.contains(address + 1) {
+            return None;
+        }
+        let offset = address - self.start;
+
+        Some(((self.program[offset] as u16) << 8) | self.program[offset + 1] as u16)
+    }
+
+    fn add_label(&mut self, address: usize, kind: LabelKind) {
+        if !self.contains(address) {
+            return;
+        }
+        let label = self.labels.entry(address).or_insert(kind);
+        *label = (*label).max(kind);
+    }
+
+    fn trace(&mut self) {
+        let mut queue = vec![self.start];
+
+        while let Some(address) = queue.pop() {
+            if self.instructions.contains_key(&address) {
+                continue;
+            }
+            let Some(opcode) = self.opcode(address).and_then(Opcode::decode) else {
+                continue;
+            };
+            let width = opcode.width();
+            if address + width > self.end() {
+                continue;
+            }
+            self.instructions.insert(address, width);
+
+            if let Some(target) = self.index_target(address, opcode) {
+                self.add_label(target, LabelKind::Data);
+            }
+
+            let next = address + width;
+            match flow(opcode) {
+                Flow::Next => queue.push(next),
+                Flow::Stop => {},
+                Flow::Jump(target) => {
+                    self.add_label(target, LabelKind::Jump);
+                    queue.push(target);
+                },
+                Flow::Call(target) => {
+                    self.add_label(target, LabelKind::Subroutine);
+                    queue.push(target);
+                    queue.push(next);
+                },
+                Flow::Skip => {
+                    queue.push(next);
+                    queue.push(next + self.opcode(next).and_then(Opcode::decode).map_or(2, |opcode| opcode.width()));
+                },
+                Flow::Table(target) => {
+                    self.add_label(target, LabelKind::Jump);
+                    queue.push(target);
+
+                    let mut entry = target;
+                    while matches!(self.opcode(entry).and_then(Opcode::decode), Some(Opcode::Jump { .. })) {
+                        queue.push(entry);
+                        entry += 2;
+                    }
+                },
+            }
+        }
+    }
+
+    /// The address ANNN or F000 NNNN points I at
+    fn index_target(&self, address: usize, opcode: Opcode) -> Option<usize> {
+        match opcode {
+            Opcode::SetIndexLong => self.opcode(address + 2).map(|value| value as usize),
+            Opcode::SetIndex { nnn } => Some(nnn as usize),
+            _ => None,
+        }
+    }
+
+    pub fn is_code(&self, address: usize) -> bool {
+        self.instructions.range(..=address).next_back().is_some_and(|(start, width)| address < start + width)
+    }
+
+    pub fn label_kind(&self, address: usize) -> Option<LabelKind> {
+        self.labels.get(&address).copied()
+    }
+
+    pub fn label(&self, address: usize) -> Option<String> {
+        let prefix = match self.labels.get(&address)? {
+            LabelKind::Data => "data",
+            LabelKind::Jump => "label",
+            LabelKind::Subroutine => "sub",
+        };
+
+        Some(format!("{}_{:03x}", prefix, address))
+    }
+
+    /// Every labelled address, in order
+    pub fn labels(&self) -> impl Iterator<Item = usize> + '_ {
+        self.labels.keys().copied()
+    }
+
+    /// The label for `address`, or the address itself if it has none
+    pub fn label_or_address(&self, address: usize) -> String {
+        self.label(address).unwrap_or_else(|| format!("{:#05x}", address))
+    }
+
+    /// The program as instructions and runs of data, data being broken at labels and after `DATA_BYTES_PER_LINE` bytes
+    pub fn lines(&self) -> Vec<Line> {
+        let mut result = vec![];
+
+        let mut address = self.start;
+        while address < self.end() {
+            let offset = address - self.start;
+
+            if let Some(width) = self.instructions.get(&address) {
+                result.push(Line::Instruction { address, bytes: self.program[offset..offset + width].to_vec() });
+                address += width;
+                continue;
+            }
+
+            let mut next = address + 1;
+            while next < self.end() && next - address < DATA_BYTES_PER_LINE && !self.instructions.contains_key(&next) && !self.labels.contains_key(&next) {
+                next += 1;
+            }
+            result.push(Line::Data { address, bytes: self.program[offset..next - self.start].to_vec() });
+            address = next;
+        }
+
+        result
+    }
+
+    /// The mnemonic for the instruction at the start of `bytes`, with labels in place of addresses
+    pub fn mnemonic(&self, bytes: &[u8]) -> String {
+        let opcode = ((bytes[0] as u16) << 8) | bytes[1] as u16;
+
+        match Opcode::decode(opcode) {
+            Some(Opcode::Clear) => "CLS".to_string(),
+            Some(Opcode::Return) => "RET".to_string(),
+            Some(Opcode::ScrollRight) => "SCR".to_string(),
+            Some(Opcode::ScrollLeft) => "SCL".to_string(),
+            Some(Opcode::Exit) => "EXIT".to_string(),
+            Some(Opcode::LowResolution) => "LOW".to_string(),
+            Some(Opcode::HighResolution) => "HIGH".to_string(),
+            Some(Opcode::ScrollDown { n }) => format!("SCD {}", n),
+            Some(Opcode::ScrollUp { n }) => format!("SCU {}", n),
+            Some(Opcode::Jump { nnn }) => format!("JP {}", self.label_or_address(nnn as usize)),
+            Some(Opcode::Call { nnn }) => format!("CALL {}", self.label_or_address(nnn as usize)),
+            Some(Opcode::SkipIfEqualImm { x, nn }) => format!("SE V{:X}, {:#04x}", x, nn),
+            Some(Opcode::SkipIfNotEqualImm { x, nn }) => format!("SNE V{:X}, {:#04x}", x, nn),
+            Some(Opcode::SkipIfEqual { x, y }) => format!("SE V{:X}, V{:X}", x, y),
+            Some(Opcode::SaveRange { x, y }) => format!("SAVE V{:X}-V{:X}", x, y),
+            Some(Opcode::LoadRange { x, y }) => format!("LOAD V{:X}-V{:X}", x, y),
+            Some(Opcode::SetImm { x, nn }) => format!("LD V{:X}, {:#04x}", x, nn),
+            Some(Opcode::AddImm { x, nn }) => format!("ADD V{:X}, {:#04x}", x, nn),
+            Some(Opcode::Set { x, y }) => format!("LD V{:X}, V{:X}", x, y),
+            Some(Opcode::Or { x, y }) => format!("OR V{:X}, V{:X}", x, y),
+            Some(Opcode::And { x, y }) => format!("AND V{:X}, V{:X}", x, y),
+            Some(Opcode::Xor { x, y }) => format!("XOR V{:X}, V{:X}", x, y),
+            Some(Opcode::Add { x, y }) => format!("ADD V{:X}, V{:X}", x, y),
+            Some(Opcode::Sub { x, y }) => format!("SUB V{:X}, V{:X}", x, y),
+            Some(Opcode::ShiftRight { x, y }) => format!("SHR V{:X}, V{:X}", x, y),
+            Some(Opcode::SubReversed { x, y }) => format!("SUBN V{:X}, V{:X}", x, y),
+            Some(Opcode::ShiftLeft { x, y }) => format!("SHL V{:X}, V{:X}", x, y),
+            Some(Opcode::SkipIfNotEqual { x, y }) => format!("SNE V{:X}, V{:X}", x, y),
+            Some(Opcode::SetIndex { nnn }) => format!("LD I, {}", self.label_or_address(nnn as usize)),
+            Some(Opcode::JumpOffset { nnn, .. }) => format!("JP V0, {}", self.label_or_address(nnn as usize)),
+            Some(Opcode::Random { x, nn }) => format!("RND V{:X}, {:#04x}", x, nn),
+            Some(Opcode::Draw { x, y, n }) => format!("DRW V{:X}, V{:X}, {}", x, y, n),
+            Some(Opcode::SkipIfKey { x }) => format!("SKP V{:X}", x),
+            Some(Opcode::SkipIfNotKey { x }) => format!("SKNP V{:X}", x),
+            Some(Opcode::SetIndexLong) if bytes.len() >= 4 => {
+                let address = ((bytes[2] as usize) << 8) | bytes[3] as usize;
+                format!("LD I, long {}", self.label_or_address(address))
+            },
+            Some(Opcode::SelectPlanes { n }) => format!("PLANE {}", n),
+            Some(Opcode::LoadAudio) => "AUDIO".to_string(),
+            Some(Opcode::GetDelay { x }) => format!("LD V{:X}, DT", x),
+            Some(Opcode::WaitKey { x }) => format!("LD V{:X}, K", x),
+            Some(Opcode::SetDelay { x }) => format!("LD DT, V{:X}", x),
+            Some(Opcode::SetSound { x }) => format!("LD ST, V{:X}", x),
+            Some(Opcode::AddIndex { x }) => format!("ADD I, V{:X}", x),
+            Some(Opcode::Font { x }) => format!("LD F, V{:X}", x),
+            Some(Opcode::LargeFont { x }) => format!("LD HF, V{:X}", x),
+            Some(Opcode::Bcd { x }) => format!("LD B, V{:X}", x),
+            Some(Opcode::SetPitch { x }) => format!("PITCH V{:X}", x),
+            Some(Opcode::Save { x }) => format!("LD [I], V{:X}", x),
+            Some(Opcode::Load { x }) => format!("LD V{:X}, [I]", x),
+            Some(Opcode::SaveFlags { x }) => format!("LD R, V{:X}", x),
+            Some(Opcode::LoadFlags { x }) => format!("LD V{:X}, R", x),
+            Some(Opcode::SetIndexLong) | None => format!("DW {:#06x}", opcode),
+        }
+    }
+
+    /// Address, raw bytes and mnemonic for each line, with labels on their own lines
+    pub fn listing(&self) -> String {
+        let mut result = String::new();
+
+        for line in self.lines() {
+            let address = line.address();
+            if let Some(label) = self.label(address) {
+                let _ = writeln!(result, "{}:", label);
+            }
+
+            let bytes: String = line.bytes().iter().map(|byte| format!("{:02x}", byte)).collect();
+            let text = match &line {
+                Line::Instruction { bytes, .. } => self.mnemonic(bytes),
+                Line::Data { bytes, .. } => format!("DB {}", bytes.iter().map(|byte| format!("{:#04x}", byte)).collect::<Vec<String>>().join(", ")),
+            };
+            let _ = writeln!(result, "{:#06x}  {:<16}  {}", address, bytes, text);
+
+            // Labels can point inside an instruction, usually to modify it
+            for inside in address + 1..address + line.bytes().len() {
+                if let Some(label) = self.label(inside) {
+                    let _ = writeln!(result, "; {} is at {:#06x}, inside the instruction above", label, inside);
+                }
+            }
+        }
+
+        result
+    }
+
+    /// The Octo statement for the instruction at the start of `bytes`, with labels in place of addresses
+    pub fn octo_statement(&self, bytes: &[u8]) -> String {
+        let opcode = ((bytes[0] as u16) << 8) | bytes[1] as u16;
+
+        // Octo's `if` runs the next statement when its condition holds, so it is the opposite of the skip
+        match Opcode::decode(opcode) {
+            Some(Opcode::Clear) => "clear".to_string(),
+            Some(Opcode::Return) => "return".to_string(),
+            Some(Opcode::ScrollRight) => "scroll-right".to_string(),
+            Some(Opcode::ScrollLeft) => "scroll-left".to_string(),
+            Some(Opcode::Exit) => "exit".to_string(),
+            Some(Opcode::LowResolution) => "lores".to_string(),
+            Some(Opcode::HighResolution) => "hires".to_string(),
+            Some(Opcode::ScrollDown { n }) => format!("scroll-down {}", n),
+            Some(Opcode::ScrollUp { n }) => format!("scroll-up {}", n),
+            Some(Opcode::Jump { nnn }) => format!("jump {}", self.label_or_address(nnn as usize)),
+            Some(Opcode::Call { nnn }) => match self.label(nnn as usize) {
+                Some(label) => label,
+                None => format!(":call {:#05x}", nnn),
+            },
+            Some(Opcode::SkipIfEqualImm { x, nn }) => format!("if v{:x} != {} then", x, nn),
+            Some(Opcode::SkipIfNotEqualImm { x, nn }) => format!("if v{:x} == {} then", x, nn),
+            Some(Opcode::SkipIfEqual { x, y }) => format!("if v{:x} != v{:x} then", x, y),
+            Some(Opcode::SaveRange { x, y }) => format!("save v{:x} - v{:x}", x, y),
+            Some(Opcode::LoadRange { x, y }) => format!("load v{:x} - v{:x}", x, y),
+            Some(Opcode::SetImm { x, nn }) => format!("v{:x} := {}", x, nn),
+            Some(Opcode::AddImm { x, nn }) => format!("v{:x} += {}", x, nn),
+            Some(Opcode::Set { x, y }) => format!("v{:x} := v{:x}", x, y),
+            Some(Opcode::Or { x, y }) => format!("v{:x} |= v{:x}", x, y),
+            Some(Opcode::And { x, y }) => format!("v{:x} &= v{:x}", x, y),
+            Some(Opcode::Xor { x, y }) => format!("v{:x} ^= v{:x}", x, y),
+            Some(Opcode::Add { x, y }) => format!("v{:x} += v{:x}", x, y),
+            Some(Opcode::Sub { x, y }) => format!("v{:x} -= v{:x}", x, y),
+            Some(Opcode::ShiftRight { x, y }) => format!("v{:x} >>= v{:x}", x, y),
+            Some(Opcode::SubReversed { x, y }) => format!("v{:x} =- v{:x}", x, y),
+            Some(Opcode::ShiftLeft { x, y }) => format!("v{:x} <<= v{:x}", x, y),
+            Some(Opcode::SkipIfNotEqual { x, y }) => format!("if v{:x} == v{:x} then", x, y),
+            Some(Opcode::SetIndex { nnn }) => format!("i := {}", self.label_or_address(nnn as usize)),
+            Some(Opcode::JumpOffset { nnn, .. }) => format!("jump0 {}", self.label_or_address(nnn as usize)),
+            Some(Opcode::Random { x, nn }) => format!("v{:x} := random {:#04x}", x, nn),
+            Some(Opcode::Draw { x, y, n }) => format!("sprite v{:x} v{:x} {}", x, y, n),
+            Some(Opcode::SkipIfKey { x }) => format!("if v{:x} -key then", x),
+            Some(Opcode::SkipIfNotKey { x }) => format!("if v{:x} key then", x),
+            Some(Opcode::SetIndexLong) if bytes.len() >= 4 => {
+                let address = ((bytes[2] as usize) << 8) | bytes[3] as usize;
+                format!("i := long {}", self.label_or_address(address))
+            },
+            Some(Opcode::SelectPlanes { n }) => format!("plane {}", n),
+            Some(Opcode::LoadAudio) => "audio".to_string(),
+            Some(Opcode::GetDelay { x }) => format!("v{:x} := delay", x),
+            Some(Opcode::WaitKey { x }) => format!("v{:x} := key", x),
+            Some(Opcode::SetDelay { x }) => format!("delay := v{:x}", x),
+            Some(Opcode::SetSound { x }) => format!("buzzer := v{:x}", x),
+            Some(Opcode::AddIndex { x }) => format!("i += v{:x}", x),
+            Some(Opcode::Font { x }) => format!("i := hex v{:x}", x),
+            Some(Opcode::LargeFont { x }) => format!("i := bighex v{:x}", x),
+            Some(Opcode::Bcd { x }) => format!("bcd v{:x}", x),
+            Some(Opcode::SetPitch { x }) => format!("pitch := v{:x}", x),
+            Some(Opcode::Save { x }) => format!("save v{:x}", x),
+            Some(Opcode::Load { x }) => format!("load v{:x}", x),
+            Some(Opcode::SaveFlags { x }) => format!("saveflags v{:x}", x),
+            Some(Opcode::LoadFlags { x }) => format!("loadflags v{:x}", x),
+            Some(Opcode::SetIndexLong) | None => octo_bytes(bytes),
+        }
+    }
+
+    /// Octo source that assembles back to the same bytes
+    pub fn octo(&self) -> String {
+        let mut result = String::new();
+        if self.start != DEFAULT_START {
+            let _ = writeln!(result, ":org {:#05x}", self.start);
+        }
+        // Octo starts programs at main, jumping to it unless it comes first
+        let _ = writeln!(result, ": main");
+
+        let write_label = |result: &mut String, address: usize| {
+            if let Some(label) = self.label(address) {
+                if !result.is_empty() {
+                    result.push('\n');
+                }
+                let _ = writeln!(result, ": {}", label);
+            }
+        };
+
+        for line in self.lines() {
+            let address = line.address();
+            write_label(&mut result, address);
+
+            match &line {
+                Line::Instruction { bytes, .. } => {
+                    let inside: Vec<usize> = (address + 1..address + bytes.len()).filter(|inside| self.label(*inside).is_some()).collect();
+                    if inside.is_empty() {
+                        let statement = self.octo_statement(bytes);
+                        // Keep the statement an `if` guards on the same line
+                        if result.ends_with(" then\n") && self.label(address).is_none() && !statement.starts_with("if ") {
+                            result.pop();
+                            let _ = writeln!(result, " {}", statement);
+                        } else {
+                            let _ = writeln!(result, "\t{}", statement);
+                        }
+                        continue;
+                    }
+
+                    // Labels inside an instruction can only be placed between its bytes
+                    let _ = writeln!(result, "\t# {}", self.octo_statement(bytes));
+                    for (offset, byte) in bytes.iter().enumerate() {
+                        if offset > 0 {
+                            write_label(&mut result, address + offset);
+                        }
+                        let _ = writeln!(result, "\t{}", octo_bytes(&[*byte]));
+                    }
+                },
+                Line::Data { bytes, .. } => {
+                    let _ = writeln!(result, "\t{}", octo_bytes(bytes));
+                },
+            }
+        }
+
+        result
+    }
+}
+
+fn octo_bytes(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!(":byte {:#04x}", byte)).collect::<Vec<String>>().join(" ")
+}
+
+fn flow(opcode: Opcode) -> Flow {
+    match opcode {
+        Opcode::Return | Opcode::Exit => Flow::Stop,
+        Opcode::Jump { nnn } => Flow::Jump(nnn as usize),
+        Opcode::Call { nnn } => Flow::Call(nnn as usize),
+        Opcode::SkipIfEqualImm { .. } | Opcode::SkipIfNotEqualImm { .. } | Opcode::SkipIfEqual { .. } | Opcode::SkipIfNotEqual { .. } => Flow::Skip,
+        Opcode::SkipIfKey { .. } | Opcode::SkipIfNotKey { .. } => Flow::Skip,
+        Opcode::JumpOffset { nnn, .. } => Flow::Table(nnn as usize),
+        _ => Flow::Next,
+    }
+}
@@ -1,7 +1,20 @@
+/// Bytes per character of the regular 4x5 font
+pub const CHARACTER_SIZE: usize = 5;
+/// Bytes per character of the SUPER-CHIP 8x10 font
+pub const LARGE_CHARACTER_SIZE: usize = 10;
+/// Where the large font is placed relative to the start of the regular font, directly after its 16 characters
+pub const LARGE_FONT_OFFSET: usize = 16 * CHARACTER_SIZE;
+
 pub struct Font {
     contents: Vec<Vec<u8>>
 }
 
+impl Default for Font {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Font {
     pub fn new() -> Self {
         Font {
@@ -25,6 +38,30 @@ impl Font {
             ]
         }
     }
+
+    /// SUPER-CHIP 8x10 font, used by FX30
+    pub fn new_large() -> Self {
+        Font {
+            contents: vec![
+                vec![0xFF, 0xFF, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xFF, 0xFF], // 0
+                vec![0x18, 0x78, 0x78, 0x18, 0x18, 0x18, 0x18, 0x18, 0xFF, 0xFF], // 1
+                vec![0xFF, 0xFF, 0x03, 0x03, 0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF], // 2
+                vec![0xFF, 0xFF, 0x03, 0x03, 0xFF, 0xFF, 0x03, 0x03, 0xFF, 0xFF], // 3
+                vec![0xC3, 0xC3, 0xC3, 0xC3, 0xFF, 0xFF, 0x03, 0x03, 0x03, 0x03], // 4
+                vec![0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0x03, 0x03, 0xFF, 0xFF], // 5
+                vec![0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0xC3, 0xC3, 0xFF, 0xFF], // 6
+                vec![0xFF, 0xFF, 0x03, 0x03, 0x06, 0x0C, 0x18, 0x18, 0x18, 0x18], // 7
+                vec![0xFF, 0xFF, 0xC3, 0xC3, 0xFF, 0xFF, 0xC3, 0xC3, 0xFF, 0xFF], // 8
+                vec![0xFF, 0xFF, 0xC3, 0xC3, 0xFF, 0xFF, 0x03, 0x03, 0xFF, 0xFF], // 9
+                vec![0x7E, 0xFF, 0xC3, 0xC3, 0xC3, 0xFF, 0xFF, 0xC3, 0xC3, 0xC3], // A
+                vec![0xFC, 0xFC, 0xC3, 0xC3, 0xFC, 0xFC, 0xC3, 0xC3, 0xFC, 0xFC], // B
+                vec![0x3C, 0xFF, 0xC3, 0xC0, 0xC0, 0xC0, 0xC0, 0xC3, 0xFF, 0x3C], // C
+                vec![0xFC, 0xFE, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xFE, 0xFC], // D
+                vec![0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF], // E
+                vec![0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0xC0, 0xC0, 0xC0, 0xC0]  // F
+            ]
+        }
+    }
 }
 
 impl Clone for Font {
@@ -40,7 +77,8 @@ impl chip8_traits::Font for Font {
         let mut offset = start;
         for character in self.contents.iter() {
             for value in character.iter() {
-                memory.set(offset, *value);
+                // A font placed past the end of memory is cut short
+                let _ = memory.set(offset, *value);
                 offset += 1;
             }
         }
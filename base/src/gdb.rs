@@ -0,0 +1,495 @@
+use std::{io::{self, BufRead, BufReader, Read, Write}, net::{TcpListener, TcpStream, ToSocketAddrs}};
+
+use chip8_traits::{Memory, Timer};
+
+use crate::{Access, Debugger, StopReason, debugger::{Breakpoint, Watchpoint}};
+
+/// Registers in the order `g` and `p` number them
+const REGISTERS: [(&str, usize); 20] = [
+    ("v0", 1), ("v1", 1), ("v2", 1), ("v3", 1), ("v4", 1), ("v5", 1), ("v6", 1), ("v7", 1),
+    ("v8", 1), ("v9", 1), ("va", 1), ("vb", 1), ("vc", 1), ("vd", 1), ("ve", 1), ("vf", 1),
+    ("i", 2), ("pc", 2), ("dt", 1), ("st", 1),
+];
+const INDEX_REGISTER: usize = 16;
+const PROGRAM_COUNTER: usize = 17;
+const DELAY_TIMER: usize = 18;
+const SOUND_TIMER: usize = 19;
+
+const INTERRUPT: u8 = 0x03;
+
+const ERROR: &str = "E01";
+
+/// Wait for one debugger to connect to `address`, and serve it until it detaches
+pub fn serve<Renderer, Keypad, Random>(debugger: &mut Debugger<Renderer, Keypad, Random>, address: impl ToSocketAddrs) -> io::Result<()>
+where Renderer: chip8_traits::Renderer,
+    Keypad: chip8_traits::Keypad,
+    Random: chip8_traits::Random {
+    let listener = TcpListener::bind(address)?;
+    let (stream, _) = listener.accept()?;
+
+    GdbStub::new(debugger, stream)?.run()
+}
+
+/// Serves the GDB remote serial protocol for a single connection
+pub struct GdbStub<'debugger, Renderer, Keypad, Random>
+where Renderer: chip8_traits::Renderer,
+    Keypad: chip8_traits::Keypad,
+    Random: chip8_traits::Random {
+    debugger: &'debugger mut Debugger<Renderer, Keypad, Random>,
+
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+
+    /// Set by `QStartNoAckMode`, packets are no longer acknowledged either way
+    no_acknowledgement: bool,
+}
+
+impl<'debugger, Renderer, Keypad, Random> GdbStub<'debugger, Renderer, Keypad, Random>
+where Renderer: chip8_traits::Renderer,
+    Keypad: chip8_traits::Keypad,
+    Random: chip8_traits::Random {
+    pub fn new(debugger: &'debugger mut Debugger<Renderer, Keypad, Random>, stream: TcpStream) -> io::Result<GdbStub<'debugger, Renderer, Keypad, Random>> {
+        stream.set_nodelay(true)?;
+        let writer = stream.try_clone()?;
+
+        Ok(GdbStub {
+            debugger,
+
+            reader: BufReader::new(stream),
+            writer,
+
+            no_acknowledgement: false,
+        })
+    }
+
+    /// Answer packets until the debugger detaches, kills the program or disconnects
+    pub fn run(&mut self) -> io::Result<()> {
+        loop {
+            let Some(packet) = self.read_packet()? else {
+                return Ok(());
+            };
+
+            match packet.as_str() {
+                "D" => {
+                    self.write_packet("OK")?;
+                    return Ok(());
+                },
+                "k" => return Ok(()),
+                _ => {
+                    let response = self.handle(&packet)?;
+                    self.write_packet(&response)?;
+                },
+            }
+        }
+    }
+
+    fn handle(&mut self, packet: &str) -> io::Result<String> {
+        let Some(command) = packet.chars().next() else {
+            return Ok("".to_string());
+        };
+        let arguments = &packet[command.len_utf8()..];
+
+        let response = match command {
+            '?' => "S05".to_string(),
+            'g' => (0..REGISTERS.len()).map(|register| self.read_register(register)).collect(),
+            'G' => self.write_registers(arguments),
+            'p' => match usize::from_str_radix(arguments, 16) {
+                Ok(register) if register < REGISTERS.len() => self.read_register(register),
+                _ => ERROR.to_string(),
+            },
+            'P' => self.write_register(arguments),
+            'm' => self.read_memory(arguments),
+            'M' => self.write_memory(arguments),
+            'Z' => self.insert_breakpoint(arguments),
+            'z' => self.remove_breakpoint(arguments),
+            'c' => {
+                self.resume_at(arguments);
+                self.resume()?
+            },
+            's' => {
+                self.resume_at(arguments);
+                let reason = self.debugger.step_into();
+                stop_reply(reason)
+            },
+            'H' => "OK".to_string(),
+            'T' => "OK".to_string(),
+            'q' | 'Q' => self.query(packet),
+            _ => "".to_string(),
+        };
+
+        Ok(response)
+    }
+
+    fn query(&mut self, packet: &str) -> String {
+        if packet.starts_with("qSupported") {
+            return "PacketSize=4000;qXfer:features:read+;QStartNoAckMode+".to_string();
+        }
+        if packet == "QStartNoAckMode" {
+            self.no_acknowledgement = true;
+            return "OK".to_string();
+        }
+        if let Some(range) = packet.strip_prefix("qXfer:features:read:target.xml:") {
+            return read_chunk(&target_description(), range);
+        }
+
+        match packet {
+            "qAttached" => "1".to_string(),
+            "qC" => "QC1".to_string(),
+            "qfThreadInfo" => "m1".to_string(),
+            "qsThreadInfo" => "l".to_string(),
+            _ => "".to_string(),
+        }
+    }
+
+    /// Little endian hex, as GDB expects from the target description
+    fn read_register(&self, register: usize) -> String {
+        let interpreter = self.debugger.interpreter();
+        let value = match register {
+            INDEX_REGISTER => interpreter.index_register(),
+            PROGRAM_COUNTER => interpreter.dump_program_counter(),
+            DELAY_TIMER => interpreter.delay_timer().get() as usize,
+            SOUND_TIMER => interpreter.sound_timer().get() as usize,
+            register => interpreter.variable_registers().get(register as u8).unwrap_or(0) as usize,
+        };
+
+        to_hex(&value.to_le_bytes()[..REGISTERS[register].1])
+    }
+
+    fn set_register(&mut self, register: usize, bytes: &[u8]) {
+        let value = bytes.iter().rev().fold(0, |value, byte| (value << 8) | *byte as usize);
+        let interpreter = self.debugger.interpreter_mut();
+
+        match register {
+            INDEX_REGISTER => interpreter.set_index_register(value),
+            PROGRAM_COUNTER => interpreter.set_program_counter(value),
+            DELAY_TIMER => interpreter.delay_timer_mut().set(value as u8),
+            SOUND_TIMER => interpreter.sound_timer_mut().set(value as u8),
+            register => {
+                let _ = interpreter.variable_registers_mut().set(register as u8, value as u8);
+            },
+        }
+    }
+
+    fn write_registers(&mut self, arguments: &str) -> String {
+        let Some(bytes) = from_hex(arguments) else {
+            return ERROR.to_string();
+        };
+        if bytes.len() != REGISTERS.iter().map(|(_, size)| size).sum::<usize>() {
+            return ERROR.to_string();
+        }
+
+        let mut offset = 0;
+        for (register, (_, size)) in REGISTERS.iter().enumerate() {
+            self.set_register(register, &bytes[offset..offset + size]);
+            offset += size;
+        }
+
+        "OK".to_string()
+    }
+
+    /// `register=value`
+    fn write_register(&mut self, arguments: &str) -> String {
+        let mut parts = arguments.splitn(2, '=');
+        let register = parts.next().and_then(|register| usize::from_str_radix(register, 16).ok());
+        let bytes = parts.next().and_then(from_hex);
+
+        match (register, bytes) {
+            (Some(register), Some(bytes)) if register < REGISTERS.len() && bytes.len() == REGISTERS[register].1 => {
+                self.set_register(register, &bytes);
+                "OK".to_string()
+            },
+            _ => ERROR.to_string(),
+        }
+    }
+
+    /// The `address,length` range when it lies within memory
+    fn memory_range(&self, arguments: &str) -> Option<(usize, usize)> {
+        let mut parts = arguments.splitn(2, ',');
+        let address = usize::from_str_radix(parts.next()?, 16).ok()?;
+        let length = usize::from_str_radix(parts.next()?, 16).ok()?;
+
+        if address.checked_add(length)? > self.debugger.interpreter().memory().len() {
+            return None;
+        }
+
+        Some((address, length))
+    }
+
+    fn read_memory(&self, arguments: &str) -> String {
+        let Some((address, length)) = self.memory_range(arguments) else {
+            return ERROR.to_string();
+        };
+
+        match self.debugger.interpreter().memory().get_range(address, length) {
+            Ok(bytes) => to_hex(&bytes),
+            Err(_) => ERROR.to_string(),
+        }
+    }
+
+    /// `address,length:bytes`
+    fn write_memory(&mut self, arguments: &str) -> String {
+        let mut parts = arguments.splitn(2, ':');
+        let range = parts.next().and_then(|range| self.memory_range(range));
+        let bytes = parts.next().and_then(from_hex);
+
+        let (Some((address, length)), Some(bytes)) = (range, bytes) else {
+            return ERROR.to_string();
+        };
+        if bytes.len() != length {
+            return ERROR.to_string();
+        }
+
+        let memory = self.debugger.interpreter_mut().memory_mut();
+        for (offset, value) in bytes.iter().enumerate() {
+            if memory.set(address + offset, *value).is_err() {
+                return ERROR.to_string();
+            }
+        }
+
+        "OK".to_string()
+    }
+
+    /// `type,address,kind` with the kind of a watchpoint being its length
+    fn parse_breakpoint(arguments: &str) -> Option<(char, usize, usize)> {
+        let mut parts = arguments.splitn(3, ',');
+        let kind = parts.next()?.chars().next()?;
+        let address = usize::from_str_radix(parts.next()?, 16).ok()?;
+        let length = usize::from_str_radix(parts.next()?.split(';').next()?, 16).ok()?;
+
+        Some((kind, address, length))
+    }
+
+    fn insert_breakpoint(&mut self, arguments: &str) -> String {
+        let Some((kind, address, length)) = Self::parse_breakpoint(arguments) else {
+            return ERROR.to_string();
+        };
+
+        match kind {
+            '0' | '1' => self.debugger.add_breakpoint(Breakpoint { address, condition: None }),
+            '2' | '3' | '4' => self.debugger.add_watchpoint(Watchpoint { range: address..address + length.max(1), access: watch_access(kind) }),
+            _ => return "".to_string(),
+        }
+
+        "OK".to_string()
+    }
+
+    fn remove_breakpoint(&mut self, arguments: &str) -> String {
+        let Some((kind, address, length)) = Self::parse_breakpoint(arguments) else {
+            return ERROR.to_string();
+        };
+
+        match kind {
+            '0' | '1' => {
+                self.debugger.remove_breakpoint(address);
+            },
+            '2' | '3' | '4' => {
+                let watchpoint = Watchpoint { range: address..address + length.max(1), access: watch_access(kind) };
+                if let Some(index) = self.debugger.watchpoints().iter().position(|existing| *existing == watchpoint) {
+                    self.debugger.remove_watchpoint(index);
+                }
+            },
+            _ => return "".to_string(),
+        }
+
+        "OK".to_string()
+    }
+
+    /// `c` and `s` may give an address to resume from
+    fn resume_at(&mut self, arguments: &str) {
+        if let Ok(address) = usize::from_str_radix(arguments, 16) {
+            self.debugger.interpreter_mut().set_program_counter(address);
+        }
+    }
+
+    /// Run frames in real time until something stops the program or the debugger interrupts it
+    fn resume(&mut self) -> io::Result<String> {
+        chip8_traits::interpreter::run_paced(|| {
+            if let Some(reason) = self.debugger.run_frame() {
+                return Ok(Some(stop_reply(reason)));
+            }
+            if self.interrupted()? {
+                return Ok(Some("S02".to_string()));
+            }
+
+            Ok(None)
+        })
+    }
+
+    /// Whether the debugger sent an interrupt, without waiting for one
+    fn interrupted(&mut self) -> io::Result<bool> {
+        if self.reader.buffer().is_empty() {
+            self.reader.get_ref().set_nonblocking(true)?;
+            let result = self.reader.fill_buf().map(|_| ());
+            self.reader.get_ref().set_nonblocking(false)?;
+
+            match result {
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => return Ok(false),
+                result => result?,
+            }
+        }
+
+        if self.reader.buffer().first() == Some(&INTERRUPT) {
+            self.reader.consume(1);
+            return Ok(true);
+        }
+
+        Ok(false)
+    }
+
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        let mut byte = [0];
+        match self.reader.read(&mut byte)? {
+            0 => Ok(None),
+            _ => Ok(Some(byte[0])),
+        }
+    }
+
+    /// The next `$packet#checksum`, acknowledging it, or None once the connection closes
+    fn read_packet(&mut self) -> io::Result<Option<String>> {
+        loop {
+            let Some(byte) = self.read_byte()? else {
+                return Ok(None);
+            };
+
+            match byte {
+                b'$' => {},
+                // Already stopped, so an interrupt only needs reporting
+                INTERRUPT => {
+                    self.write_packet("S02")?;
+                    continue;
+                },
+                _ => continue,
+            }
+
+            let mut contents = vec![];
+            loop {
+                let Some(byte) = self.read_byte()? else {
+                    return Ok(None);
+                };
+                if byte == b'#' {
+                    break;
+                }
+                contents.push(byte);
+            }
+
+            let mut checksum = [0; 2];
+            self.reader.read_exact(&mut checksum)?;
+            let expected = std::str::from_utf8(&checksum).ok().and_then(|checksum| u8::from_str_radix(checksum, 16).ok());
+            let valid = expected == Some(packet_checksum(&contents));
+
+            if !self.no_acknowledgement {
+                self.writer.write_all(if valid { b"+" } else { b"-" })?;
+            }
+            if valid {
+                return Ok(Some(String::from_utf8_lossy(&unescape(&contents)).into_owned()));
+            }
+        }
+    }
+
+    fn write_packet(&mut self, contents: &str) -> io::Result<()> {
+        let packet = format!("${}#{:02x}", contents, packet_checksum(contents.as_bytes()));
+
+        loop {
+            self.writer.write_all(packet.as_bytes())?;
+            self.writer.flush()?;
+            if self.no_acknowledgement {
+                return Ok(());
+            }
+
+            // Resend until acknowledged
+            match self.read_byte()? {
+                Some(b'-') => continue,
+                _ => return Ok(()),
+            }
+        }
+    }
+}
+
+fn stop_reply(reason: StopReason) -> String {
+    match reason {
+        StopReason::Watchpoint { location, access, .. } => {
+            let kind = match access {
+                Access::Read => "rwatch",
+                Access::Write => "watch",
+                Access::ReadWrite => "awatch",
+            };
+            format!("T05{}:{:x};", kind, location)
+        },
+        StopReason::Exited => "W00".to_string(),
+        // SIGILL, for instructions the interpreter couldn't execute
+        StopReason::Error(_) => "S04".to_string(),
+        _ => "S05".to_string(),
+    }
+}
+
+fn watch_access(kind: char) -> Access {
+    match kind {
+        '2' => Access::Write,
+        '3' => Access::Read,
+        _ => Access::ReadWrite,
+    }
+}
+
+/// Describes the registers, as there is no CHIP-8 architecture built into GDB
+fn target_description() -> String {
+    let mut result = String::from("<?xml version=\"1.0\"?><!DOCTYPE target SYSTEM \"gdb-target.dtd\"><target version=\"1.0\"><feature name=\"org.chip8.core\">");
+    for (name, size) in REGISTERS.iter() {
+        let kind = match *name {
+            "i" => "data_ptr",
+            "pc" => "code_ptr",
+            _ => "uint8",
+        };
+        result.push_str(&format!("<reg name=\"{}\" bitsize=\"{}\" type=\"{}\"/>", name, size * 8, kind));
+    }
+    result.push_str("</feature></target>");
+
+    result
+}
+
+/// Answer a `qXfer` read of `offset,length`, prefixed with `l` when it reaches the end
+fn read_chunk(contents: &str, range: &str) -> String {
+    let mut parts = range.splitn(2, ',');
+    let offset = parts.next().and_then(|offset| usize::from_str_radix(offset, 16).ok());
+    let length = parts.next().and_then(|length| usize::from_str_radix(length, 16).ok());
+
+    let (Some(offset), Some(length)) = (offset, length) else {
+        return ERROR.to_string();
+    };
+    if offset >= contents.len() {
+        return "l".to_string();
+    }
+
+    let end = offset.saturating_add(length).min(contents.len());
+    let prefix = if end == contents.len() { "l" } else { "m" };
+    format!("{}{}", prefix, &contents[offset..end])
+}
+
+fn packet_checksum(contents: &[u8]) -> u8 {
+    contents.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte))
+}
+
+/// Undo `}` escaping, which clients use for `#`, `$`, `}` and `*` in binary data
+fn unescape(contents: &[u8]) -> Vec<u8> {
+    let mut result = vec![];
+    let mut bytes = contents.iter();
+    while let Some(byte) = bytes.next() {
+        match byte {
+            b'}' => result.extend(bytes.next().map(|escaped| escaped ^ 0x20)),
+            byte => result.push(*byte),
+        }
+    }
+
+    result
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn from_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+
+    (0..text.len()).step_by(2).map(|index| u8::from_str_radix(text.get(index..index + 2)?, 16).ok()).collect()
+}
@@ -1,104 +1,112 @@
-use std::{convert::TryInto, fmt};
+use std::fmt;
 
+use crate::Opcode;
+
+/// An instruction as fetched from memory, decoded once into its `Opcode`
+///
+/// The `chip8_traits::Instruction` bit accessors are kept as an adapter over the raw opcode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Instruction {
-    first: u8,
-    second: u8
+    word: u16,
+    /// The word following a double width instruction
+    extension: Option<u16>,
+    opcode: Option<Opcode>,
 }
 
-// TODO: remove panics
-// TODO: double check trading in [bool; x]s vs one or two bytes from a design perspective
 impl Instruction {
 
     pub fn new(first: u8, second: u8) -> Instruction {
+        let word = u16::from_be_bytes([first, second]);
+
         Instruction {
-            first,
-            second
+            word,
+            extension: None,
+            opcode: Opcode::decode(word),
         }
     }
 
-    fn get_bits(from: u8) -> [bool; 8] {
-        let mut result = [false; 8];
-        
-        result[7] = from & (1 << 0) != 0;
-        result[6] = from & (1 << 1) != 0;
-        result[5] = from & (1 << 2) != 0;
-        result[4] = from & (1 << 3) != 0;
-        result[3] = from & (1 << 4) != 0;
-        result[2] = from & (1 << 5) != 0;
-        result[1] = from & (1 << 6) != 0;
-        result[0] = from & (1 << 7) != 0;
+    /// A double width instruction, such as XO-CHIP's F000 NNNN
+    pub fn new_long(first: u8, second: u8, third: u8, fourth: u8) -> Instruction {
+        Instruction {
+            extension: Some(u16::from_be_bytes([third, fourth])),
+            ..Instruction::new(first, second)
+        }
+    }
 
-        result
+    /// Whether an instruction starting with these two bytes is followed by another word
+    pub fn is_long(first: u8, second: u8) -> bool {
+        first == 0xf0 && second == 0x00
     }
-}
 
-impl Copy for Instruction {}
+    /// The raw opcode
+    pub fn word(&self) -> u16 {
+        self.word
+    }
+
+    /// The word following a double width instruction, otherwise 0
+    pub fn extension(&self) -> u16 {
+        self.extension.unwrap_or(0)
+    }
 
-impl Clone for Instruction {
-    fn clone(&self) -> Instruction {
-        Instruction::new(self.first, self.second)
+    /// None when the interpreter doesn't support the instruction
+    pub fn opcode(&self) -> Option<Opcode> {
+        self.opcode
+    }
+
+    /// The lowest `N` bits of `value`, most significant first
+    fn bits<const N: usize>(value: u16) -> [bool; N] {
+        let mut result = [false; N];
+        for (index, bit) in result.iter_mut().enumerate() {
+            *bit = value & (1 << (N - 1 - index)) != 0;
+        }
+
+        result
     }
 }
 
 impl chip8_traits::Instruction for Instruction {
     fn w(&self) -> [bool; 4] {
-        Instruction::get_bits(self.first)[0..=3].try_into().expect("Slice with incorrect length")
+        Instruction::bits(self.word >> 12)
     }
 
     fn x(&self) -> [bool; 4] {
-        Instruction::get_bits(self.first)[4..=7].try_into().expect("Slice with incorrect length")        
+        Instruction::bits(self.word >> 8)
     }
 
     fn y(&self) -> [bool; 4] {
-        Instruction::get_bits(self.second)[0..=3].try_into().expect("Slice with incorrect length")
-        
+        Instruction::bits(self.word >> 4)
     }
 
     fn n(&self) -> [bool; 4] {
-        Instruction::get_bits(self.second)[4..=7].try_into().expect("Slice with incorrect length")        
-        
+        Instruction::bits(self.word)
     }
 
     fn nn(&self) -> [bool; 8] {
-        Instruction::get_bits(self.second)
+        Instruction::bits(self.word)
     }
 
     fn nnn(&self) -> [bool; 12] {
-        let mut result = [false; 12];
+        Instruction::bits(self.word)
+    }
 
-        let mut index = 0;
-        for value in self.x().iter() {
-            result[index] = *value;
-            index += 1;
-        }
+    fn nnnn(&self) -> [bool; 16] {
+        Instruction::bits(self.extension())
+    }
 
-        for value in self.nn().iter() {
-            result[index] = *value;
-            index += 1;
+    fn width(&self) -> usize {
+        match self.extension {
+            Some(_) => 4,
+            None => 2
         }
-
-        result
     }
 }
 
 impl fmt::Display for Instruction {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:02X}{:02X}", self.first, self.second)
-    }
-}
-
-// TODO: add optional context String
-#[derive(Debug, Clone)]
-pub enum InstructionError<Instruction: chip8_traits::Instruction> {
-    UnsupportedInstructionError(Instruction),
-    InstructionExecuteError(Instruction),
-}
-
-impl<Instruction: chip8_traits::Instruction> fmt::Display for InstructionError<Instruction> {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            InstructionError::InstructionExecuteError(instruction) => write!(f, "Issue when executing instruction '{}'", instruction),
-            InstructionError::UnsupportedInstructionError(instruction) => write!(f, "Unsupported instruction '{}'", instruction)
+        match self.extension {
+            Some(extension) => write!(f, "{:04X}{:04X}", self.word, extension),
+            None => write!(f, "{:04X}", self.word)
         }
     }
 }
+
@@ -0,0 +1,61 @@
+use crate::Instruction;
+
+/// The longest instruction, XO-CHIP's F000 NNNN
+const MAX_INSTRUCTION_WIDTH: usize = 4;
+
+/// Instructions already decoded, by the address they start at
+///
+/// Entries are dropped when any byte they were decoded from is written, so self-modifying programs stay correct
+pub struct InstructionCache {
+    entries: Vec<Option<Instruction>>,
+    enabled: bool,
+}
+
+impl InstructionCache {
+    pub fn new(size: usize) -> InstructionCache {
+        InstructionCache {
+            entries: vec![None; size],
+            enabled: true,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Turning the cache off empties it, so every fetch decodes again
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        self.clear();
+    }
+
+    pub fn get(&self, location: usize) -> Option<Instruction> {
+        self.entries.get(location).copied().flatten()
+    }
+
+    pub fn insert(&mut self, location: usize, instruction: Instruction) {
+        if !self.enabled {
+            return;
+        }
+        if let Some(entry) = self.entries.get_mut(location) {
+            *entry = Some(instruction);
+        }
+    }
+
+    /// Drop any instruction that includes the byte at `location`
+    pub fn invalidate(&mut self, location: usize) {
+        let start = location.saturating_sub(MAX_INSTRUCTION_WIDTH - 1);
+        let end = (location + 1).min(self.entries.len());
+        for entry in self.entries[start.min(end)..end].iter_mut() {
+            *entry = None;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.iter_mut().for_each(|entry| *entry = None);
+    }
+
+    pub fn resize(&mut self, size: usize) {
+        self.entries = vec![None; size];
+    }
+}
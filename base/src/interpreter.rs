@@ -1,9 +1,10 @@
-use std::{borrow::{BorrowMut}, fs, usize};
+use std::{borrow::{BorrowMut}, fs};
 
 use chip8_traits::{ProgramCounter, Timer};
 
-use crate::{Instruction, bus::Bus, cpu::execute};
+use crate::{Error, ErrorContext, Instruction, bus::Bus, cpu::execute};
 
+pub const DEFAULT_INSTRUCTIONS_PER_FRAME: usize = 15;
 
 pub struct Interpreter<Renderer, Keypad, Random> 
 where Renderer: chip8_traits::Renderer, 
@@ -21,7 +22,13 @@ where Renderer: chip8_traits::Renderer,
     delay_timer: crate::DelayTimer,
     sound_timer: crate::SoundTimer,
 
+    audio: Box<dyn chip8_traits::Audio>,
+    /// What the audio output was last told, so it only hears about changes
+    audio_playing: bool,
+    audio_pattern: Option<([u8; 16], u8)>,
+
     keypad: Keypad,
+    key_latch: crate::KeyLatch,
 
     program_counter: crate::ProgramCounter,
 
@@ -30,15 +37,35 @@ where Renderer: chip8_traits::Renderer,
     variable_registers: crate::VariableRegisters,
 
     font: crate::Font,
+    large_font: crate::Font,
     font_start: usize,
 
     random: Random,
+
+    /// SUPER-CHIP persistent user flags, kept between programs
+    rpl_flags: [u8; 16],
+
+    quirks: crate::Quirks,
+
+    /// Instructions executed for each 60 Hz frame, which sets the emulation speed
+    instructions_per_frame: usize,
+    /// Instructions executed since the program was loaded
+    instruction_count: u64,
+
+    rewind: crate::RewindBuffer,
+    /// Record the state before the next instruction, set at the start of each frame
+    rewind_pending: bool,
+
+    exited: bool,
+    /// Instructions stop for the rest of the frame, as the COSMAC VIP waited for the vertical blank after drawing
+    waiting_for_frame: bool,
 }
 
 impl<Renderer, Keypad, Random> Interpreter<Renderer, Keypad, Random> 
 where Renderer: chip8_traits::Renderer, 
     Keypad: chip8_traits::Keypad,
     Random: chip8_traits::Random  {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         memory: crate::Memory,
         screen_memory: crate::ScreenMemory,
@@ -68,8 +95,13 @@ where Renderer: chip8_traits::Renderer,
             delay_timer,
     
             sound_timer,
+
+            audio: Box::new(crate::NullAudio),
+            audio_playing: false,
+            audio_pattern: None,
     
             keypad,
+            key_latch: crate::KeyLatch::new(),
     
             program_counter,
     
@@ -78,10 +110,23 @@ where Renderer: chip8_traits::Renderer,
             variable_registers: crate::VariableRegisters::new(),
     
             font,
+            large_font: crate::Font::new_large(),
             font_start: 0x050,
     
             random,
 
+            rpl_flags: [0; 16],
+
+            quirks: crate::Quirks::default(),
+
+            instructions_per_frame: DEFAULT_INSTRUCTIONS_PER_FRAME,
+            instruction_count: 0,
+
+            rewind: crate::RewindBuffer::new(0),
+            rewind_pending: true,
+
+            exited: false,
+            waiting_for_frame: false,
         }
     }
 
@@ -108,10 +153,339 @@ where Renderer: chip8_traits::Renderer,
             screen_memory: self.screen_memory.borrow_mut(),
             variable_registers: self.variable_registers.borrow_mut(),
             keypad: self.keypad.borrow_mut(),
+            key_latch: &mut self.key_latch,
             index_register: self.index_register.borrow_mut(),
             delay_timer: self.delay_timer.borrow_mut(),
             sound_timer: self.sound_timer.borrow_mut(),
-            random: self.random.borrow_mut()
+            random: self.random.borrow_mut(),
+            rpl_flags: &mut self.rpl_flags,
+            quirks: &self.quirks,
+        }
+    }
+
+    pub fn quirks(&self) -> crate::Quirks {
+        self.quirks
+    }
+
+    pub fn set_quirks(&mut self, quirks: crate::Quirks) {
+        self.quirks = quirks;
+    }
+
+    pub fn instructions_per_frame(&self) -> usize {
+        self.instructions_per_frame
+    }
+
+    pub fn set_instructions_per_frame(&mut self, instructions_per_frame: usize) {
+        self.instructions_per_frame = instructions_per_frame;
+    }
+
+    /// Resize and clear memory, for example to XO-CHIP's 64 KiB
+    pub fn set_memory_size(&mut self, size: usize) {
+        chip8_traits::Memory::set_size(&mut self.memory, size);
+    }
+
+    pub fn memory(&self) -> &crate::Memory {
+        &self.memory
+    }
+
+    pub fn memory_mut(&mut self) -> &mut crate::Memory {
+        &mut self.memory
+    }
+
+    /// What happens to reads and writes past the end of memory
+    pub fn set_memory_policy(&mut self, policy: crate::MemoryPolicy) {
+        self.memory.set_policy(policy);
+    }
+
+    /// Decoding every fetch instead of reusing decoded instructions, for comparing speed
+    pub fn set_instruction_cache_enabled(&mut self, enabled: bool) {
+        self.memory.instruction_cache_mut().set_enabled(enabled);
+    }
+
+    pub fn screen_memory(&self) -> &crate::ScreenMemory {
+        &self.screen_memory
+    }
+
+    pub fn stack(&self) -> &crate::Stack {
+        &self.stack
+    }
+
+    /// Limit call nesting, None for unbounded
+    pub fn set_stack_depth(&mut self, depth: Option<usize>) {
+        self.stack.set_depth(depth);
+    }
+
+    /// Keep return addresses in emulated memory where the COSMAC VIP interpreter did
+    pub fn set_stack_in_memory(&mut self, in_memory: bool) {
+        self.stack.set_memory_address(if in_memory { Some(crate::stack::VIP_STACK_ADDRESS) } else { None });
+    }
+
+    /// Return addresses of the calls not yet returned from, oldest first
+    pub fn call_stack(&self) -> Vec<usize> {
+        self.stack.return_addresses(&self.memory)
+    }
+
+    pub fn variable_registers(&self) -> &crate::VariableRegisters {
+        &self.variable_registers
+    }
+
+    pub fn variable_registers_mut(&mut self) -> &mut crate::VariableRegisters {
+        &mut self.variable_registers
+    }
+
+    pub fn index_register(&self) -> usize {
+        self.index_register
+    }
+
+    pub fn set_index_register(&mut self, value: usize) {
+        self.index_register = value;
+    }
+
+    pub fn set_program_counter(&mut self, position: usize) {
+        self.program_counter.set_position(position);
+    }
+
+    pub fn delay_timer(&self) -> &crate::DelayTimer {
+        &self.delay_timer
+    }
+
+    pub fn delay_timer_mut(&mut self) -> &mut crate::DelayTimer {
+        &mut self.delay_timer
+    }
+
+    pub fn sound_timer(&self) -> &crate::SoundTimer {
+        &self.sound_timer
+    }
+
+    pub fn sound_timer_mut(&mut self) -> &mut crate::SoundTimer {
+        &mut self.sound_timer
+    }
+
+    pub fn keypad(&self) -> &Keypad {
+        &self.keypad
+    }
+
+    pub fn keypad_mut(&mut self) -> &mut Keypad {
+        &mut self.keypad
+    }
+
+    pub fn random_mut(&mut self) -> &mut Random {
+        &mut self.random
+    }
+
+    pub fn set_audio(&mut self, audio: Box<dyn chip8_traits::Audio>) {
+        self.audio = audio;
+        self.audio_playing = false;
+        self.audio_pattern = None;
+    }
+
+    /// Capture the whole machine, see `SaveState::to_bytes` for the format
+    pub fn save_state(&self) -> Vec<u8> {
+        let keys_held = chip8_traits::Keypad::state(&self.keypad).iter().enumerate()
+            .fold(0, |bits, (index, held)| bits | ((*held as u16) << index));
+
+        crate::SaveState {
+            program_counter: self.program_counter.get_position(),
+            index_register: self.index_register,
+            variable_registers: self.variable_registers.get_all(),
+            delay_timer: self.delay_timer.get(),
+            sound_timer: self.sound_timer.get(),
+            rpl_flags: self.rpl_flags,
+            exited: self.exited,
+
+            memory: chip8_traits::Memory::dump(&self.memory),
+
+            screen_width: self.screen_memory.width(),
+            screen_height: self.screen_memory.height(),
+            high_resolution: chip8_traits::ScreenMemory::is_high_resolution(&self.screen_memory),
+            selected_planes: chip8_traits::ScreenMemory::selected_planes(&self.screen_memory),
+            screen: self.screen_memory.iter().flatten().cloned().collect(),
+
+            stack: self.stack.values().to_vec(),
+
+            audio_pattern: self.sound_timer.pattern(),
+            pitch: self.sound_timer.pitch(),
+
+            quirks: self.quirks,
+
+            random: chip8_traits::Random::state(&self.random),
+
+            keys_held,
+            key_latch: self.key_latch,
+        }.to_bytes()
+    }
+
+    /// Restore a state made by `save_state`, leaving the interpreter untouched if it can't be read
+    pub fn load_state(&mut self, bytes: &[u8]) -> Result<(), crate::SaveStateError> {
+        let state = crate::SaveState::from_bytes(bytes)?;
+        self.restore_state(state);
+
+        self.instruction_count = 0;
+        self.rewind.clear();
+        self.rewind_pending = true;
+
+        Ok(())
+    }
+
+    fn restore_state(&mut self, state: crate::SaveState) {
+        self.program_counter.set_position(state.program_counter);
+        self.index_register = state.index_register;
+        self.variable_registers.set_all(state.variable_registers);
+        self.delay_timer.set(state.delay_timer);
+        self.sound_timer.set(state.sound_timer);
+        self.rpl_flags = state.rpl_flags;
+        self.exited = state.exited;
+        self.waiting_for_frame = false;
+
+        self.memory.restore(state.memory);
+
+        self.screen_memory.restore(state.screen_width, state.screen_height, state.high_resolution, state.selected_planes, &state.screen);
+
+        self.stack.restore(state.stack);
+
+        self.sound_timer.set_pattern(state.audio_pattern);
+        self.sound_timer.set_pitch(state.pitch);
+
+        self.quirks = state.quirks;
+
+        chip8_traits::Random::set_state(&mut self.random, &state.random);
+
+        self.key_latch = state.key_latch;
+    }
+
+    /// Go to the start of a movie, to replay it with a `MoviePlayer` for the keypad
+    pub fn start_replay(&mut self, movie: &crate::Movie) -> Result<(), crate::SaveStateError> {
+        self.load_state(&movie.start_state)?;
+        self.instructions_per_frame = movie.instructions_per_frame as usize;
+
+        Ok(())
+    }
+
+    pub fn instruction_count(&self) -> u64 {
+        self.instruction_count
+    }
+
+    /// Keep the state at the start of each of the last `frames` frames so they can be rewound to, 0 turns rewinding off
+    pub fn set_rewind_frames(&mut self, frames: usize) {
+        self.rewind = crate::RewindBuffer::new(frames);
+        self.rewind_pending = true;
+    }
+
+    /// Frames that can currently be rewound
+    pub fn rewind_len(&self) -> usize {
+        self.rewind.len()
+    }
+
+    /// Go back to the start of the frame `frames` frames ago, or the oldest one kept
+    pub fn rewind(&mut self, frames: usize) -> bool {
+        if frames == 0 || self.rewind.is_empty() {
+            return false;
+        }
+
+        self.restore_rewind(self.rewind.len().saturating_sub(frames))
+    }
+
+    /// Go back to the state before the last instruction, by replaying from the start of its frame
+    ///
+    /// Exact as long as the random generator's state can be restored and the keypad gives the same answers
+    pub fn reverse_step(&mut self) -> bool {
+        let Some(target) = self.instruction_count.checked_sub(1) else {
+            return false;
+        };
+        let Some(index) = self.rewind.find(target) else {
+            return false;
+        };
+        if !self.restore_rewind(index) {
+            return false;
+        }
+
+        while self.instruction_count < target {
+            if chip8_traits::Interpreter::update(self).is_err() {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Whether the rest of the frame is skipped, after a draw with the display_wait quirk
+    pub fn is_waiting_for_frame(&self) -> bool {
+        self.waiting_for_frame
+    }
+
+    /// Restore the rewind entry at `index`, dropping it and anything newer since they will be recorded again
+    fn restore_rewind(&mut self, index: usize) -> bool {
+        let Some((instruction_count, bytes)) = self.rewind.get(index) else {
+            return false;
+        };
+        let Ok(state) = crate::SaveState::from_bytes(&bytes) else {
+            return false;
+        };
+
+        self.restore_state(state);
+        self.instruction_count = instruction_count;
+        self.rewind.truncate(index);
+        self.rewind_pending = true;
+
+        true
+    }
+
+    /// Finish a frame once its instructions have run: tick the timers and render
+    pub fn end_frame(&mut self) -> Result<(), Error> {
+        self.waiting_for_frame = false;
+        self.poll_keypad();
+
+        // The tone plays for any frame that starts with the sound timer above 0
+        self.update_audio().map_err(|error| error.with_context(self.error_context()))?;
+
+        // TODO: something seems broken that I can't do "use" and have to fully qualify or when that fails, cast
+        (&mut self.sound_timer as &mut dyn chip8_traits::Timer).update().map_err(|error| error.with_context(self.error_context()))?;
+
+        // TODO: something seems broken that I can't do "use" and have to fully qualify or when that fails, cast
+        (&mut self.delay_timer as &mut dyn chip8_traits::Timer).update().map_err(|error| error.with_context(self.error_context()))?;
+
+        self.rewind_pending = true;
+
+        chip8_traits::Interpreter::render(self)
+    }
+
+    /// Take the keypad's presses and releases for the next frame's instructions
+    fn poll_keypad(&mut self) {
+        let events = chip8_traits::Keypad::take_events(&mut self.keypad);
+        let state = chip8_traits::Keypad::state(&self.keypad);
+        self.key_latch.update(&events, state);
+    }
+
+    /// Pass sound timer changes on to the audio output
+    fn update_audio(&mut self) -> Result<(), Error> {
+        let pattern = (self.sound_timer.pattern(), self.sound_timer.pitch());
+        if self.audio_pattern != Some(pattern) {
+            self.audio.set_pattern(pattern.0, self.sound_timer.sample_rate())?;
+            self.audio_pattern = Some(pattern);
+        }
+
+        let playing = self.sound_timer.is_playing();
+        if playing != self.audio_playing {
+            self.audio.set_playing(playing)?;
+            self.audio_playing = playing;
+        }
+
+        self.audio.update_frame()
+    }
+
+    /// Where the program is now, for errors raised between instructions
+    fn error_context(&self) -> ErrorContext {
+        let position = self.program_counter.get_position();
+        let memory = &self.memory as &dyn chip8_traits::Memory;
+        let opcode = match (memory.get(position), memory.get(position + 1)) {
+            (Ok(first), Ok(second)) => (first as u16) << 8 | second as u16,
+            _ => 0,
+        };
+
+        ErrorContext {
+            program_counter: position,
+            opcode,
+            cycle: self.instruction_count,
         }
     }
 
@@ -119,24 +493,41 @@ where Renderer: chip8_traits::Renderer,
         font.apply(&mut self.memory, self.font_start);
     }
 
-    fn fetch(&mut self) -> Box<crate::Instruction> {
-        // chip8_traits::ProgramCounter::read(&mut self.program_counter, self.memory.as_ref())
-
+    fn fetch(&mut self) -> Result<crate::Instruction, Error> {
         let position = self.program_counter.get_position();
-        let first = chip8_traits::Memory::get(&self.memory, position);
-        let second = chip8_traits::Memory::get(&self.memory, position + 1);
-        self.program_counter.skip();
+        let Some(instruction) = self.memory.instruction(position) else {
+            return Err(Error::ProgramCounterOutOfRange(self.error_context()));
+        };
+        self.program_counter.set_position(position + chip8_traits::Instruction::width(&instruction));
 
-        Box::new(super::Instruction::new(first, second))
+        Ok(instruction)
     }
 
     fn reset(&mut self) {
         self.memory.clear();
         self.apply_font(self.font.clone());
+        chip8_traits::Font::apply(&self.large_font, &mut self.memory, self.font_start + crate::font::LARGE_FONT_OFFSET);
+        chip8_traits::ScreenMemory::set_high_resolution(&mut self.screen_memory, false);
+        self.exited = false;
+        self.waiting_for_frame = false;
+        self.key_latch = crate::KeyLatch::new();
         self.variable_registers.reset();
         self.index_register = 0;
         self.sound_timer.reset();
         self.delay_timer.reset();
+        self.instruction_count = 0;
+        self.rewind.clear();
+        self.rewind_pending = true;
+    }
+
+    fn record_rewind(&mut self) {
+        self.rewind_pending = false;
+        if self.rewind.capacity() == 0 {
+            return;
+        }
+
+        let state = self.save_state();
+        self.rewind.push(self.instruction_count, state);
     }
 }
 
@@ -147,8 +538,10 @@ where Renderer: chip8_traits::Renderer,
     fn load(&mut self, program: Vec<u8>, start_position: usize) {
         self.reset();
         
-        for (index, value) in program.iter().enumerate() {
-            chip8_traits::Memory::set(&mut self.memory, start_position + index, *value);
+        // Anything past the end of memory is dropped, load_file refuses programs that don't fit
+        let fits = self.memory.len().saturating_sub(start_position);
+        for (index, value) in program.iter().enumerate().take(fits) {
+            let _ = chip8_traits::Memory::set(&mut self.memory, start_position + index, *value);
         }
         chip8_traits::ProgramCounter::set_position(&mut self.program_counter, start_position);
     }
@@ -157,60 +550,79 @@ where Renderer: chip8_traits::Renderer,
         let result = fs::read(file_name);
         match result {
             Ok(contents) => {
+                if start_position + contents.len() > self.memory.len() {
+                    return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "Program doesn't fit in memory"));
+                }
                 self.load(contents, start_position);
-                return Ok(());
+                Ok(())
             },
             Err(error) => {
-                return Err(error);
+                Err(error)
             }
         }
     }
 
-    fn update(&mut self) -> Result<crate::cpu::ExecutionState, String> {
-        let instruction = self.fetch();
+    fn update(&mut self) -> Result<crate::cpu::ExecutionState, Error> {
+        if self.rewind_pending {
+            self.record_rewind();
+        }
+
+        let position = self.program_counter.get_position();
+        let instruction = self.fetch()?;
+        self.instruction_count += 1;
         
         let execution_state: crate::cpu::ExecutionState;
 
         let result = execute(
             true,
-            *instruction.as_ref(), 
+            instruction, 
             &mut self.program_counter, 
             &mut self.stack, 
             &mut self.memory,
             &mut self.screen_memory,
             &mut self.variable_registers,
-            &mut self.keypad,
+            &self.keypad,
+            &mut self.key_latch,
             &mut self.index_register,
             &mut self.delay_timer,
             &mut self.sound_timer,
             &mut self.random,
+            &mut self.rpl_flags,
+            &self.quirks,
             self.font_start
         );
         match result {
-            Ok(value) => { execution_state = value; },
+            Ok(value) => {
+                self.exited = value.exit;
+                self.waiting_for_frame |= value.wait_for_frame;
+                execution_state = value;
+            },
             Err(error) => {
-                return Err(format!("While executing instruction: {}", error))
+                return Err(error.with_context(ErrorContext {
+                    program_counter: position,
+                    opcode: instruction.word(),
+                    cycle: self.instruction_count,
+                }))
             }
         }
 
-        // TODO: something seems broken that I can't do "use" and have to fully qualify or when that fails, cast
-        let result = (&mut self.sound_timer as &mut dyn chip8_traits::Timer).update();
-        if let Err(error) = result {
-            return Err(error);
-        }
+        Ok(execution_state)
+    }
 
-        // TODO: something seems broken that I can't do "use" and have to fully qualify or when that fails, cast
-        let result = (&mut self.delay_timer as &mut dyn chip8_traits::Timer).update();
-        if let Err(error) = result {
-            return Err(error);
+    fn update_frame(&mut self) -> Result<(), Error> {
+        for _ in 0..self.instructions_per_frame {
+            if self.exited || self.waiting_for_frame {
+                break;
+            }
+            self.update()?;
         }
 
-        let result = self.renderer.render(self.screen_memory.iter());
-        if let Err(error) = result {
-            return Err(format!("{}", error));
-        }
+        self.end_frame()
+    }
 
-        Ok(execution_state)
+    fn render(&mut self) -> Result<(), Error> {
+        let context = self.error_context();
+        self.renderer.render(self.screen_memory.iter()).map_err(|error| error.with_context(context))
     }
 
     fn clear_screen(&mut self) {
@@ -220,6 +632,10 @@ where Renderer: chip8_traits::Renderer,
     fn dump_memory(&self) -> Vec<u8> {
         chip8_traits::Memory::dump(&self.memory)
     }
+
+    fn has_exited(&self) -> bool {
+        self.exited
+    }
 }
 
 pub struct InterpreterSnapshot {
@@ -268,16 +684,14 @@ where Renderer: chip8_traits::Renderer,
         let mut is_first = true;
         let mut first_value: u8 = 0;
         let mut first_location: usize = 0;
-        for index in 0..memory_snapshot.len() {
+        for snapshot in memory_snapshot.iter() {
             if is_first {
-                first_value = memory_snapshot[index].value;
-                first_location = memory_snapshot[index].location;
+                first_value = snapshot.value;
+                first_location = snapshot.location;
             } else {
-                let second_value = memory_snapshot[index].value;
+                let second_value = snapshot.value;
                 let instruction = Instruction::new(first_value, second_value);
-                let disassembly: String;
-                
-                match execute(
+                let disassembly = match execute(
                     false,
                     instruction, 
                     &mut self.program_counter, 
@@ -285,16 +699,19 @@ where Renderer: chip8_traits::Renderer,
                     &mut self.memory,
                     &mut self.screen_memory,
                     &mut self.variable_registers,
-                    &mut self.keypad,
+                    &self.keypad,
+                    &mut self.key_latch,
                     &mut self.index_register,
                     &mut self.delay_timer,
                     &mut self.sound_timer,
                     &mut self.random,
+                    &mut self.rpl_flags,
+                    &self.quirks,
                     self.font_start
                 ) {
-                    Ok(result) => { disassembly = result.instruction_disassembly; },
-                    Err(_) => { disassembly = "".to_string(); }
-                }
+                    Ok(result) => result.instruction_disassembly,
+                    Err(_) => "".to_string()
+                };
 
                 result.push(PartialDisassembleSnapshot {
                     location: first_location,
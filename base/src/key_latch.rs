@@ -0,0 +1,94 @@
+use chip8_traits::KeyEvent;
+
+/// Key edges seen by the interpreter, fed from the keypad once per frame, and FX0A's progress through them
+///
+/// With the key_release quirk FX0A arms the latch, takes the first key pressed (or already held) after that, and
+/// completes when that key is released, so a key held over from the previous FX0A doesn't trigger it again
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KeyLatch {
+    /// Keys down as of the last update, one bit per key
+    held: u16,
+    /// FX0A is waiting for a key
+    armed: bool,
+    /// Key FX0A saw go down, waiting for it to come up
+    pressed: Option<u8>,
+    /// Key that went down and up while armed, ready for FX0A
+    released: Option<u8>,
+}
+
+impl KeyLatch {
+    pub fn new() -> KeyLatch {
+        KeyLatch::default()
+    }
+
+    /// Apply a frame's events, then any change in `state` the events didn't cover
+    pub fn update(&mut self, events: &[KeyEvent], state: [bool; 16]) {
+        for event in events {
+            self.apply(*event);
+        }
+
+        for (index, held) in state.iter().enumerate() {
+            if *held != self.is_held(index) {
+                self.apply(if *held { KeyEvent::Pressed(index) } else { KeyEvent::Released(index) });
+            }
+        }
+    }
+
+    fn apply(&mut self, event: KeyEvent) {
+        match event {
+            KeyEvent::Pressed(index) if index < 16 => {
+                self.held |= 1 << index;
+                if self.armed && self.pressed.is_none() {
+                    self.pressed = Some(index as u8);
+                }
+            },
+            KeyEvent::Released(index) if index < 16 => {
+                self.held &= !(1 << index);
+                if self.armed && self.pressed == Some(index as u8) {
+                    self.released = Some(index as u8);
+                }
+            },
+            _ => {},
+        }
+    }
+
+    pub fn is_held(&self, index: usize) -> bool {
+        index < 16 && self.held & (1 << index) != 0
+    }
+
+    /// Start waiting for a key, taking one that is already down
+    pub fn arm(&mut self) {
+        if self.armed {
+            return;
+        }
+
+        self.armed = true;
+        self.pressed = (0..16).find(|index| self.is_held(*index)).map(|index| index as u8);
+    }
+
+    /// The key pressed and released since arming, disarming the latch once there is one
+    pub fn take_released(&mut self) -> Option<u8> {
+        let released = self.released.take()?;
+        self.armed = false;
+        self.pressed = None;
+
+        Some(released)
+    }
+
+    /// Held keys, armed, the pressed key and the released key with 0xFF for none
+    pub fn to_bytes(&self) -> [u8; 5] {
+        let held = self.held.to_le_bytes();
+        [held[0], held[1], self.armed as u8, self.pressed.unwrap_or(0xff), self.released.unwrap_or(0xff)]
+    }
+
+    pub fn from_bytes(bytes: [u8; 5]) -> KeyLatch {
+        let key = |value: u8| if value < 16 { Some(value) } else { None };
+
+        KeyLatch {
+            held: u16::from_le_bytes([bytes[0], bytes[1]]),
+            armed: bytes[2] != 0,
+            pressed: key(bytes[3]),
+            released: key(bytes[4]),
+        }
+    }
+}
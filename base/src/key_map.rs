@@ -0,0 +1,216 @@
+use std::{collections::BTreeMap, fmt, fs, path::Path};
+
+use serde::Deserialize;
+
+/// Keyboard keys in the same positions as the hex keypad, indexed by hex key
+///
+/// ```text
+/// 1 2 3 C    1 2 3 4
+/// 4 5 6 D    Q W E R
+/// 7 8 9 E    A S D F
+/// A 0 B F    Z X C V
+/// ```
+const QWERTY: [&str; 16] = ["x", "1", "2", "3", "q", "w", "e", "a", "s", "d", "z", "c", "4", "r", "f", "v"];
+
+#[derive(Debug)]
+pub enum KeyMapError {
+    Read(std::io::Error),
+    ParseJson(serde_json::Error),
+    ParseToml(toml::de::Error),
+    /// A CHIP-8 key other than 0 to F
+    InvalidKey(String),
+    /// A program hash that isn't a 32-bit hex number
+    InvalidHash(String),
+}
+
+impl fmt::Display for KeyMapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            KeyMapError::Read(error) => write!(f, "Unable to read key map: {}", error),
+            KeyMapError::ParseJson(error) => write!(f, "Unable to parse key map: {}", error),
+            KeyMapError::ParseToml(error) => write!(f, "Unable to parse key map: {}", error),
+            KeyMapError::InvalidKey(key) => write!(f, "Invalid CHIP-8 key in key map: {}", key),
+            KeyMapError::InvalidHash(hash) => write!(f, "Invalid program hash in key map: {}", hash),
+        }
+    }
+}
+
+impl std::error::Error for KeyMapError {}
+
+/// CHIP-8 key to keyboard keys, as written in a key map file
+type BindingsFile = BTreeMap<String, Vec<String>>;
+
+#[derive(Deserialize)]
+struct KeyMapFile {
+    #[serde(default)]
+    keys: BindingsFile,
+    #[serde(default)]
+    overrides: Vec<OverrideFile>,
+}
+
+#[derive(Deserialize)]
+struct OverrideFile {
+    #[serde(default)]
+    hash: Option<String>,
+    #[serde(default)]
+    title: Option<String>,
+    keys: BindingsFile,
+}
+
+/// Bindings that replace the defaults for one program
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Override {
+    /// CRC-32 of the program
+    hash: Option<u32>,
+    /// Title in the program library
+    title: Option<String>,
+    bindings: Vec<(usize, Vec<String>)>,
+}
+
+/// Keyboard keys bound to each CHIP-8 key, with overrides for particular programs
+///
+/// Keys are named as a browser's `KeyboardEvent.key` names them, ignoring case: the character for printable keys,
+/// `Space`, `ArrowUp` and so on. Files list the keys to rebind, anything not listed keeps the QWERTY layout:
+///
+/// ```toml
+/// [keys]
+/// 5 = ["w", "ArrowUp"]
+///
+/// [[overrides]]
+/// title = "AIRPLANE"
+/// keys = { 8 = ["s", "Space"] }
+/// ```
+///
+/// Overrides match a program by `title` in the program library or by `hash`, the CRC-32 of the program in hex
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyMap {
+    bindings: [Vec<String>; 16],
+    overrides: Vec<Override>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        KeyMap::new()
+    }
+}
+
+impl KeyMap {
+    /// The QWERTY layout without overrides
+    pub fn new() -> KeyMap {
+        KeyMap {
+            bindings: QWERTY.map(|key| vec![key.to_string()]),
+            overrides: vec![],
+        }
+    }
+
+    /// Parse JSON if the text starts with `{`, TOML otherwise
+    pub fn parse(text: &str) -> Result<KeyMap, KeyMapError> {
+        if text.trim_start().starts_with('{') {
+            KeyMap::parse_json(text)
+        } else {
+            KeyMap::parse_toml(text)
+        }
+    }
+
+    pub fn parse_json(json: &str) -> Result<KeyMap, KeyMapError> {
+        let file = serde_json::from_str(json).map_err(KeyMapError::ParseJson)?;
+        KeyMap::from_file(file)
+    }
+
+    pub fn parse_toml(toml: &str) -> Result<KeyMap, KeyMapError> {
+        let file = toml::from_str(toml).map_err(KeyMapError::ParseToml)?;
+        KeyMap::from_file(file)
+    }
+
+    /// Load a `.json` file as JSON and anything else as TOML
+    pub fn load_file(file_name: &str) -> Result<KeyMap, KeyMapError> {
+        let text = fs::read_to_string(file_name).map_err(KeyMapError::Read)?;
+
+        let is_json = Path::new(file_name).extension().is_some_and(|extension| extension.eq_ignore_ascii_case("json"));
+        if is_json {
+            KeyMap::parse_json(&text)
+        } else {
+            KeyMap::parse_toml(&text)
+        }
+    }
+
+    fn from_file(file: KeyMapFile) -> Result<KeyMap, KeyMapError> {
+        let mut key_map = KeyMap::new();
+        key_map.rebind(&parse_bindings(file.keys)?);
+
+        for override_file in file.overrides {
+            let hash = match override_file.hash {
+                Some(hash) => {
+                    let digits = hash.trim_start_matches("0x");
+                    Some(u32::from_str_radix(digits, 16).map_err(|_| KeyMapError::InvalidHash(hash.clone()))?)
+                },
+                None => None,
+            };
+
+            key_map.overrides.push(Override {
+                hash,
+                title: override_file.title,
+                bindings: parse_bindings(override_file.keys)?,
+            });
+        }
+
+        Ok(key_map)
+    }
+
+    fn rebind(&mut self, bindings: &[(usize, Vec<String>)]) {
+        for (index, keys) in bindings {
+            self.bindings[*index] = keys.clone();
+        }
+    }
+
+    /// Hash identifying a program for overrides
+    pub fn program_hash(program: &[u8]) -> u32 {
+        crate::save_state::crc32(program)
+    }
+
+    /// The bindings for a program, with the first override matching its hash or title applied
+    pub fn for_program(&self, hash: u32, title: Option<&str>) -> KeyMap {
+        let mut key_map = self.clone();
+
+        let title_matches = |entry: &Override| match (&entry.title, title) {
+            (Some(entry_title), Some(title)) => entry_title.eq_ignore_ascii_case(title),
+            _ => false,
+        };
+        let matching = self.overrides.iter().find(|entry| entry.hash == Some(hash) || title_matches(entry));
+        if let Some(entry) = matching {
+            key_map.rebind(&entry.bindings);
+        }
+
+        key_map
+    }
+
+    /// Keyboard keys bound to a CHIP-8 key
+    pub fn keys(&self, key_index: usize) -> &[String] {
+        self.bindings.get(key_index).map_or(&[], |keys| keys.as_slice())
+    }
+
+    /// CHIP-8 keys a keyboard key is bound to
+    pub fn key_indexes(&self, key: &str) -> Vec<usize> {
+        let key = normalize(key);
+
+        (0..16).filter(|index| self.bindings[*index].iter().any(|bound| normalize(bound) == key)).collect()
+    }
+}
+
+/// Lower case, with the space character named
+fn normalize(key: &str) -> String {
+    if key == " " {
+        "space".to_string()
+    } else {
+        key.to_lowercase()
+    }
+}
+
+fn parse_bindings(file: BindingsFile) -> Result<Vec<(usize, Vec<String>)>, KeyMapError> {
+    file.into_iter().map(|(key, keys)| {
+        match u8::from_str_radix(&key, 16) {
+            Ok(index) if index < 16 && key.len() == 1 => Ok((index as usize, keys)),
+            _ => Err(KeyMapError::InvalidKey(key)),
+        }
+    }).collect()
+}
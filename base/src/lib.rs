@@ -1,28 +1,55 @@
+
+pub mod audio;
+pub use self::audio::{NullAudio, PcmAudio};
 pub mod cpu;
+pub mod debugger;
+pub use self::debugger::{CallFrame, Debugger, StopReason};
 pub mod delay_timer;
 pub use self::delay_timer::DelayTimer;
+pub mod disassembler;
+pub use self::disassembler::Disassembly;
+pub use chip8_traits::{Error, ErrorContext};
 pub mod font;
 pub use self::font::Font;
+pub mod gdb;
 pub mod interpreter;
 pub use self::interpreter::Interpreter;
 pub mod instruction;
 pub use self::instruction::Instruction;
+pub mod instruction_cache;
+pub use self::instruction_cache::InstructionCache;
+pub mod key_latch;
+pub use self::key_latch::KeyLatch;
+pub mod key_map;
+pub use self::key_map::{KeyMap, KeyMapError};
 pub mod math;
 pub use self::math::*;
 pub mod memory;
-pub use self::memory::Memory;
-pub mod out_of_bounds_error;
-pub use self::out_of_bounds_error::OutOfBoundsError;
+pub use self::memory::{Access, Memory, MemoryPolicy};
+pub mod movie;
+pub use self::movie::{Movie, MovieError, MovieFrame, MoviePlayer, MovieRecorder};
+pub mod opcode;
+pub use self::opcode::Opcode;
 pub mod program_counter;
 pub use self::program_counter::ProgramCounter;
+pub mod program_library;
+pub use self::program_library::ProgramLibrary;
+pub mod quirks;
+pub use self::quirks::Quirks;
+pub mod random;
+pub use self::random::SeededRandom;
+pub mod rewind;
+pub use self::rewind::RewindBuffer;
+pub mod save_state;
+pub use self::save_state::{SaveState, SaveStateError};
 pub mod screen_memory;
 pub use self::screen_memory::ScreenMemory;
 pub mod sound_timer;
 pub use self::sound_timer::SoundTimer;
 pub mod stack;
 pub use self::stack::Stack;
+pub mod symbols;
+pub use self::symbols::SymbolMap;
 pub mod variable_registers;
 pub use self::variable_registers::VariableRegisters;
 pub mod bus;
-
-#[macro_use] extern crate guard;
@@ -1,42 +1,198 @@
-use std::{slice::Iter, usize};
+use std::cell::RefCell;
+
+use crate::{Error, ErrorContext, Instruction, InstructionCache};
+
+pub const CHIP8_SIZE: usize = 4096;
+/// XO-CHIP addresses the full 16-bit range
+pub const XO_CHIP_SIZE: usize = 0x10000;
+
+/// What happens to reads and writes past the end of memory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MemoryPolicy {
+    /// Stop with `Error::MemoryOutOfRange`
+    #[default]
+    Error,
+    /// Wrap around at the memory size, as interpreters that mask the address do
+    Wrap,
+    /// Read zero and drop writes
+    Ignore,
+}
+
+/// How a program touched memory, or which touches a watchpoint stops on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+    ReadWrite,
+}
+
+impl Access {
+    pub(crate) fn includes(&self, access: Access) -> bool {
+        *self == Access::ReadWrite || *self == access
+    }
+}
 
 pub struct Memory {
     contents: Vec<u8>,
+    instruction_cache: InstructionCache,
+    policy: MemoryPolicy,
+    /// Reads and writes through `chip8_traits::Memory` since the log was last taken, kept only while watching
+    accesses: RefCell<Vec<(usize, Access)>>,
+    watching: bool,
 }
 
 impl Memory {
     pub fn new(size: usize) -> Self {
         Memory {
-            contents: vec![0; size]
+            contents: vec![0; size],
+            instruction_cache: InstructionCache::new(size),
+            policy: MemoryPolicy::default(),
+            accesses: RefCell::new(vec![]),
+            watching: false,
         }
     }
 
     pub fn new_chip8() -> Self {
-        Memory::new(4096)
+        Memory::new(CHIP8_SIZE)
+    }
+
+    pub fn new_xo_chip() -> Self {
+        Memory::new(XO_CHIP_SIZE)
     }
 
     pub fn clear(&mut self) {
         self.contents = vec![0; self.contents.len()];
+        self.instruction_cache.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.contents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.contents.is_empty()
+    }
+
+    /// Replace the contents, resizing to match
+    pub fn restore(&mut self, contents: Vec<u8>) {
+        self.instruction_cache.resize(contents.len());
+        self.contents = contents;
+    }
+
+    pub fn policy(&self) -> MemoryPolicy {
+        self.policy
+    }
+
+    pub fn set_policy(&mut self, policy: MemoryPolicy) {
+        self.policy = policy;
+    }
+
+    /// Log every read and write of a location from here on, for watchpoints
+    pub fn set_watching(&mut self, watching: bool) {
+        self.watching = watching;
+        self.accesses.get_mut().clear();
+    }
+
+    /// Locations read or written since the last call, in order, instruction fetches not included
+    pub fn take_accesses(&mut self) -> Vec<(usize, Access)> {
+        std::mem::take(self.accesses.get_mut())
+    }
+
+    fn log_access(&self, location: usize, access: Access) {
+        if self.watching {
+            self.accesses.borrow_mut().push((location, access));
+        }
+    }
+
+    /// Where an access to `location` lands under the policy, None when it's dropped or an error
+    fn resolve(&self, location: usize) -> Option<usize> {
+        if location < self.contents.len() {
+            return Some(location);
+        }
+
+        match self.policy {
+            MemoryPolicy::Wrap if !self.contents.is_empty() => Some(location % self.contents.len()),
+            _ => None,
+        }
+    }
+
+    fn out_of_range(location: usize) -> Error {
+        Error::MemoryOutOfRange { address: location, context: ErrorContext::default() }
+    }
+
+    /// A byte for decoding, None when the policy turns the access into an error
+    fn read(&self, location: usize) -> Option<u8> {
+        match (self.resolve(location), self.policy) {
+            (Some(location), _) => Some(self.contents[location]),
+            (None, MemoryPolicy::Ignore) => Some(0),
+            (None, _) => None,
+        }
+    }
+
+    pub fn instruction_cache(&self) -> &InstructionCache {
+        &self.instruction_cache
+    }
+
+    pub fn instruction_cache_mut(&mut self) -> &mut InstructionCache {
+        &mut self.instruction_cache
+    }
+
+    /// The instruction starting at `location`, decoded once and reused until its bytes are written
+    ///
+    /// None when the instruction runs past the end of memory and the policy makes that an error
+    pub fn instruction(&mut self, location: usize) -> Option<Instruction> {
+        if let Some(instruction) = self.instruction_cache.get(location) {
+            return Some(instruction);
+        }
+
+        let first = self.read(location)?;
+        let second = self.read(location + 1)?;
+        let long = Instruction::is_long(first, second);
+        let instruction = {
+            if long {
+                Instruction::new_long(first, second, self.read(location + 2)?, self.read(location + 3)?)
+            } else {
+                Instruction::new(first, second)
+            }
+        };
+        // One wrapping past the end was partly read from the start, which invalidating a write there wouldn't drop
+        let width = if long { 4 } else { 2 };
+        if location + width <= self.contents.len() {
+            self.instruction_cache.insert(location, instruction);
+        }
+
+        Some(instruction)
     }
 }
 
 impl chip8_traits::Memory for Memory {
     fn set_size(&mut self, size: usize) {
         self.contents = vec![0; size];
+        self.instruction_cache.resize(size);
     }
 
-    fn set(&mut self, location: usize, value: u8) {
-        // TODO: Result
-        self.contents[location] = value;
+    fn set(&mut self, location: usize, value: u8) -> Result<(), Error> {
+        match (self.resolve(location), self.policy) {
+            (Some(location), _) => {
+                self.contents[location] = value;
+                self.instruction_cache.invalidate(location);
+                self.log_access(location, Access::Write);
+                Ok(())
+            },
+            (None, MemoryPolicy::Ignore) => Ok(()),
+            (None, _) => Err(Memory::out_of_range(location)),
+        }
     }
 
-    fn get(&self, location: usize) -> u8 {
-        // TODO: Result
-        self.contents[location]
+    fn get(&self, location: usize) -> Result<u8, Error> {
+        if let Some(resolved) = self.resolve(location) {
+            self.log_access(resolved, Access::Read);
+        }
+        self.peek(location)
     }
 
-    fn get_iter(&self, location: usize) -> Iter<u8> {
-        return self.contents.split_at(location).1.iter();
+    fn peek(&self, location: usize) -> Result<u8, Error> {
+        self.read(location).ok_or_else(|| Memory::out_of_range(location))
     }
 
     fn dump(&self) -> Vec<u8> {
@@ -55,8 +211,8 @@ impl Memory {
 
         for location in start_location..=end_location {
             result.push(PartialSnapshot {
-                location: location,
-                value: chip8_traits::Memory::get(self, location)
+                location,
+                value: self.read(location).unwrap_or(0)
             })
         }
 
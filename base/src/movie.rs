@@ -0,0 +1,288 @@
+use std::{convert::TryInto, fmt};
+
+use chip8_traits::KeyEvent;
+
+use crate::{SaveState, SaveStateError, save_state::crc32};
+
+/// Identifies a movie file
+pub const MAGIC: [u8; 4] = *b"C8MV";
+pub const VERSION: u16 = 2;
+
+/// Released key events have this bit set alongside the key index
+const RELEASED_BIT: u8 = 0x80;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum MovieError {
+    BadMagic,
+    UnsupportedVersion(u16),
+    ChecksumMismatch,
+    Truncated,
+    /// The save state the movie starts from can't be read
+    StartState(SaveStateError),
+}
+
+impl fmt::Display for MovieError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MovieError::BadMagic => write!(f, "not a movie"),
+            MovieError::UnsupportedVersion(version) => write!(f, "unsupported movie version {}", version),
+            MovieError::ChecksumMismatch => write!(f, "movie checksum does not match, the file is corrupt"),
+            MovieError::Truncated => write!(f, "movie is truncated"),
+            MovieError::StartState(error) => write!(f, "movie start: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for MovieError {}
+
+/// The keypad's input for one frame that changed something
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MovieFrame {
+    /// Frames since the movie started
+    pub frame: u32,
+    /// Presses and releases the keypad reported, oldest first
+    pub events: Vec<KeyEvent>,
+    /// Keys held for the frame, one bit per key
+    pub keys: u16,
+}
+
+/// A recorded session: the state it started from and every change to the keypad since
+///
+/// Replaying it is exact as long as the random generator's state can be restored, the start state carries it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Movie {
+    /// Save state taken when recording started
+    pub start_state: Vec<u8>,
+    pub instructions_per_frame: u32,
+    pub frames: Vec<MovieFrame>,
+}
+
+impl Movie {
+    /// The random generator's state at the start, empty when the generator can't be restored
+    pub fn seed(&self) -> Vec<u8> {
+        SaveState::from_bytes(&self.start_state).map(|state| state.random).unwrap_or_default()
+    }
+
+    /// Serialize as the magic, version, start state, frames and a CRC-32 of everything before it
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut result: Vec<u8> = vec![];
+        result.extend_from_slice(&MAGIC);
+        result.extend_from_slice(&VERSION.to_le_bytes());
+
+        result.extend_from_slice(&(self.start_state.len() as u32).to_le_bytes());
+        result.extend_from_slice(&self.start_state);
+        result.extend_from_slice(&self.instructions_per_frame.to_le_bytes());
+
+        result.extend_from_slice(&(self.frames.len() as u32).to_le_bytes());
+        for frame in self.frames.iter() {
+            result.extend_from_slice(&frame.frame.to_le_bytes());
+            result.extend_from_slice(&frame.keys.to_le_bytes());
+            result.extend_from_slice(&(frame.events.len() as u32).to_le_bytes());
+            for event in frame.events.iter() {
+                result.push(match event {
+                    KeyEvent::Pressed(index) => *index as u8,
+                    KeyEvent::Released(index) => *index as u8 | RELEASED_BIT,
+                });
+            }
+        }
+
+        let checksum = crc32(&result);
+        result.extend_from_slice(&checksum.to_le_bytes());
+
+        result
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Movie, MovieError> {
+        if bytes.len() < MAGIC.len() || bytes[0..MAGIC.len()] != MAGIC {
+            return Err(MovieError::BadMagic);
+        }
+        if bytes.len() < MAGIC.len() + 2 + 4 {
+            return Err(MovieError::Truncated);
+        }
+
+        let (contents, checksum) = bytes.split_at(bytes.len() - 4);
+        if crc32(contents) != u32::from_le_bytes(checksum.try_into().unwrap()) {
+            return Err(MovieError::ChecksumMismatch);
+        }
+
+        let version = u16::from_le_bytes(contents[4..6].try_into().unwrap());
+        if version != VERSION {
+            return Err(MovieError::UnsupportedVersion(version));
+        }
+
+        let mut reader = Reader { bytes: &contents[6..] };
+        let start_state_length = reader.u32()? as usize;
+        let start_state = reader.take(start_state_length)?.to_vec();
+        SaveState::from_bytes(&start_state).map_err(MovieError::StartState)?;
+        let instructions_per_frame = reader.u32()?;
+
+        let frame_count = reader.u32()?;
+        let mut frames = vec![];
+        for _ in 0..frame_count {
+            let frame = reader.u32()?;
+            let keys = u16::from_le_bytes(reader.take(2)?.try_into().unwrap());
+            let event_count = reader.u32()? as usize;
+            let events = reader.take(event_count)?.iter().map(|value| {
+                let index = (value & 0x0f) as usize;
+                if value & RELEASED_BIT != 0 { KeyEvent::Released(index) } else { KeyEvent::Pressed(index) }
+            }).collect();
+
+            frames.push(MovieFrame { frame, events, keys });
+        }
+
+        Ok(Movie {
+            start_state,
+            instructions_per_frame,
+            frames,
+        })
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8], MovieError> {
+        if self.bytes.len() < length {
+            return Err(MovieError::Truncated);
+        }
+        let (taken, rest) = self.bytes.split_at(length);
+        self.bytes = rest;
+
+        Ok(taken)
+    }
+
+    fn u32(&mut self) -> Result<u32, MovieError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+}
+
+fn keys_to_bits(keys: [bool; 16]) -> u16 {
+    keys.iter().enumerate().fold(0, |bits, (index, held)| bits | ((*held as u16) << index))
+}
+
+fn keys_from_bits(bits: u16) -> [bool; 16] {
+    let mut keys = [false; 16];
+    for (index, held) in keys.iter_mut().enumerate() {
+        *held = bits & (1 << index) != 0;
+    }
+    keys
+}
+
+/// Records a keypad's input a frame at a time
+///
+/// The keys held only change when the interpreter takes a frame's events, so instructions see the same keys on replay
+pub struct MovieRecorder<Keypad: chip8_traits::Keypad> {
+    keypad: Keypad,
+    /// Frames since recording started
+    frame: u32,
+    keys: [bool; 16],
+    frames: Vec<MovieFrame>,
+}
+
+impl<Keypad: chip8_traits::Keypad> MovieRecorder<Keypad> {
+    pub fn new(keypad: Keypad) -> MovieRecorder<Keypad> {
+        MovieRecorder {
+            keypad,
+            frame: 0,
+            keys: [false; 16],
+            frames: vec![],
+        }
+    }
+
+    pub fn keypad(&self) -> &Keypad {
+        &self.keypad
+    }
+
+    pub fn keypad_mut(&mut self) -> &mut Keypad {
+        &mut self.keypad
+    }
+
+    /// Drop what has been recorded and count frames from here
+    pub fn restart(&mut self) {
+        self.frame = 0;
+        self.frames.clear();
+    }
+
+    /// The recording so far, starting from `start_state` which should be saved when recording started
+    pub fn movie(&self, start_state: Vec<u8>, instructions_per_frame: usize) -> Movie {
+        Movie {
+            start_state,
+            instructions_per_frame: instructions_per_frame as u32,
+            frames: self.frames.clone(),
+        }
+    }
+}
+
+impl<Keypad: chip8_traits::Keypad> chip8_traits::Keypad for MovieRecorder<Keypad> {
+    fn state(&self) -> [bool; 16] {
+        self.keys
+    }
+
+    fn key_state(&self, key_index: usize) -> bool {
+        self.keys.get(key_index).copied().unwrap_or(false)
+    }
+
+    fn take_events(&mut self) -> Vec<KeyEvent> {
+        let events = self.keypad.take_events();
+        let keys = self.keypad.state();
+        if !events.is_empty() || keys != self.keys {
+            self.frames.push(MovieFrame { frame: self.frame, events: events.clone(), keys: keys_to_bits(keys) });
+        }
+
+        self.keys = keys;
+        self.frame += 1;
+
+        events
+    }
+}
+
+/// Plays a movie's input back, holding the last keys once it runs out
+pub struct MoviePlayer {
+    frames: Vec<MovieFrame>,
+    /// Next entry in `frames`
+    position: usize,
+    frame: u32,
+    keys: [bool; 16],
+}
+
+impl MoviePlayer {
+    pub fn new(movie: &Movie) -> MoviePlayer {
+        let keys = SaveState::from_bytes(&movie.start_state).map_or(0, |state| state.keys_held);
+
+        MoviePlayer {
+            frames: movie.frames.clone(),
+            position: 0,
+            frame: 0,
+            keys: keys_from_bits(keys),
+        }
+    }
+
+    /// Every recorded frame has been played
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.frames.len()
+    }
+}
+
+impl chip8_traits::Keypad for MoviePlayer {
+    fn state(&self) -> [bool; 16] {
+        self.keys
+    }
+
+    fn key_state(&self, key_index: usize) -> bool {
+        self.keys.get(key_index).copied().unwrap_or(false)
+    }
+
+    fn take_events(&mut self) -> Vec<KeyEvent> {
+        let mut events = vec![];
+        if let Some(frame) = self.frames.get(self.position).filter(|frame| frame.frame == self.frame) {
+            events = frame.events.clone();
+            self.keys = keys_from_bits(frame.keys);
+            self.position += 1;
+        }
+        self.frame += 1;
+
+        events
+    }
+}
@@ -0,0 +1,182 @@
+/// An instruction decoded from its opcode, with its operands split out
+///
+/// `x` and `y` are register indexes, `n`, `nn` and `nnn` the 4, 8 and 12-bit immediates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Opcode {
+    /// 00CN
+    ScrollDown { n: u8 },
+    /// 00DN
+    ScrollUp { n: u8 },
+    /// 00E0
+    Clear,
+    /// 00EE
+    Return,
+    /// 00FB
+    ScrollRight,
+    /// 00FC
+    ScrollLeft,
+    /// 00FD
+    Exit,
+    /// 00FE
+    LowResolution,
+    /// 00FF
+    HighResolution,
+    /// 1NNN
+    Jump { nnn: u16 },
+    /// 2NNN
+    Call { nnn: u16 },
+    /// 3XNN
+    SkipIfEqualImm { x: u8, nn: u8 },
+    /// 4XNN
+    SkipIfNotEqualImm { x: u8, nn: u8 },
+    /// 5XY0
+    SkipIfEqual { x: u8, y: u8 },
+    /// 5XY2
+    SaveRange { x: u8, y: u8 },
+    /// 5XY3
+    LoadRange { x: u8, y: u8 },
+    /// 6XNN
+    SetImm { x: u8, nn: u8 },
+    /// 7XNN
+    AddImm { x: u8, nn: u8 },
+    /// 8XY0
+    Set { x: u8, y: u8 },
+    /// 8XY1
+    Or { x: u8, y: u8 },
+    /// 8XY2
+    And { x: u8, y: u8 },
+    /// 8XY3
+    Xor { x: u8, y: u8 },
+    /// 8XY4
+    Add { x: u8, y: u8 },
+    /// 8XY5
+    Sub { x: u8, y: u8 },
+    /// 8XY6
+    ShiftRight { x: u8, y: u8 },
+    /// 8XY7
+    SubReversed { x: u8, y: u8 },
+    /// 8XYE
+    ShiftLeft { x: u8, y: u8 },
+    /// 9XY0
+    SkipIfNotEqual { x: u8, y: u8 },
+    /// ANNN
+    SetIndex { nnn: u16 },
+    /// BNNN, X only matters with the jump quirk
+    JumpOffset { x: u8, nnn: u16 },
+    /// CXNN
+    Random { x: u8, nn: u8 },
+    /// DXYN
+    Draw { x: u8, y: u8, n: u8 },
+    /// EX9E
+    SkipIfKey { x: u8 },
+    /// EXA1
+    SkipIfNotKey { x: u8 },
+    /// F000 NNNN, the address being the word after the opcode
+    SetIndexLong,
+    /// FN01, N being a mask of the planes
+    SelectPlanes { n: u8 },
+    /// F002
+    LoadAudio,
+    /// FX07
+    GetDelay { x: u8 },
+    /// FX0A
+    WaitKey { x: u8 },
+    /// FX15
+    SetDelay { x: u8 },
+    /// FX18
+    SetSound { x: u8 },
+    /// FX1E
+    AddIndex { x: u8 },
+    /// FX29
+    Font { x: u8 },
+    /// FX30
+    LargeFont { x: u8 },
+    /// FX33
+    Bcd { x: u8 },
+    /// FX3A
+    SetPitch { x: u8 },
+    /// FX55
+    Save { x: u8 },
+    /// FX65
+    Load { x: u8 },
+    /// FX75
+    SaveFlags { x: u8 },
+    /// FX85
+    LoadFlags { x: u8 },
+}
+
+impl Opcode {
+    /// Decode an opcode, None when the interpreter doesn't support it
+    pub fn decode(word: u16) -> Option<Opcode> {
+        let x = ((word & 0x0f00) >> 8) as u8;
+        let y = ((word & 0x00f0) >> 4) as u8;
+        let n = (word & 0x000f) as u8;
+        let nn = (word & 0x00ff) as u8;
+        let nnn = word & 0x0fff;
+
+        let opcode = match (word & 0xf000, nn, n) {
+            (0x0000, _, _) if x != 0 => return None,
+            (0x0000, 0xc0..=0xcf, _) => Opcode::ScrollDown { n },
+            (0x0000, 0xd0..=0xdf, _) => Opcode::ScrollUp { n },
+            (0x0000, 0xe0, _) => Opcode::Clear,
+            (0x0000, 0xee, _) => Opcode::Return,
+            (0x0000, 0xfb, _) => Opcode::ScrollRight,
+            (0x0000, 0xfc, _) => Opcode::ScrollLeft,
+            (0x0000, 0xfd, _) => Opcode::Exit,
+            (0x0000, 0xfe, _) => Opcode::LowResolution,
+            (0x0000, 0xff, _) => Opcode::HighResolution,
+            (0x1000, _, _) => Opcode::Jump { nnn },
+            (0x2000, _, _) => Opcode::Call { nnn },
+            (0x3000, _, _) => Opcode::SkipIfEqualImm { x, nn },
+            (0x4000, _, _) => Opcode::SkipIfNotEqualImm { x, nn },
+            (0x5000, _, 0x0) => Opcode::SkipIfEqual { x, y },
+            (0x5000, _, 0x2) => Opcode::SaveRange { x, y },
+            (0x5000, _, 0x3) => Opcode::LoadRange { x, y },
+            (0x6000, _, _) => Opcode::SetImm { x, nn },
+            (0x7000, _, _) => Opcode::AddImm { x, nn },
+            (0x8000, _, 0x0) => Opcode::Set { x, y },
+            (0x8000, _, 0x1) => Opcode::Or { x, y },
+            (0x8000, _, 0x2) => Opcode::And { x, y },
+            (0x8000, _, 0x3) => Opcode::Xor { x, y },
+            (0x8000, _, 0x4) => Opcode::Add { x, y },
+            (0x8000, _, 0x5) => Opcode::Sub { x, y },
+            (0x8000, _, 0x6) => Opcode::ShiftRight { x, y },
+            (0x8000, _, 0x7) => Opcode::SubReversed { x, y },
+            (0x8000, _, 0xe) => Opcode::ShiftLeft { x, y },
+            (0x9000, _, 0x0) => Opcode::SkipIfNotEqual { x, y },
+            (0xa000, _, _) => Opcode::SetIndex { nnn },
+            (0xb000, _, _) => Opcode::JumpOffset { x, nnn },
+            (0xc000, _, _) => Opcode::Random { x, nn },
+            (0xd000, _, _) => Opcode::Draw { x, y, n },
+            (0xe000, 0x9e, _) => Opcode::SkipIfKey { x },
+            (0xe000, 0xa1, _) => Opcode::SkipIfNotKey { x },
+            (0xf000, 0x00, _) if x == 0 => Opcode::SetIndexLong,
+            (0xf000, 0x01, _) => Opcode::SelectPlanes { n: x },
+            (0xf000, 0x02, _) if x == 0 => Opcode::LoadAudio,
+            (0xf000, 0x07, _) => Opcode::GetDelay { x },
+            (0xf000, 0x0a, _) => Opcode::WaitKey { x },
+            (0xf000, 0x15, _) => Opcode::SetDelay { x },
+            (0xf000, 0x18, _) => Opcode::SetSound { x },
+            (0xf000, 0x1e, _) => Opcode::AddIndex { x },
+            (0xf000, 0x29, _) => Opcode::Font { x },
+            (0xf000, 0x30, _) => Opcode::LargeFont { x },
+            (0xf000, 0x33, _) => Opcode::Bcd { x },
+            (0xf000, 0x3a, _) => Opcode::SetPitch { x },
+            (0xf000, 0x55, _) => Opcode::Save { x },
+            (0xf000, 0x65, _) => Opcode::Load { x },
+            (0xf000, 0x75, _) => Opcode::SaveFlags { x },
+            (0xf000, 0x85, _) => Opcode::LoadFlags { x },
+            _ => return None,
+        };
+
+        Some(opcode)
+    }
+
+    /// Length of the instruction in bytes, 4 for F000 NNNN and 2 otherwise
+    pub fn width(&self) -> usize {
+        match self {
+            Opcode::SetIndexLong => 4,
+            _ => 2,
+        }
+    }
+}
@@ -2,6 +2,12 @@ pub struct ProgramCounter {
     position: usize
 }
 
+impl Default for ProgramCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ProgramCounter {
     pub fn new() -> ProgramCounter {
         ProgramCounter {
@@ -20,15 +26,21 @@ impl chip8_traits::ProgramCounter for ProgramCounter  {
     // }
     
     fn get_position(&self) -> usize {
-        return self.position;
+        self.position
     }
 
     fn set_position(&mut self, new_position: usize) {
         self.position = new_position;
     }
 
-    fn skip(&mut self) {
-        self.position += 2;
+    fn skip(&mut self, memory: &dyn chip8_traits::Memory) -> Result<(), chip8_traits::Error> {
+        if crate::Instruction::is_long(memory.peek(self.position)?, memory.peek(self.position + 1)?) {
+            self.position += 4;
+        } else {
+            self.position += 2;
+        }
+
+        Ok(())
     }
 
     fn go_back(&mut self) {
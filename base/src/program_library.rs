@@ -0,0 +1,125 @@
+use std::{fmt, fs, path::Path, slice::Iter};
+
+use serde::Deserialize;
+
+use crate::Quirks;
+
+/// File extension Octo uses for programs that need XO-CHIP
+pub const XO_CHIP_EXTENSION: &str = "xo8";
+
+pub fn is_xo_chip_file(file: &str) -> bool {
+    Path::new(file).extension().is_some_and(|extension| extension.eq_ignore_ascii_case(XO_CHIP_EXTENSION))
+}
+
+/// Quirks a program is listed as needing, anything not listed keeps the `Quirks::default()` setting
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProgramQuirks {
+    pub shift: Option<bool>,
+    pub load_store: Option<bool>,
+    pub jump: Option<bool>,
+    pub vf_reset: Option<bool>,
+    pub clipping: Option<bool>,
+    pub display_wait: Option<bool>,
+    pub add_index_overflow: Option<bool>,
+    pub collision_rows: Option<bool>,
+    pub key_release: Option<bool>,
+}
+
+/// A program described in a catalog such as programs/programs.json
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ProgramEntry {
+    pub title: String,
+    pub file: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub quirks: ProgramQuirks,
+}
+
+impl ProgramEntry {
+    /// Quirks to run this program with, the defaults overridden by whatever the entry lists
+    pub fn quirks(&self) -> Quirks {
+        let listed = &self.quirks;
+        let defaults = Quirks::default();
+
+        Quirks {
+            shift: listed.shift.unwrap_or(defaults.shift),
+            load_store: listed.load_store.unwrap_or(defaults.load_store),
+            jump: listed.jump.unwrap_or(defaults.jump),
+            vf_reset: listed.vf_reset.unwrap_or(defaults.vf_reset),
+            clipping: listed.clipping.unwrap_or(defaults.clipping),
+            display_wait: listed.display_wait.unwrap_or(defaults.display_wait),
+            add_index_overflow: listed.add_index_overflow.unwrap_or(defaults.add_index_overflow),
+            collision_rows: listed.collision_rows.unwrap_or(defaults.collision_rows),
+            key_release: listed.key_release.unwrap_or(defaults.key_release),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ProgramLibraryError {
+    Read(std::io::Error),
+    Parse(serde_json::Error),
+}
+
+impl fmt::Display for ProgramLibraryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProgramLibraryError::Read(error) => write!(f, "Unable to read program library: {}", error),
+            ProgramLibraryError::Parse(error) => write!(f, "Unable to parse program library: {}", error)
+        }
+    }
+}
+
+impl std::error::Error for ProgramLibraryError {}
+
+#[derive(Debug, Clone, Default)]
+pub struct ProgramLibrary {
+    entries: Vec<ProgramEntry>
+}
+
+impl ProgramLibrary {
+    pub fn new() -> Self {
+        ProgramLibrary {
+            entries: vec![]
+        }
+    }
+
+    pub fn parse(json: &str) -> Result<Self, ProgramLibraryError> {
+        match serde_json::from_str(json) {
+            Ok(entries) => Ok(ProgramLibrary { entries }),
+            Err(error) => Err(ProgramLibraryError::Parse(error))
+        }
+    }
+
+    pub fn load_file(file_name: &str) -> Result<Self, ProgramLibraryError> {
+        match fs::read_to_string(file_name) {
+            Ok(contents) => ProgramLibrary::parse(&contents),
+            Err(error) => Err(ProgramLibraryError::Read(error))
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_, ProgramEntry> {
+        self.entries.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Find an entry by its file, ignoring any directories leading up to the file name
+    pub fn find_by_file(&self, file: &str) -> Option<&ProgramEntry> {
+        let file_name = Path::new(file).file_name()?;
+
+        self.entries.iter().find(|entry| Path::new(&entry.file).file_name() == Some(file_name))
+    }
+
+    pub fn find_by_title(&self, title: &str) -> Option<&ProgramEntry> {
+        self.entries.iter().find(|entry| entry.title == title)
+    }
+}
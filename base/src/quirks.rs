@@ -0,0 +1,96 @@
+/// Behaviors that differ between interpreters for otherwise ambiguous instructions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quirks {
+    /// 8XY6 / 8XYE shift VX in place, ignoring VY (CHIP-48, SUPER-CHIP), instead of shifting VY into VX (COSMAC VIP)
+    pub shift: bool,
+    /// FX55 / FX65 leave I unchanged (CHIP-48, SUPER-CHIP) instead of incrementing it by X + 1 (COSMAC VIP)
+    pub load_store: bool,
+    /// BNNN behaves as BXNN, jumping to XNN + VX (CHIP-48, SUPER-CHIP), instead of NNN + V0
+    pub jump: bool,
+    /// 8XY1 / 8XY2 / 8XY3 reset VF to 0 (COSMAC VIP)
+    pub vf_reset: bool,
+    /// Sprites are clipped at the screen edges instead of wrapping around to the opposite side
+    pub clipping: bool,
+    /// DXYN waits for the next vertical blank before drawing (COSMAC VIP)
+    pub display_wait: bool,
+    /// FX1E sets VF when I moves past 0x0FFF (Amiga interpreter)
+    pub add_index_overflow: bool,
+    /// DXYN in high resolution sets VF to the number of sprite rows that collided or were clipped off the bottom
+    /// (SUPER-CHIP 1.1), instead of 1 for any collision
+    pub collision_rows: bool,
+    /// FX0A waits for a key to be pressed and released, storing it on release (COSMAC VIP, Octo), instead of taking
+    /// the first key held
+    pub key_release: bool,
+}
+
+impl Quirks {
+    /// The original COSMAC VIP interpreter
+    pub fn cosmac_vip() -> Self {
+        Quirks {
+            shift: false,
+            load_store: false,
+            jump: false,
+            vf_reset: true,
+            clipping: true,
+            display_wait: true,
+            add_index_overflow: false,
+            collision_rows: false,
+            key_release: true,
+        }
+    }
+
+    /// CHIP-48 on the HP-48 calculators, which most later interpreters followed
+    pub fn chip48() -> Self {
+        Quirks {
+            shift: true,
+            load_store: true,
+            jump: true,
+            vf_reset: false,
+            clipping: true,
+            display_wait: false,
+            add_index_overflow: false,
+            collision_rows: false,
+            key_release: false,
+        }
+    }
+
+    /// SUPER-CHIP 1.1, which kept CHIP-48's behavior apart from counting collisions in high resolution
+    pub fn super_chip() -> Self {
+        Quirks {
+            collision_rows: true,
+            ..Quirks::chip48()
+        }
+    }
+
+    /// XO-CHIP as implemented by Octo
+    pub fn xo_chip() -> Self {
+        Quirks {
+            shift: false,
+            load_store: false,
+            jump: false,
+            vf_reset: false,
+            clipping: false,
+            display_wait: false,
+            add_index_overflow: false,
+            collision_rows: false,
+            key_release: true,
+        }
+    }
+}
+
+impl Default for Quirks {
+    /// COSMAC VIP arithmetic with CHIP-48 load / store, which runs the majority of the included programs
+    fn default() -> Self {
+        Quirks {
+            shift: false,
+            load_store: true,
+            jump: false,
+            vf_reset: false,
+            clipping: true,
+            display_wait: false,
+            add_index_overflow: false,
+            collision_rows: false,
+            key_release: false,
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use std::convert::TryInto;
+
+/// A SplitMix64 generator for CXNN, the same sequence every time for a given seed
+///
+/// Its whole state goes into save states, so restoring one (or replaying a movie) reproduces every random number after
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeededRandom {
+    state: u64,
+}
+
+impl SeededRandom {
+    pub fn new(seed: u64) -> SeededRandom {
+        SeededRandom {
+            state: seed,
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut value = self.state;
+        value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        value ^ (value >> 31)
+    }
+}
+
+impl chip8_traits::Random for SeededRandom {
+    fn value(&mut self) -> u8 {
+        (self.next_u64() >> 56) as u8
+    }
+
+    fn state(&self) -> Vec<u8> {
+        self.state.to_le_bytes().to_vec()
+    }
+
+    /// Ignores states from other generators, which won't be 8 bytes
+    fn set_state(&mut self, state: &[u8]) {
+        if let Ok(bytes) = state.try_into() {
+            self.state = u64::from_le_bytes(bytes);
+        }
+    }
+}
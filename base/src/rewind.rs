@@ -0,0 +1,158 @@
+use std::collections::VecDeque;
+
+/// Store a full state this often, frames in between only keep the bytes that changed
+pub const KEYFRAME_INTERVAL: usize = 60;
+
+enum Frame {
+    Keyframe(Vec<u8>),
+    /// Runs of changed bytes relative to the previous entry, as offset and replacement
+    Delta(Vec<(usize, Vec<u8>)>),
+}
+
+struct Entry {
+    /// Instructions executed before this state was recorded
+    instruction_count: u64,
+    frame: Frame,
+}
+
+/// Ring buffer of save states taken at the start of each frame
+pub struct RewindBuffer {
+    entries: VecDeque<Entry>,
+    capacity: usize,
+    /// The full state of the newest entry, to diff the next one against
+    last_state: Vec<u8>,
+    frames_since_keyframe: usize,
+}
+
+impl RewindBuffer {
+    /// Keep up to `capacity` frames
+    pub fn new(capacity: usize) -> RewindBuffer {
+        RewindBuffer {
+            entries: VecDeque::new(),
+            capacity,
+            last_state: vec![],
+            frames_since_keyframe: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.last_state.clear();
+        self.frames_since_keyframe = 0;
+    }
+
+    pub fn push(&mut self, instruction_count: u64, state: Vec<u8>) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let frame = {
+            if self.entries.is_empty() || self.frames_since_keyframe >= KEYFRAME_INTERVAL || state.len() != self.last_state.len() {
+                self.frames_since_keyframe = 0;
+                Frame::Keyframe(state.clone())
+            } else {
+                self.frames_since_keyframe += 1;
+                Frame::Delta(diff(&self.last_state, &state))
+            }
+        };
+        self.entries.push_back(Entry {
+            instruction_count,
+            frame,
+        });
+        self.last_state = state;
+
+        while self.entries.len() > self.capacity {
+            self.pop_front();
+        }
+    }
+
+    /// Drop the oldest entry, turning the next one into a keyframe if it depended on it
+    fn pop_front(&mut self) {
+        let Some(front) = self.entries.pop_front() else {
+            return;
+        };
+        let Frame::Keyframe(mut state) = front.frame else {
+            return;
+        };
+
+        if let Some(next) = self.entries.front_mut() {
+            if let Frame::Delta(changes) = &next.frame {
+                apply(&mut state, changes);
+                next.frame = Frame::Keyframe(state);
+            }
+        }
+    }
+
+    /// The instruction count and full state of the entry at `index`, 0 being the oldest
+    pub fn get(&self, index: usize) -> Option<(u64, Vec<u8>)> {
+        if index >= self.entries.len() {
+            return None;
+        }
+
+        let keyframe_index = (0..=index).rev().find(|candidate| matches!(self.entries[*candidate].frame, Frame::Keyframe(_)))?;
+        let mut state = match &self.entries[keyframe_index].frame {
+            Frame::Keyframe(state) => state.clone(),
+            Frame::Delta(_) => return None,
+        };
+        for entry in self.entries.range(keyframe_index + 1..=index) {
+            if let Frame::Delta(changes) = &entry.frame {
+                apply(&mut state, changes);
+            }
+        }
+
+        Some((self.entries[index].instruction_count, state))
+    }
+
+    /// Index of the newest entry recorded at or before `instruction_count`
+    pub fn find(&self, instruction_count: u64) -> Option<usize> {
+        self.entries.iter().rposition(|entry| entry.instruction_count <= instruction_count)
+    }
+
+    /// Drop every entry from `index` on, so recording continues from there
+    pub fn truncate(&mut self, index: usize) {
+        self.entries.truncate(index);
+        self.frames_since_keyframe = self.entries.iter().rev().take_while(|entry| matches!(entry.frame, Frame::Delta(_))).count();
+        self.last_state = match index.checked_sub(1).and_then(|last| self.get(last)) {
+            Some((_, state)) => state,
+            None => vec![],
+        };
+    }
+}
+
+fn diff(previous: &[u8], next: &[u8]) -> Vec<(usize, Vec<u8>)> {
+    let mut result: Vec<(usize, Vec<u8>)> = vec![];
+
+    let mut index = 0;
+    while index < next.len() {
+        if previous[index] == next[index] {
+            index += 1;
+            continue;
+        }
+
+        let start = index;
+        while index < next.len() && previous[index] != next[index] {
+            index += 1;
+        }
+        result.push((start, next[start..index].to_vec()));
+    }
+
+    result
+}
+
+fn apply(state: &mut [u8], changes: &[(usize, Vec<u8>)]) {
+    for (offset, values) in changes {
+        state[*offset..offset + values.len()].copy_from_slice(values);
+    }
+}
@@ -0,0 +1,305 @@
+use std::{convert::TryInto, fmt};
+
+use crate::{KeyLatch, Quirks};
+
+/// Identifies a save state file
+pub const MAGIC: [u8; 4] = *b"C8ST";
+pub const VERSION: u16 = 2;
+/// Oldest version that still loads, version 1 states have no key latch section
+pub const OLDEST_VERSION: u16 = 1;
+
+const CPU_SECTION: [u8; 4] = *b"CPU ";
+const MEMORY_SECTION: [u8; 4] = *b"MEM ";
+const SCREEN_SECTION: [u8; 4] = *b"SCRN";
+const STACK_SECTION: [u8; 4] = *b"STAK";
+const SOUND_SECTION: [u8; 4] = *b"SND ";
+const QUIRKS_SECTION: [u8; 4] = *b"QRKS";
+const RANDOM_SECTION: [u8; 4] = *b"RNG ";
+const KEYPAD_SECTION: [u8; 4] = *b"KEYS";
+const KEY_LATCH_SECTION: [u8; 4] = *b"KLAT";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SaveStateError {
+    BadMagic,
+    UnsupportedVersion(u16),
+    ChecksumMismatch,
+    Truncated,
+    MissingSection([u8; 4]),
+    /// A section was present but its contents don't make sense
+    InvalidSection([u8; 4]),
+}
+
+impl fmt::Display for SaveStateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveStateError::BadMagic => write!(f, "not a save state"),
+            SaveStateError::UnsupportedVersion(version) => write!(f, "unsupported save state version {}", version),
+            SaveStateError::ChecksumMismatch => write!(f, "save state checksum does not match, the file is corrupt"),
+            SaveStateError::Truncated => write!(f, "save state is truncated"),
+            SaveStateError::MissingSection(tag) => write!(f, "save state is missing the {} section", String::from_utf8_lossy(tag)),
+            SaveStateError::InvalidSection(tag) => write!(f, "save state has an invalid {} section", String::from_utf8_lossy(tag)),
+        }
+    }
+}
+
+impl std::error::Error for SaveStateError {}
+
+/// Everything needed to resume a program exactly where it was
+#[derive(Debug, Clone, PartialEq)]
+pub struct SaveState {
+    pub program_counter: usize,
+    pub index_register: usize,
+    pub variable_registers: [u8; 16],
+    pub delay_timer: u8,
+    pub sound_timer: u8,
+    pub rpl_flags: [u8; 16],
+    pub exited: bool,
+
+    pub memory: Vec<u8>,
+
+    pub screen_width: usize,
+    pub screen_height: usize,
+    pub high_resolution: bool,
+    pub selected_planes: u8,
+    /// Row major, one plane mask per pixel
+    pub screen: Vec<u8>,
+
+    pub stack: Vec<usize>,
+
+    pub audio_pattern: [u8; 16],
+    pub pitch: u8,
+
+    pub quirks: Quirks,
+
+    /// Generator specific, empty when the generator can't be restored
+    pub random: Vec<u8>,
+
+    /// Keys held when the state was saved, for reference as the keypad itself isn't restored
+    pub keys_held: u16,
+    /// Key edges as the interpreter last saw them and FX0A's progress, added in version 2, nothing is latched in version 1 states
+    pub key_latch: KeyLatch,
+}
+
+impl SaveState {
+    /// Serialize as the magic, version, tagged sections and a CRC-32 of everything before it
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut result: Vec<u8> = vec![];
+        result.extend_from_slice(&MAGIC);
+        result.extend_from_slice(&VERSION.to_le_bytes());
+
+        let mut cpu: Vec<u8> = vec![];
+        cpu.extend_from_slice(&(self.program_counter as u32).to_le_bytes());
+        cpu.extend_from_slice(&(self.index_register as u32).to_le_bytes());
+        cpu.extend_from_slice(&self.variable_registers);
+        cpu.push(self.delay_timer);
+        cpu.push(self.sound_timer);
+        cpu.extend_from_slice(&self.rpl_flags);
+        cpu.push(self.exited as u8);
+        write_section(&mut result, CPU_SECTION, &cpu);
+
+        write_section(&mut result, MEMORY_SECTION, &self.memory);
+
+        let mut screen: Vec<u8> = vec![];
+        screen.extend_from_slice(&(self.screen_width as u16).to_le_bytes());
+        screen.extend_from_slice(&(self.screen_height as u16).to_le_bytes());
+        screen.push(self.high_resolution as u8);
+        screen.push(self.selected_planes);
+        screen.extend_from_slice(&self.screen);
+        write_section(&mut result, SCREEN_SECTION, &screen);
+
+        let mut stack: Vec<u8> = vec![];
+        for value in self.stack.iter() {
+            stack.extend_from_slice(&(*value as u32).to_le_bytes());
+        }
+        write_section(&mut result, STACK_SECTION, &stack);
+
+        let mut sound: Vec<u8> = vec![];
+        sound.extend_from_slice(&self.audio_pattern);
+        sound.push(self.pitch);
+        write_section(&mut result, SOUND_SECTION, &sound);
+
+        write_section(&mut result, QUIRKS_SECTION, &quirks_to_bits(&self.quirks).to_le_bytes());
+        write_section(&mut result, RANDOM_SECTION, &self.random);
+        write_section(&mut result, KEYPAD_SECTION, &self.keys_held.to_le_bytes());
+        write_section(&mut result, KEY_LATCH_SECTION, &self.key_latch.to_bytes());
+
+        let checksum = crc32(&result);
+        result.extend_from_slice(&checksum.to_le_bytes());
+
+        result
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<SaveState, SaveStateError> {
+        if bytes.len() < MAGIC.len() || bytes[0..MAGIC.len()] != MAGIC {
+            return Err(SaveStateError::BadMagic);
+        }
+        if bytes.len() < MAGIC.len() + 2 + 4 {
+            return Err(SaveStateError::Truncated);
+        }
+
+        let (contents, checksum) = bytes.split_at(bytes.len() - 4);
+        if crc32(contents) != u32::from_le_bytes(checksum.try_into().unwrap()) {
+            return Err(SaveStateError::ChecksumMismatch);
+        }
+
+        let version = u16::from_le_bytes(contents[4..6].try_into().unwrap());
+        if !(OLDEST_VERSION..=VERSION).contains(&version) {
+            return Err(SaveStateError::UnsupportedVersion(version));
+        }
+
+        let sections = read_sections(&contents[6..])?;
+        let optional_section = |tag: [u8; 4]| -> Option<&[u8]> {
+            sections.iter()
+                .find(|(section_tag, _)| *section_tag == tag)
+                .map(|(_, data)| *data)
+        };
+        let section = |tag: [u8; 4]| -> Result<&[u8], SaveStateError> {
+            optional_section(tag).ok_or(SaveStateError::MissingSection(tag))
+        };
+
+        let cpu = section(CPU_SECTION)?;
+        if cpu.len() != 4 + 4 + 16 + 1 + 1 + 16 + 1 {
+            return Err(SaveStateError::InvalidSection(CPU_SECTION));
+        }
+
+        let screen = section(SCREEN_SECTION)?;
+        if screen.len() < 6 {
+            return Err(SaveStateError::InvalidSection(SCREEN_SECTION));
+        }
+        let screen_width = u16::from_le_bytes(screen[0..2].try_into().unwrap()) as usize;
+        let screen_height = u16::from_le_bytes(screen[2..4].try_into().unwrap()) as usize;
+        if screen.len() != 6 + screen_width * screen_height {
+            return Err(SaveStateError::InvalidSection(SCREEN_SECTION));
+        }
+
+        let stack = section(STACK_SECTION)?;
+        if stack.len() % 4 != 0 {
+            return Err(SaveStateError::InvalidSection(STACK_SECTION));
+        }
+
+        let sound = section(SOUND_SECTION)?;
+        if sound.len() != 17 {
+            return Err(SaveStateError::InvalidSection(SOUND_SECTION));
+        }
+
+        let quirks = section(QUIRKS_SECTION)?;
+        if quirks.len() != 2 {
+            return Err(SaveStateError::InvalidSection(QUIRKS_SECTION));
+        }
+
+        let keys = section(KEYPAD_SECTION)?;
+        if keys.len() != 2 {
+            return Err(SaveStateError::InvalidSection(KEYPAD_SECTION));
+        }
+
+        let key_latch = match optional_section(KEY_LATCH_SECTION) {
+            None => KeyLatch::new(),
+            Some(key_latch) => KeyLatch::from_bytes(key_latch.try_into().map_err(|_| SaveStateError::InvalidSection(KEY_LATCH_SECTION))?),
+        };
+
+        Ok(SaveState {
+            program_counter: u32::from_le_bytes(cpu[0..4].try_into().unwrap()) as usize,
+            index_register: u32::from_le_bytes(cpu[4..8].try_into().unwrap()) as usize,
+            variable_registers: cpu[8..24].try_into().unwrap(),
+            delay_timer: cpu[24],
+            sound_timer: cpu[25],
+            rpl_flags: cpu[26..42].try_into().unwrap(),
+            exited: cpu[42] != 0,
+
+            memory: section(MEMORY_SECTION)?.to_vec(),
+
+            screen_width,
+            screen_height,
+            high_resolution: screen[4] != 0,
+            selected_planes: screen[5],
+            screen: screen[6..].to_vec(),
+
+            stack: stack.chunks(4).map(|value| u32::from_le_bytes(value.try_into().unwrap()) as usize).collect(),
+
+            audio_pattern: sound[0..16].try_into().unwrap(),
+            pitch: sound[16],
+
+            quirks: quirks_from_bits(u16::from_le_bytes(quirks.try_into().unwrap())),
+
+            random: section(RANDOM_SECTION)?.to_vec(),
+
+            keys_held: u16::from_le_bytes(keys.try_into().unwrap()),
+            key_latch,
+        })
+    }
+}
+
+/// Tag and contents
+type Section<'a> = ([u8; 4], &'a [u8]);
+
+fn write_section(result: &mut Vec<u8>, tag: [u8; 4], data: &[u8]) {
+    result.extend_from_slice(&tag);
+    result.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    result.extend_from_slice(data);
+}
+
+/// Split into tagged sections, later versions may add sections that older readers skip
+fn read_sections(mut bytes: &[u8]) -> Result<Vec<Section<'_>>, SaveStateError> {
+    let mut result = vec![];
+
+    while !bytes.is_empty() {
+        if bytes.len() < 8 {
+            return Err(SaveStateError::Truncated);
+        }
+        let tag: [u8; 4] = bytes[0..4].try_into().unwrap();
+        let length = u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize;
+        if bytes.len() < 8 + length {
+            return Err(SaveStateError::Truncated);
+        }
+
+        result.push((tag, &bytes[8..8 + length]));
+        bytes = &bytes[8 + length..];
+    }
+
+    Ok(result)
+}
+
+fn quirks_to_bits(quirks: &Quirks) -> u16 {
+    [
+        quirks.shift,
+        quirks.load_store,
+        quirks.jump,
+        quirks.vf_reset,
+        quirks.clipping,
+        quirks.display_wait,
+        quirks.add_index_overflow,
+        quirks.collision_rows,
+        quirks.key_release,
+    ].iter().enumerate().fold(0, |bits, (index, value)| bits | ((*value as u16) << index))
+}
+
+fn quirks_from_bits(bits: u16) -> Quirks {
+    let bit = |index: u16| bits & (1 << index) != 0;
+
+    Quirks {
+        shift: bit(0),
+        load_store: bit(1),
+        jump: bit(2),
+        vf_reset: bit(3),
+        clipping: bit(4),
+        display_wait: bit(5),
+        add_index_overflow: bit(6),
+        collision_rows: bit(7),
+        key_release: bit(8),
+    }
+}
+
+/// CRC-32 as used by zip and PNG
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let mask = (!(crc & 1)).wrapping_add(1);
+            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+        }
+    }
+
+    !crc
+}
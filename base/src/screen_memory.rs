@@ -1,9 +1,20 @@
 use std::slice::{self, Iter};
 
+use chip8_traits::Collision;
+
+/// Number of XO-CHIP bitplanes, each pixel holds one bit per plane
+pub const PLANE_COUNT: usize = 2;
+
 pub struct ScreenMemory {
-    contents: Vec<Vec<bool>>,
+    contents: Vec<Vec<u8>>,
     width: usize,
     height: usize,
+
+    low_resolution_width: usize,
+    low_resolution_height: usize,
+    high_resolution: bool,
+
+    selected_planes: u8,
 }
 
 impl ScreenMemory {
@@ -12,6 +23,12 @@ impl ScreenMemory {
             contents: vec![],
             width: 0,
             height: 0,
+
+            low_resolution_width: width,
+            low_resolution_height: height,
+            high_resolution: false,
+
+            selected_planes: 1,
         };
         result.set_dimensions(width, height);
 
@@ -23,56 +40,175 @@ impl ScreenMemory {
     }
 
     pub fn set_dimensions(&mut self, width: usize, height: usize) {
-        self.contents = vec![vec![false; width]; height];
+        self.contents = vec![vec![0; width]; height];
         self.width = width;
         self.height = height;
     }
 
-    pub fn iter(&self) -> slice::Iter<Vec<bool>> {
-        return self.contents.iter();
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Rows of pixels, each pixel a mask of the planes it is set in
+    pub fn iter(&self) -> slice::Iter<'_, Vec<u8>> {
+        self.contents.iter()
+    }
+
+    /// Replace the screen with `contents`, row major plane masks matching `width` and `height`
+    pub fn restore(&mut self, width: usize, height: usize, high_resolution: bool, selected_planes: u8, contents: &[u8]) {
+        self.set_dimensions(width, height);
+        self.high_resolution = high_resolution;
+        self.selected_planes = selected_planes;
+        for (row, values) in self.contents.iter_mut().zip(contents.chunks(width.max(1))) {
+            row.copy_from_slice(values);
+        }
     }
 
     pub fn is_empty(&self) -> bool {
-        for value in self.iter() {
-            if value[0] {
+        for row in self.iter() {
+            if row.iter().any(|value| *value != 0) {
                 return false;
-            } 
+            }
         }
-        return true;
+        true
     }
-}
 
-impl chip8_traits::ScreenMemory for ScreenMemory {
-    fn clear(&mut self) {
-        self.set_dimensions(self.width, self.height);
+    fn planes(&self) -> impl Iterator<Item = u8> {
+        let selected_planes = self.selected_planes;
+        (0..PLANE_COUNT).map(|plane| 1 << plane).filter(move |mask| selected_planes & mask != 0)
     }
 
-    fn display(&mut self, x: u8, y: u8, memory: Iter<u8>, count: u8) -> bool {
-        let x = (x as usize) % self.width; 
+    /// XOR `rows` rows of `bytes_per_row` wide sprite data onto the screen for each selected plane in turn
+    ///
+    /// Only the starting position wraps when clipping, the rest of the sprite stops at the edges
+    fn draw(&mut self, x: u8, y: u8, memory: Iter<u8>, rows: usize, bytes_per_row: usize, clipping: bool) -> Collision {
+        let x = (x as usize) % self.width;
         let y = (y as usize) % self.height;
 
-        let mut memory = memory;
-
-        let mut cleared = false;
+        let planes: Vec<u8> = self.planes().collect();
+        let sprite: Vec<u8> = memory.take(planes.len() * rows * bytes_per_row).cloned().collect();
 
-        for index in 0..count {
-            let row: &mut Vec<bool> = &mut self.contents[y + index as usize];
+        // Rows are counted once however many planes collide in them
+        let mut collided_rows = vec![false; rows];
 
-            let memory_value = memory.as_ref()[0];
-
-            for bit in 0..=7 {
-                if memory_value & (1 << bit) != 0 {
-                    let bit_offset = 7 - bit;
-                    row[x + bit_offset] = !row[x + bit_offset];
-                    if row[x + bit_offset] == false {
-                        cleared = true;
+        for (plane, plane_sprite) in planes.iter().zip(sprite.chunks(rows * bytes_per_row)) {
+            for (index, row_values) in plane_sprite.chunks(bytes_per_row).enumerate() {
+                let mut row_index = y + index;
+                if row_index >= self.height {
+                    if clipping {
+                        break;
+                    }
+                    row_index %= self.height;
+                }
+                let row: &mut Vec<u8> = &mut self.contents[row_index];
+
+                for (byte_index, memory_value) in row_values.iter().enumerate() {
+                    for bit in 0..=7 {
+                        if memory_value & (0x80 >> bit) != 0 {
+                            let mut column = x + byte_index * 8 + bit;
+                            if column >= self.width {
+                                if clipping {
+                                    continue;
+                                }
+                                column %= self.width;
+                            }
+                            row[column] ^= plane;
+                            if row[column] & plane == 0 {
+                                collided_rows[index] = true;
+                            }
+                        }
                     }
                 }
             }
+        }
+
+        Collision {
+            rows: collided_rows.iter().filter(|collided| **collided).count(),
+            clipped_rows: if clipping && !planes.is_empty() { (y + rows).saturating_sub(self.height) } else { 0 },
+        }
+    }
+
+    /// Move the selected planes of every pixel by the given offset, filling the vacated pixels with 0
+    fn shift(&mut self, rows: isize, columns: isize) {
+        let mask = self.selected_planes;
+        let previous = self.contents.clone();
+
+        for (row_index, row) in self.contents.iter_mut().enumerate() {
+            for (column_index, value) in row.iter_mut().enumerate() {
+                let source_row = row_index as isize - rows;
+                let source_column = column_index as isize - columns;
+
+                let source = if source_row < 0 || source_column < 0 {
+                    0
+                } else {
+                    previous.get(source_row as usize)
+                        .and_then(|previous_row| previous_row.get(source_column as usize))
+                        .copied()
+                        .unwrap_or(0)
+                };
+
+                *value = (*value & !mask) | (source & mask);
+            }
+        }
+    }
+}
 
-            memory.next();
+impl chip8_traits::ScreenMemory for ScreenMemory {
+    fn clear(&mut self) {
+        let mask = self.selected_planes;
+        for row in self.contents.iter_mut() {
+            for value in row.iter_mut() {
+                *value &= !mask;
+            }
         }
+    }
 
-        cleared
+    fn display(&mut self, x: u8, y: u8, memory: Iter<u8>, count: u8, clipping: bool) -> Collision {
+        self.draw(x, y, memory, count as usize, 1, clipping)
+    }
+
+    fn display_large(&mut self, x: u8, y: u8, memory: Iter<u8>, clipping: bool) -> Collision {
+        self.draw(x, y, memory, 16, 2, clipping)
+    }
+
+    fn is_high_resolution(&self) -> bool {
+        self.high_resolution
+    }
+
+    fn set_high_resolution(&mut self, high_resolution: bool) {
+        self.high_resolution = high_resolution;
+        if high_resolution {
+            self.set_dimensions(self.low_resolution_width * 2, self.low_resolution_height * 2);
+        } else {
+            self.set_dimensions(self.low_resolution_width, self.low_resolution_height);
+        }
     }
-}
\ No newline at end of file
+
+    fn selected_planes(&self) -> u8 {
+        self.selected_planes
+    }
+
+    fn select_planes(&mut self, planes: u8) {
+        self.selected_planes = planes & ((1 << PLANE_COUNT) - 1);
+    }
+
+    fn scroll_down(&mut self, rows: usize) {
+        self.shift(rows.min(self.height) as isize, 0);
+    }
+
+    fn scroll_up(&mut self, rows: usize) {
+        self.shift(-(rows.min(self.height) as isize), 0);
+    }
+
+    fn scroll_left(&mut self, columns: usize) {
+        self.shift(0, -(columns.min(self.width) as isize));
+    }
+
+    fn scroll_right(&mut self, columns: usize) {
+        self.shift(0, columns.min(self.width) as isize);
+    }
+}
@@ -1,16 +1,63 @@
+/// XO-CHIP pitch that plays the audio pattern at 4000 bits per second
+pub const DEFAULT_PITCH: u8 = 64;
+pub const DEFAULT_SAMPLE_RATE: f64 = 4000.0;
+/// Square wave played until a program loads its own pattern, 250 Hz at the default pitch
+pub const DEFAULT_PATTERN: [u8; 16] = [0xff, 0x00, 0xff, 0x00, 0xff, 0x00, 0xff, 0x00, 0xff, 0x00, 0xff, 0x00, 0xff, 0x00, 0xff, 0x00];
+
 pub struct SoundTimer {
-    value: u8
+    value: u8,
+
+    /// XO-CHIP 1-bit audio pattern, 128 samples played in a loop while the timer is active
+    pattern: [u8; 16],
+    pitch: u8,
+}
+
+impl Default for SoundTimer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl SoundTimer {
     pub fn new() -> SoundTimer {
         SoundTimer {
-            value: 0
+            value: 0,
+
+            pattern: DEFAULT_PATTERN,
+            pitch: DEFAULT_PITCH,
         }
     }
 
     pub fn reset(&mut self) {
         self.value = 0;
+        self.pattern = DEFAULT_PATTERN;
+        self.pitch = DEFAULT_PITCH;
+    }
+
+    /// The buzzer sounds while the timer is above 0
+    pub fn is_playing(&self) -> bool {
+        self.value > 0
+    }
+
+    pub fn pattern(&self) -> [u8; 16] {
+        self.pattern
+    }
+
+    pub fn set_pattern(&mut self, pattern: [u8; 16]) {
+        self.pattern = pattern;
+    }
+
+    pub fn pitch(&self) -> u8 {
+        self.pitch
+    }
+
+    pub fn set_pitch(&mut self, pitch: u8) {
+        self.pitch = pitch;
+    }
+
+    /// Playback rate of the audio pattern in bits per second
+    pub fn sample_rate(&self) -> f64 {
+        DEFAULT_SAMPLE_RATE * 2f64.powf((self.pitch as f64 - DEFAULT_PITCH as f64) / 48.0)
     }
 }
 
@@ -23,20 +70,9 @@ impl chip8_traits::Timer for SoundTimer {
         self.value = value;
     }
 
-    fn update(&mut self) -> Result<(), String> {
-        // TODO: only call beep if changed
+    fn update(&mut self) -> Result<(), chip8_traits::Error> {
         if self.value > 0 {
             self.value -= 1;
-            print!("\x07\r");
-            // let result = beep(440);
-            // if let Err(error) = result {
-            //     return Err(format!("{}", error));
-            // }
-        // } else {
-        //     let result = beep(0);
-        //     if let Err(error) = result {
-        //         return Err(format!("{}", error));
-        //     }
         }
         Ok(())
     }
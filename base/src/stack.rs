@@ -1,34 +1,116 @@
+use chip8_traits::{Error, ErrorContext};
+
+use crate::Memory;
+
+/// Nesting the COSMAC VIP interpreter allows
+pub const VIP_DEPTH: usize = 12;
+/// Nesting SCHIP allows
+pub const SCHIP_DEPTH: usize = 16;
+/// The COSMAC VIP interpreter's stack grows down from here, two bytes per return address, high byte first
+pub const VIP_STACK_ADDRESS: usize = 0xed0;
+/// Highest return address, the end of XO-CHIP's 64 KiB and the most the two bytes in memory hold
+pub const MAX_RETURN_ADDRESS: usize = 0xffff;
+
 pub struct Stack {
-    contents: Vec<usize>
+    contents: Vec<usize>,
+    /// Deepest the stack can go, None for unbounded
+    depth: Option<usize>,
+    /// Where the return addresses are also kept in emulated memory, the top of a stack growing down
+    memory_address: Option<usize>,
+}
+
+impl Default for Stack {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Stack {
     pub fn new() -> Stack {
         Stack {
-            contents: vec![]
+            contents: vec![],
+            depth: None,
+            memory_address: None,
         } 
     }
 
     pub fn is_empty(&self) -> bool {
-        return self.contents.len() == 0;
+        self.contents.len() == 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.contents.len()
+    }
+
+    /// Return addresses, oldest first
+    pub fn values(&self) -> &[usize] {
+        &self.contents
+    }
+
+    pub fn restore(&mut self, values: Vec<usize>) {
+        self.contents = values;
+    }
+
+    pub fn depth(&self) -> Option<usize> {
+        self.depth
+    }
+
+    /// Limit the nesting, `VIP_DEPTH` or `SCHIP_DEPTH` to match those interpreters, or None for unbounded
+    pub fn set_depth(&mut self, depth: Option<usize>) {
+        self.depth = depth;
+    }
+
+    pub fn memory_address(&self) -> Option<usize> {
+        self.memory_address
+    }
+
+    /// Keep return addresses in emulated memory too, growing down from `address`, so programs that read or change
+    /// them there see what they would on the original interpreter
+    pub fn set_memory_address(&mut self, address: Option<usize>) {
+        self.memory_address = address;
+    }
+
+    /// Where the return address at `level` is kept in memory, 0 being the oldest
+    pub fn location(&self, level: usize) -> Option<usize> {
+        self.memory_address?.checked_sub((level + 1) * 2)
+    }
+
+    /// Return addresses oldest first, as returning would find them
+    pub fn return_addresses(&self, memory: &Memory) -> Vec<usize> {
+        self.contents.iter().enumerate().map(|(level, value)| {
+            self.read(level, memory).unwrap_or(*value)
+        }).collect()
+    }
+
+    /// The return address at `level` from memory, when kept there
+    fn read(&self, level: usize, memory: &Memory) -> Option<usize> {
+        let location = self.location(level)?;
+        let memory = memory as &dyn chip8_traits::Memory;
+
+        Some((memory.get(location).ok()? as usize) << 8 | memory.get(location + 1).ok()? as usize)
     }
 }
 
 impl chip8_traits::Stack for Stack {
-    // TODO: limit to 12-bit (or memory length) values
-    // TODO: optionally limit size
-    fn push(&mut self, value: usize) {
+    fn push(&mut self, value: usize) -> Result<(), Error> {
+        if value > MAX_RETURN_ADDRESS {
+            return Err(Error::ProgramCounterOutOfRange(ErrorContext::default()));
+        }
+
+        let full = self.depth.is_some_and(|depth| self.contents.len() >= depth);
+        if full || (self.memory_address.is_some() && self.location(self.contents.len()).is_none()) {
+            return Err(Error::StackOverflow(ErrorContext::default()));
+        }
+
         self.contents.push(value);
+        Ok(())
     }
 
-    // TODO: stack underflow
-    fn pop(&mut self) -> Option<usize> {
-        self.contents.pop()
+    fn pop(&mut self) -> Result<usize, Error> {
+        self.contents.pop().ok_or(Error::StackUnderflow(ErrorContext::default()))
     }
 
     fn clear(&mut self) {
-        self.push(0);
-        self.pop();
         self.contents.clear();
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,77 @@
+use std::{collections::BTreeMap, fmt};
+
+const BREAKPOINT_MARKER: &str = ":breakpoint";
+
+/// Names for addresses in a program, as written by an assembler
+///
+/// As text each line is an address and a label name, or an address, `:breakpoint` and the breakpoint's name
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SymbolMap {
+    labels: BTreeMap<String, usize>,
+    /// Places the source asked the debugger to stop at
+    breakpoints: BTreeMap<String, usize>,
+}
+
+impl SymbolMap {
+    pub fn new() -> SymbolMap {
+        SymbolMap::default()
+    }
+
+    pub fn insert_label(&mut self, name: &str, address: usize) {
+        self.labels.insert(name.to_string(), address);
+    }
+
+    pub fn insert_breakpoint(&mut self, name: &str, address: usize) {
+        self.breakpoints.insert(name.to_string(), address);
+    }
+
+    pub fn address(&self, name: &str) -> Option<usize> {
+        self.labels.get(name).copied()
+    }
+
+    /// The first label at `address` in name order, if any
+    pub fn name_at(&self, address: usize) -> Option<&str> {
+        self.labels.iter().find(|(_, label_address)| **label_address == address).map(|(name, _)| name.as_str())
+    }
+
+    pub fn labels(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.labels.iter().map(|(name, address)| (name.as_str(), *address))
+    }
+
+    pub fn breakpoints(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.breakpoints.iter().map(|(name, address)| (name.as_str(), *address))
+    }
+
+    pub fn parse(text: &str) -> Result<SymbolMap, String> {
+        let mut result = SymbolMap::new();
+
+        for (index, line) in text.lines().enumerate() {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.is_empty() {
+                continue;
+            }
+
+            let address = parts[0].strip_prefix("0x").and_then(|address| usize::from_str_radix(address, 16).ok());
+            match (address, &parts[1..]) {
+                (Some(address), [name]) => result.insert_label(name, address),
+                (Some(address), [marker, name]) if *marker == BREAKPOINT_MARKER => result.insert_breakpoint(name, address),
+                _ => return Err(format!("Invalid symbol on line {}: '{}'", index + 1, line)),
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+impl fmt::Display for SymbolMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, address) in self.labels() {
+            writeln!(f, "{:#06x} {}", address, name)?;
+        }
+        for (name, address) in self.breakpoints() {
+            writeln!(f, "{:#06x} {} {}", address, BREAKPOINT_MARKER, name)?;
+        }
+
+        Ok(())
+    }
+}
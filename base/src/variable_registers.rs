@@ -1,9 +1,15 @@
-use crate::OutOfBoundsError;
+use chip8_traits::{Error, ErrorContext};
 
 pub struct VariableRegisters {
     value: [u8; 16]
 }
 
+impl Default for VariableRegisters {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl VariableRegisters {
     pub fn new() -> VariableRegisters {
         VariableRegisters {
@@ -20,12 +26,16 @@ impl VariableRegisters {
     }
 
     pub fn get_all(&self) -> [u8; 16] {
-        self.value.clone()
+        self.value
+    }
+
+    pub fn set_all(&mut self, value: [u8; 16]) {
+        self.value = value;
     }
 
-    pub fn set(&mut self, index: u8, value: u8) -> Result<(), OutOfBoundsError> {
+    pub fn set(&mut self, index: u8, value: u8) -> Result<(), Error> {
         if index as usize >=  self.value.len() {
-            Err(OutOfBoundsError::new(index))
+            Err(Error::RegisterOutOfRange { index, context: ErrorContext::default() })
         } else {
             self.value[index as usize] = value;
             Ok(())
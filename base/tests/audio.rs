@@ -0,0 +1,78 @@
+mod common;
+
+#[cfg(test)]
+mod audio_tests {
+    use chip8_base::PcmAudio;
+    use chip8_traits::Audio;
+
+    use crate::common::new_interpreter;
+
+    use mockall::predicate::*;
+    use mockall::{mock, Sequence};
+
+    mock! {
+        Audio {}
+        impl chip8_traits::Audio for Audio {
+            fn set_playing(&mut self, playing: bool) -> Result<(), chip8_base::Error>;
+            fn set_pattern(&mut self, pattern: [u8; 16], sample_rate: f64) -> Result<(), chip8_base::Error>;
+            fn update_frame(&mut self) -> Result<(), chip8_base::Error>;
+        }
+    }
+
+    #[test]
+    fn pcm_audio_test() {
+        let mut audio = PcmAudio::new(6000);
+
+        audio.update_frame().unwrap();
+        assert_eq!(audio.samples().len(), 100);
+        assert!(audio.samples().iter().all(|sample| *sample == 0));
+
+        audio.set_pattern(chip8_base::sound_timer::DEFAULT_PATTERN, 6000.0).unwrap();
+        audio.set_playing(true).unwrap();
+        audio.update_frame().unwrap();
+
+        let tone = &audio.samples()[100..];
+        assert_eq!(tone.len(), 100);
+        assert!(tone[0..8].iter().all(|sample| *sample > 0));
+        assert!(tone[8..16].iter().all(|sample| *sample < 0));
+    }
+
+    #[test]
+    fn write_wav_test() {
+        let mut audio = PcmAudio::new(6000);
+        audio.set_playing(true).unwrap();
+        audio.update_frame().unwrap();
+
+        let mut wav: Vec<u8> = vec![];
+        audio.write_wav(&mut wav).expect("writing to memory should succeed");
+
+        assert_eq!(wav.len(), 44 + 200);
+        assert_eq!(&wav[0..4], b"RIFF");
+        assert_eq!(&wav[8..12], b"WAVE");
+        assert_eq!(&wav[24..28], &6000u32.to_le_bytes());
+        assert_eq!(&wav[40..44], &200u32.to_le_bytes());
+    }
+
+    #[test]
+    fn interpreter_audio_test() {
+        let mut sequence = Sequence::new();
+        let mut audio = MockAudio::new();
+        audio.expect_set_pattern().times(1).returning(|_, _| Ok(()));
+        audio.expect_update_frame().returning(|| Ok(()));
+        audio.expect_set_playing().with(eq(true)).times(1).in_sequence(&mut sequence).returning(|_| Ok(()));
+        audio.expect_set_playing().with(eq(false)).times(1).in_sequence(&mut sequence).returning(|_| Ok(()));
+
+        let mut interpreter = new_interpreter(&[
+            0x60, 0x02, // V0 = 2
+            0xf0, 0x18, // sound timer = V0
+            0x12, 0x04, // jump 0x204
+        ]);
+        interpreter.set_instructions_per_frame(1);
+        interpreter.set_audio(Box::new(audio));
+
+        for _ in 0..6 {
+            let result = chip8_traits::Interpreter::update_frame(&mut interpreter);
+            assert!(result.is_ok());
+        }
+    }
+}
@@ -0,0 +1,90 @@
+//! Mocks and interpreter factories shared by the integration tests, each of which uses some of them
+#![allow(dead_code)]
+
+use std::{slice::Iter, sync::{Arc, atomic::{AtomicUsize, Ordering}}};
+
+use chip8_base::{Debugger, Interpreter};
+use chip8_traits::KeyEvent;
+
+use mockall::mock;
+
+mock! {
+    pub Renderer {}
+    impl chip8_traits::Renderer for Renderer {
+        fn render<'a>(&mut self, memory: Iter<'a, Vec<u8>>) -> Result<(), chip8_base::Error>;
+    }
+}
+
+mock! {
+    pub Keypad {}
+    impl chip8_traits::Keypad for Keypad {
+        fn state(&self) -> [bool; 16];
+        fn key_state(&self, key_index: usize) -> bool;
+        fn take_events(&mut self) -> Vec<KeyEvent>;
+    }
+}
+
+mock! {
+    pub Random {}
+    impl chip8_traits::Random for Random {
+        fn value(&mut self) -> u8;
+    }
+}
+
+pub type TestInterpreter = Interpreter<MockRenderer, MockKeypad, MockRandom>;
+pub type TestDebugger = Debugger<MockRenderer, MockKeypad, MockRandom>;
+
+/// A renderer that accepts every frame
+pub fn new_renderer() -> MockRenderer {
+    let mut renderer = MockRenderer::new();
+    renderer.expect_render().returning(|_| Ok(()));
+    renderer
+}
+
+/// A keypad with nothing pressed
+pub fn idle_keypad() -> MockKeypad {
+    let mut keypad = MockKeypad::new();
+    keypad.expect_state().returning(|| [false; 16]);
+    keypad.expect_key_state().returning(|_| false);
+    keypad.expect_take_events().returning(Vec::new);
+    keypad
+}
+
+/// A keypad that reports `events[n]` on the nth poll, with `held[n]` held afterwards
+pub fn scripted_keypad(events: Vec<Vec<KeyEvent>>, held: Vec<Option<usize>>) -> MockKeypad {
+    let polls = Arc::new(AtomicUsize::new(0));
+
+    let mut keypad = MockKeypad::new();
+    let events_polls = Arc::clone(&polls);
+    keypad.expect_take_events().returning(move || {
+        let poll = events_polls.fetch_add(1, Ordering::SeqCst);
+        events.get(poll).cloned().unwrap_or_default()
+    });
+    keypad.expect_state().returning(move || {
+        let mut state = [false; 16];
+        if let Some(Some(key)) = held.get(polls.load(Ordering::SeqCst).saturating_sub(1)) {
+            state[*key] = true;
+        }
+        state
+    });
+
+    keypad
+}
+
+/// An interpreter with the crate's default parts, `keypad`, `random` and `program` loaded at 0x200
+pub fn new_interpreter_with<Keypad, Random>(keypad: Keypad, random: Random, program: &[u8]) -> Interpreter<MockRenderer, Keypad, Random>
+where Keypad: chip8_traits::Keypad,
+    Random: chip8_traits::Random {
+    let mut interpreter = Interpreter::new_crate_defaults(new_renderer(), keypad, random);
+    chip8_traits::Interpreter::load(&mut interpreter, program.to_vec(), 0x200);
+    interpreter
+}
+
+/// An interpreter with nothing pressed and `program` loaded at 0x200
+pub fn new_interpreter(program: &[u8]) -> TestInterpreter {
+    new_interpreter_with(idle_keypad(), MockRandom::new(), program)
+}
+
+pub fn new_debugger(program: &[u8]) -> TestDebugger {
+    Debugger::new(new_interpreter(program))
+}
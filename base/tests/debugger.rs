@@ -0,0 +1,150 @@
+mod common;
+
+#[cfg(test)]
+mod debugger_tests {
+    use chip8_base::{Access, StopReason, debugger::{Breakpoint, Comparison, Condition, OpcodeBreakpoint, Operand, Watchpoint}};
+
+    use crate::common::{TestDebugger, new_debugger};
+
+    const PROGRAM: [u8; 14] = [
+        0x60, 0x05, // 0x200: V0 = 5
+        0x22, 0x0a, // 0x202: call 0x20a
+        0xa3, 0x00, // 0x204: I = 0x300
+        0xf0, 0x55, // 0x206: Memory[I] = V0
+        0x12, 0x08, // 0x208: jump to itself
+        0x70, 0x01, // 0x20a: V0 += 1
+        0x00, 0xee, // 0x20c: return
+    ];
+
+    fn program_counter(debugger: &TestDebugger) -> usize {
+        debugger.interpreter().dump_program_counter()
+    }
+
+    #[test]
+    fn breakpoint_test() {
+        let mut debugger = new_debugger(&PROGRAM);
+        debugger.add_breakpoint(Breakpoint { address: 0x204, condition: None });
+
+        assert_eq!(debugger.run_frame(), Some(StopReason::Breakpoint { address: 0x204 }));
+        assert_eq!(program_counter(&debugger), 0x204);
+
+        // Resuming runs past the breakpoint it stopped at
+        assert_eq!(debugger.run_frame(), None);
+        assert_eq!(program_counter(&debugger), 0x208);
+
+        assert!(debugger.remove_breakpoint(0x204));
+        assert!(!debugger.remove_breakpoint(0x204));
+    }
+
+    #[test]
+    fn conditional_breakpoint_test() {
+        let mut debugger = new_debugger(&PROGRAM);
+        debugger.add_breakpoint(Breakpoint { address: 0x20c, condition: Some(Condition::parse("V0 == 0x10").unwrap()) });
+        assert_eq!(debugger.run_frame(), None);
+
+        let mut debugger = new_debugger(&PROGRAM);
+        debugger.add_breakpoint(Breakpoint { address: 0x20c, condition: Some(Condition::parse("v0 >= 6").unwrap()) });
+        assert_eq!(debugger.run_frame(), Some(StopReason::Breakpoint { address: 0x20c }));
+    }
+
+    #[test]
+    fn condition_parse_test() {
+        assert_eq!(Condition::parse("V3 == 0x10"), Ok(Condition { operand: Operand::Register(3), comparison: Comparison::Equal, value: 0x10 }));
+        assert_eq!(Condition::parse("I<=768"), Ok(Condition { operand: Operand::IndexRegister, comparison: Comparison::LessOrEqual, value: 0x300 }));
+        assert_eq!(Condition::parse("DT != 0").unwrap().to_string(), "DT != 0x0");
+
+        assert!(Condition::parse("V3 = 1").is_err());
+        assert!(Condition::parse("VG == 1").is_err());
+        assert!(Condition::parse("V3 == x").is_err());
+    }
+
+    #[test]
+    fn watchpoint_test() {
+        let mut debugger = new_debugger(&PROGRAM);
+        debugger.add_watchpoint(Watchpoint { range: 0x300..0x301, access: Access::Read });
+        assert_eq!(debugger.run_frame(), None);
+
+        let mut debugger = new_debugger(&PROGRAM);
+        debugger.add_watchpoint(Watchpoint { range: 0x2ff..0x302, access: Access::ReadWrite });
+        assert_eq!(debugger.run_frame(), Some(StopReason::Watchpoint { address: 0x206, location: 0x300, access: Access::Write }));
+        assert_eq!(chip8_traits::Interpreter::dump_memory(debugger.interpreter())[0x300], 6);
+        assert_eq!(program_counter(&debugger), 0x208);
+
+        assert!(debugger.remove_watchpoint(0).is_some());
+        assert!(debugger.remove_watchpoint(0).is_none());
+    }
+
+    #[test]
+    fn skip_watchpoint_test() {
+        let mut debugger = new_debugger(&[
+            0x60, 0x05, // 0x200: V0 = 5
+            0x30, 0x05, // 0x202: skip if V0 == 5
+            0x60, 0x00, // 0x204: V0 = 0
+            0x12, 0x06, // 0x206: jump to itself
+        ]);
+        // Looking at the skipped instruction's length isn't a read by the program
+        debugger.add_watchpoint(Watchpoint { range: 0x204..0x206, access: Access::Read });
+
+        assert_eq!(debugger.run_frame(), None);
+        assert_eq!(program_counter(&debugger), 0x206);
+    }
+
+    #[test]
+    fn stack_watchpoint_test() {
+        let mut debugger = new_debugger(&PROGRAM);
+        debugger.interpreter_mut().set_stack_in_memory(true);
+        debugger.add_watchpoint(Watchpoint { range: 0xece..0xed0, access: Access::ReadWrite });
+
+        assert_eq!(debugger.run_frame(), Some(StopReason::Watchpoint { address: 0x202, location: 0xece, access: Access::Write }));
+        assert_eq!(program_counter(&debugger), 0x20a);
+        assert_eq!(debugger.run_frame(), Some(StopReason::Watchpoint { address: 0x20c, location: 0xece, access: Access::Read }));
+        assert_eq!(program_counter(&debugger), 0x204);
+    }
+
+    #[test]
+    fn opcode_breakpoint_test() {
+        let breakpoint = OpcodeBreakpoint::parse("DXYN").unwrap();
+        assert_eq!(breakpoint, OpcodeBreakpoint { value: 0xd000, mask: 0xf000 });
+        assert!(breakpoint.matches(0xd125));
+        assert!(!breakpoint.matches(0x6125));
+        assert!(OpcodeBreakpoint::parse("DXY").is_err());
+
+        let mut debugger = new_debugger(&PROGRAM);
+        debugger.add_opcode_breakpoint(OpcodeBreakpoint::parse("FX55").unwrap());
+        assert_eq!(debugger.run_frame(), Some(StopReason::Opcode { address: 0x206, opcode: 0xf055 }));
+    }
+
+    #[test]
+    fn step_test() {
+        let mut debugger = new_debugger(&PROGRAM);
+        assert_eq!(debugger.step_into(), StopReason::Step);
+        assert_eq!(debugger.step_over(), StopReason::Step);
+        assert_eq!(program_counter(&debugger), 0x204);
+        assert_eq!(debugger.interpreter().variable_registers().get(0), Some(6));
+
+        let mut debugger = new_debugger(&PROGRAM);
+        debugger.step_into();
+        debugger.step_into();
+        assert_eq!(program_counter(&debugger), 0x20a);
+        assert_eq!(debugger.step_out(), StopReason::Step);
+        assert_eq!(program_counter(&debugger), 0x204);
+
+        // Stepping over stops at breakpoints inside the subroutine
+        let mut debugger = new_debugger(&PROGRAM);
+        debugger.add_breakpoint(Breakpoint { address: 0x20c, condition: None });
+        debugger.step_into();
+        assert_eq!(debugger.step_over(), StopReason::Breakpoint { address: 0x20c });
+    }
+
+    #[test]
+    fn step_limit_test() {
+        let mut debugger = new_debugger(&PROGRAM);
+        debugger.step_into();
+        debugger.step_into();
+        debugger.step_into();
+        // Skip the return so the subroutine never finishes
+        chip8_traits::Interpreter::load(debugger.interpreter_mut(), vec![0x12, 0x0c], 0x20c);
+
+        assert_eq!(debugger.step_out(), StopReason::StepLimit);
+    }
+}
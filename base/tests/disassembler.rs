@@ -0,0 +1,136 @@
+#[cfg(test)]
+mod disassembler_tests {
+    use std::fs;
+
+    use chip8_base::{Disassembly, disassembler::{DEFAULT_START, LabelKind, Line}};
+
+    const PROGRAM: [u8; 24] = [
+        0x22, 0x08, // 0x200: call 0x208
+        0xa2, 0x10, // 0x202: I = 0x210
+        0xb2, 0x0c, // 0x204: jump to 0x20c + V0
+        0xff, 0xff, // 0x206: never reached
+        0x30, 0x01, // 0x208: skip if V0 == 1
+        0x00, 0xee, // 0x20a: return
+        0x12, 0x0c, // 0x20c: jump to itself
+        0x12, 0x0a, // 0x20e: jump to 0x20a
+        0xf0, 0x90, 0x90, 0x90, 0xf0, 0x00, 0xf0, 0x10, // 0x210: sprite
+    ];
+
+    #[test]
+    fn lines_test() {
+        let disassembly = Disassembly::new(&PROGRAM, DEFAULT_START);
+
+        assert_eq!(disassembly.lines(), vec![
+            Line::Instruction { address: 0x200, bytes: vec![0x22, 0x08] },
+            Line::Instruction { address: 0x202, bytes: vec![0xa2, 0x10] },
+            Line::Instruction { address: 0x204, bytes: vec![0xb2, 0x0c] },
+            Line::Data { address: 0x206, bytes: vec![0xff, 0xff] },
+            Line::Instruction { address: 0x208, bytes: vec![0x30, 0x01] },
+            Line::Instruction { address: 0x20a, bytes: vec![0x00, 0xee] },
+            Line::Instruction { address: 0x20c, bytes: vec![0x12, 0x0c] },
+            Line::Instruction { address: 0x20e, bytes: vec![0x12, 0x0a] },
+            Line::Data { address: 0x210, bytes: vec![0xf0, 0x90, 0x90, 0x90, 0xf0, 0x00, 0xf0, 0x10] },
+        ]);
+        assert!(disassembly.is_code(0x20f));
+        assert!(!disassembly.is_code(0x206));
+    }
+
+    #[test]
+    fn labels_test() {
+        let disassembly = Disassembly::new(&PROGRAM, DEFAULT_START);
+
+        assert_eq!(disassembly.labels().collect::<Vec<usize>>(), vec![0x208, 0x20a, 0x20c, 0x210]);
+        assert_eq!(disassembly.label_kind(0x208), Some(LabelKind::Subroutine));
+        assert_eq!(disassembly.label(0x20a), Some("label_20a".to_string()));
+        assert_eq!(disassembly.label(0x210), Some("data_210".to_string()));
+        assert_eq!(disassembly.label(0x200), None);
+    }
+
+    #[test]
+    fn listing_test() {
+        let listing = Disassembly::new(&PROGRAM, DEFAULT_START).listing();
+        let lines: Vec<&str> = listing.lines().collect();
+
+        assert_eq!(lines[0], "0x0200  2208              CALL sub_208");
+        assert_eq!(lines[1], "0x0202  a210              LD I, data_210");
+        assert_eq!(lines[2], "0x0204  b20c              JP V0, label_20c");
+        assert_eq!(lines[3], "0x0206  ffff              DB 0xff, 0xff");
+        assert_eq!(lines[4], "sub_208:");
+        assert_eq!(lines[5], "0x0208  3001              SE V0, 0x01");
+    }
+
+    #[test]
+    fn octo_test() {
+        let octo = Disassembly::new(&PROGRAM, DEFAULT_START).octo();
+
+        assert_eq!(octo, [
+            ": main",
+            "\tsub_208",
+            "\ti := data_210",
+            "\tjump0 label_20c",
+            "\t:byte 0xff :byte 0xff",
+            "",
+            ": sub_208",
+            "\tif v0 != 1 then",
+            "",
+            ": label_20a",
+            "\treturn",
+            "",
+            ": label_20c",
+            "\tjump label_20c",
+            "\tjump label_20a",
+            "",
+            ": data_210",
+            "\t:byte 0xf0 :byte 0x90 :byte 0x90 :byte 0x90 :byte 0xf0 :byte 0x00 :byte 0xf0 :byte 0x10",
+            "",
+        ].join("\n"));
+    }
+
+    #[test]
+    fn octo_statement_test() {
+        let disassembly = Disassembly::new(&PROGRAM, DEFAULT_START);
+
+        assert_eq!(disassembly.octo_statement(&[0x3a, 0x40, 0x12, 0x08]), "if va != 64 then");
+        assert_eq!(disassembly.octo_statement(&[0xe1, 0xa1]), "if v1 key then");
+        assert_eq!(disassembly.octo_statement(&[0x8a, 0xb7]), "va =- vb");
+        assert_eq!(disassembly.octo_statement(&[0xd0, 0x15]), "sprite v0 v1 5");
+        assert_eq!(disassembly.octo_statement(&[0x23, 0x00]), ":call 0x300");
+        assert_eq!(disassembly.octo_statement(&[0x52, 0x43]), "load v2 - v4");
+    }
+
+    #[test]
+    fn octo_inside_label_test() {
+        // I pointed at the second byte of an instruction so the program can change its operand
+        let program = [0xa2, 0x05, 0x60, 0x00, 0x71, 0x01, 0x12, 0x04];
+        let octo = Disassembly::new(&program, DEFAULT_START).octo();
+
+        assert!(octo.contains("\t# v1 += 1\n\t:byte 0x71\n\n: data_205\n\t:byte 0x01\n"));
+    }
+
+    #[test]
+    fn long_instruction_test() {
+        let program = [0xf0, 0x00, 0x02, 0x06, 0x12, 0x04, 0xaa];
+        let disassembly = Disassembly::new(&program, DEFAULT_START);
+
+        assert_eq!(disassembly.mnemonic(&program[0..4]), "LD I, long data_206");
+        assert_eq!(disassembly.lines()[0], Line::Instruction { address: 0x200, bytes: vec![0xf0, 0x00, 0x02, 0x06] });
+        assert_eq!(disassembly.lines()[2], Line::Data { address: 0x206, bytes: vec![0xaa] });
+    }
+
+    #[test]
+    fn programs_test() {
+        for entry in fs::read_dir("../programs").unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_none_or(|extension| extension != "ch8") {
+                continue;
+            }
+
+            let program = fs::read(&path).unwrap();
+            let disassembly = Disassembly::new(&program, DEFAULT_START);
+            let lines = disassembly.lines();
+
+            assert!(disassembly.is_code(DEFAULT_START), "{:?} should start with code", path);
+            assert_eq!(lines.iter().map(|line| line.bytes().len()).sum::<usize>(), program.len(), "{:?} should be fully listed", path);
+        }
+    }
+}
@@ -0,0 +1,61 @@
+mod common;
+
+#[cfg(test)]
+mod error_tests {
+    use chip8_base::{Error, ErrorContext, Interpreter};
+
+    use crate::common::{MockRandom, MockRenderer, TestInterpreter, idle_keypad, new_interpreter};
+
+    /// Run instructions until one fails
+    fn run_to_error(interpreter: &mut TestInterpreter) -> Error {
+        for _ in 0..100 {
+            if let Err(error) = chip8_traits::Interpreter::update(interpreter) {
+                return error;
+            }
+        }
+        panic!("No error");
+    }
+
+    #[test]
+    fn unsupported_opcode_test() {
+        let mut interpreter = new_interpreter(&[0x60, 0x01, 0x50, 0x1f]);
+
+        let error = run_to_error(&mut interpreter);
+        assert_eq!(error, Error::UnsupportedOpcode(ErrorContext { program_counter: 0x202, opcode: 0x501f, cycle: 2 }));
+        assert_eq!(error.to_string(), "Unsupported opcode at 0x0202 (opcode 501F, cycle 2)");
+    }
+
+    #[test]
+    fn stack_underflow_test() {
+        let mut interpreter = new_interpreter(&[0x22, 0x04, 0x00, 0xee, 0x00, 0xee]);
+
+        let error = run_to_error(&mut interpreter);
+        assert_eq!(error, Error::StackUnderflow(ErrorContext { program_counter: 0x202, opcode: 0x00ee, cycle: 3 }));
+    }
+
+    #[test]
+    fn program_counter_out_of_range_test() {
+        let mut interpreter = new_interpreter(&[0x1f, 0xff]);
+
+        let error = run_to_error(&mut interpreter);
+        assert!(matches!(error, Error::ProgramCounterOutOfRange(_)));
+        assert_eq!(error.context().program_counter, 0xfff);
+        assert_eq!(error.context().cycle, 1);
+    }
+
+    #[test]
+    fn renderer_test() {
+        let mut renderer = MockRenderer::new();
+        renderer.expect_render().returning(|_| Err(Error::renderer("No display")));
+        let mut interpreter = Interpreter::new_crate_defaults(renderer, idle_keypad(), MockRandom::new());
+        chip8_traits::Interpreter::load(&mut interpreter, vec![0x60, 0x01, 0x12, 0x02], 0x200);
+        interpreter.set_instructions_per_frame(3);
+
+        let error = chip8_traits::Interpreter::update_frame(&mut interpreter).unwrap_err();
+        assert_eq!(error, Error::Renderer {
+            message: "No display".to_string(),
+            context: ErrorContext { program_counter: 0x202, opcode: 0x1202, cycle: 3 },
+        });
+        assert_eq!(error.to_string(), "Renderer failed: No display at 0x0202 (opcode 1202, cycle 3)");
+    }
+}
@@ -0,0 +1,146 @@
+mod common;
+
+#[cfg(test)]
+mod gdb_tests {
+    use std::{io::{Read, Write}, net::{TcpListener, TcpStream}, thread};
+
+    use chip8_base::gdb::GdbStub;
+
+    use crate::common::{TestDebugger, new_debugger};
+
+    const PROGRAM: [u8; 14] = [
+        0x60, 0x05, // 0x200: V0 = 5
+        0x22, 0x0a, // 0x202: call 0x20a
+        0xa3, 0x00, // 0x204: I = 0x300
+        0xf0, 0x55, // 0x206: Memory[I] = V0
+        0x12, 0x08, // 0x208: jump to itself
+        0x70, 0x01, // 0x20a: V0 += 1
+        0x00, 0xee, // 0x20c: return
+    ];
+
+    /// Minimal client, sending each packet and returning the reply
+    struct Client {
+        stream: TcpStream,
+    }
+
+    impl Client {
+        fn read_byte(&mut self) -> u8 {
+            let mut byte = [0];
+            self.stream.read_exact(&mut byte).unwrap();
+            byte[0]
+        }
+
+        fn send(&mut self, packet: &str) -> String {
+            let checksum = packet.bytes().fold(0u8, |sum, byte| sum.wrapping_add(byte));
+            write!(self.stream, "${}#{:02x}", packet, checksum).unwrap();
+            assert_eq!(self.read_byte(), b'+');
+
+            assert_eq!(self.read_byte(), b'$');
+            let mut reply = vec![];
+            loop {
+                match self.read_byte() {
+                    b'#' => break,
+                    byte => reply.push(byte),
+                }
+            }
+            self.read_byte();
+            self.read_byte();
+            self.stream.write_all(b"+").unwrap();
+
+            String::from_utf8(reply).unwrap()
+        }
+    }
+
+    /// Run `script` against a stub serving `debugger`, returning the debugger once the script kills the session
+    fn session<Script>(mut debugger: TestDebugger, script: Script) -> TestDebugger
+    where Script: FnOnce(&mut Client) + Send + 'static {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let client = thread::spawn(move || {
+            let mut client = Client { stream: TcpStream::connect(address).unwrap() };
+            script(&mut client);
+            write!(client.stream, "$k#6b").unwrap();
+        });
+
+        let (stream, _) = listener.accept().unwrap();
+        GdbStub::new(&mut debugger, stream).unwrap().run().unwrap();
+        client.join().unwrap();
+
+        debugger
+    }
+
+    #[test]
+    fn registers_test() {
+        let debugger = session(new_debugger(&PROGRAM), |client| {
+            assert_eq!(client.send("?"), "S05");
+            assert_eq!(client.send("p11"), "0002");
+            assert_eq!(client.send("g"), format!("{}{}{}", "00".repeat(16), "0000", "00020000"));
+
+            assert_eq!(client.send("P3=2a"), "OK");
+            assert_eq!(client.send("P10=0003"), "OK");
+            assert_eq!(client.send("p3"), "2a");
+            assert_eq!(client.send("p14"), "E01");
+
+            assert!(client.send("qXfer:features:read:target.xml:0,fff").contains("<reg name=\"pc\" bitsize=\"16\" type=\"code_ptr\"/>"));
+        });
+
+        assert_eq!(debugger.interpreter().variable_registers().get(3), Some(0x2a));
+        assert_eq!(debugger.interpreter().index_register(), 0x300);
+    }
+
+    #[test]
+    fn memory_test() {
+        let debugger = session(new_debugger(&PROGRAM), |client| {
+            assert_eq!(client.send("m200,4"), "6005220a");
+            assert_eq!(client.send("M300,2:beef"), "OK");
+            assert_eq!(client.send("m2ff,3"), "00beef");
+            assert_eq!(client.send("mfff,2"), "E01");
+            assert_eq!(client.send("M300,2:be"), "E01");
+        });
+
+        assert_eq!(chip8_traits::Interpreter::dump_memory(debugger.interpreter())[0x301], 0xef);
+    }
+
+    #[test]
+    fn breakpoint_test() {
+        let debugger = session(new_debugger(&PROGRAM), |client| {
+            assert_eq!(client.send("Z0,20c,2"), "OK");
+            assert_eq!(client.send("c"), "S05");
+            assert_eq!(client.send("p11"), "0c02");
+
+            assert_eq!(client.send("z0,20c,2"), "OK");
+            assert_eq!(client.send("Z2,300,1"), "OK");
+            assert_eq!(client.send("c"), "T05watch:300;");
+            assert_eq!(client.send("p11"), "0802");
+
+            assert_eq!(client.send("s"), "S05");
+            assert_eq!(client.send("m300,1"), "06");
+        });
+
+        assert_eq!(debugger.interpreter().dump_program_counter(), 0x208);
+    }
+
+    #[test]
+    fn malformed_packet_test() {
+        session(new_debugger(&PROGRAM), |client| {
+            assert_eq!(client.send(""), "");
+            assert_eq!(client.send("é"), "");
+            assert_eq!(client.send("€1"), "");
+            assert_eq!(client.send("qXfer:features:read:target.xml:1,ffffffffffffffff").chars().next(), Some('l'));
+            assert_eq!(client.send("?"), "S05");
+        });
+    }
+
+    #[test]
+    fn step_test() {
+        let debugger = session(new_debugger(&PROGRAM), |client| {
+            assert_eq!(client.send("s"), "S05");
+            assert_eq!(client.send("s"), "S05");
+            assert_eq!(client.send("p11"), "0a02");
+            assert_eq!(client.send("s204"), "S05");
+        });
+
+        assert_eq!(debugger.interpreter().dump_program_counter(), 0x206);
+    }
+}
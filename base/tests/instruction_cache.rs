@@ -0,0 +1,92 @@
+mod common;
+
+#[cfg(test)]
+mod instruction_cache_tests {
+    use chip8_base::{Instruction, InstructionCache, MemoryPolicy};
+
+    use crate::common::TestInterpreter;
+
+    /// Rewrites its own first instruction from V3 = 1 to V3 = 7
+    const PROGRAM: [u8; 10] = [
+        0x63, 0x01, // V3 = 1
+        0xa2, 0x01, // I = 0x201
+        0x60, 0x07, // V0 = 7
+        0xf0, 0x55, // Memory[I] = V0
+        0x12, 0x00, // jump to 0x200
+    ];
+
+    fn new_interpreter(cache: bool) -> TestInterpreter {
+        let mut interpreter = crate::common::new_interpreter(&PROGRAM);
+        interpreter.set_instruction_cache_enabled(cache);
+        interpreter
+    }
+
+    #[test]
+    fn invalidate_test() {
+        let mut cache = InstructionCache::new(16);
+        for location in 0..16 {
+            cache.insert(location, Instruction::new(0x00, 0xe0));
+        }
+
+        cache.invalidate(8);
+        let cached: Vec<bool> = (0..16).map(|location| cache.get(location).is_some()).collect();
+        assert_eq!(cached[4..10], [true, false, false, false, false, true]);
+
+        cache.invalidate(1);
+        assert!(cache.get(0).is_none());
+        assert!(cache.get(2).is_some());
+
+        cache.set_enabled(false);
+        cache.insert(2, Instruction::new(0x00, 0xe0));
+        assert!(cache.get(2).is_none());
+    }
+
+    #[test]
+    fn self_modifying_test() {
+        for cache in [true, false] {
+            let mut interpreter = new_interpreter(cache);
+
+            for _ in 0..5 {
+                chip8_traits::Interpreter::update(&mut interpreter).unwrap();
+            }
+            assert_eq!(interpreter.variable_registers().get(3), Some(1));
+            // The write dropped the first instruction but not the rest
+            assert!(interpreter.memory().instruction_cache().get(0x200).is_none());
+            assert_eq!(interpreter.memory().instruction_cache().get(0x202).is_some(), cache);
+
+            chip8_traits::Interpreter::update(&mut interpreter).unwrap();
+            assert_eq!(interpreter.variable_registers().get(3), Some(7));
+        }
+    }
+
+    #[test]
+    fn external_write_test() {
+        let mut interpreter = new_interpreter(true);
+        chip8_traits::Interpreter::update(&mut interpreter).unwrap();
+
+        // A debugger poking memory
+        chip8_traits::Memory::set(interpreter.memory_mut(), 0x201, 0x09).unwrap();
+        interpreter.set_program_counter(0x200);
+        chip8_traits::Interpreter::update(&mut interpreter).unwrap();
+        assert_eq!(interpreter.variable_registers().get(3), Some(9));
+    }
+
+    #[test]
+    fn wrapped_instruction_test() {
+        let mut interpreter = new_interpreter(true);
+        interpreter.set_memory_policy(MemoryPolicy::Wrap);
+        let last = chip8_traits::Interpreter::dump_memory(&interpreter).len() - 1;
+
+        // V3 = 1, its second byte wrapping round to the start of memory
+        chip8_traits::Memory::set(interpreter.memory_mut(), last, 0x63).unwrap();
+        chip8_traits::Memory::set(interpreter.memory_mut(), 0, 0x01).unwrap();
+        interpreter.set_program_counter(last);
+        chip8_traits::Interpreter::update(&mut interpreter).unwrap();
+        assert_eq!(interpreter.variable_registers().get(3), Some(1));
+
+        chip8_traits::Memory::set(interpreter.memory_mut(), 0, 0x09).unwrap();
+        interpreter.set_program_counter(last);
+        chip8_traits::Interpreter::update(&mut interpreter).unwrap();
+        assert_eq!(interpreter.variable_registers().get(3), Some(9));
+    }
+}
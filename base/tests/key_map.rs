@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod key_map_tests {
+    use chip8_base::{KeyMap, KeyMapError};
+
+    const KEY_MAP_TOML: &str = include_str!("../../programs/keymap.toml");
+
+    #[test]
+    fn default_test() {
+        let key_map = KeyMap::new();
+
+        assert_eq!(key_map.key_indexes("1"), vec![0x1]);
+        assert_eq!(key_map.key_indexes("4"), vec![0xc]);
+        assert_eq!(key_map.key_indexes("X"), vec![0x0]);
+        assert_eq!(key_map.key_indexes("v"), vec![0xf]);
+        assert!(key_map.key_indexes("Space").is_empty());
+        assert_eq!(key_map.keys(0x8), ["s".to_string()]);
+    }
+
+    #[test]
+    fn keymap_file_test() {
+        let key_map = KeyMap::parse(KEY_MAP_TOML).expect("keymap.toml should parse");
+        // The file spells out the QWERTY layout
+        let unmatched = key_map.for_program(0, None);
+        assert!((0..16).all(|index| unmatched.keys(index) == KeyMap::new().keys(index)));
+
+        let airplane = key_map.for_program(0, Some("Airplane"));
+        assert_eq!(airplane.key_indexes(" "), vec![0x8]);
+        assert_eq!(airplane.key_indexes("s"), vec![0x8]);
+        assert!(key_map.for_program(0, Some("15 PUZZLE")).key_indexes(" ").is_empty());
+    }
+
+    #[test]
+    fn parse_test() {
+        let toml = r#"
+            [keys]
+            5 = ["w", "ArrowUp"]
+            8 = ["s", "ArrowDown"]
+
+            [[overrides]]
+            hash = "0x1234ABCD"
+            keys = { 5 = ["i"] }
+        "#;
+        let json = r#"{
+            "keys": { "5": ["w", "ArrowUp"], "8": ["s", "ArrowDown"] },
+            "overrides": [{ "hash": "1234abcd", "keys": { "5": ["i"] } }]
+        }"#;
+
+        for key_map in [KeyMap::parse(toml).unwrap(), KeyMap::parse(json).unwrap()] {
+            assert_eq!(key_map.key_indexes("arrowup"), vec![0x5]);
+            assert_eq!(key_map.key_indexes("w"), vec![0x5]);
+            assert_eq!(key_map.key_indexes("q"), vec![0x4]);
+
+            let program = key_map.for_program(0x1234abcd, None);
+            assert_eq!(program.key_indexes("i"), vec![0x5]);
+            assert!(program.key_indexes("w").is_empty());
+            assert_eq!(program.key_indexes("ArrowDown"), vec![0x8]);
+        }
+    }
+
+    #[test]
+    fn shared_key_test() {
+        let key_map = KeyMap::parse("[keys]\n4 = [\"q\", \"Space\"]\n6 = [\"e\", \"Space\"]").unwrap();
+
+        assert_eq!(key_map.key_indexes(" "), vec![0x4, 0x6]);
+    }
+
+    #[test]
+    fn program_hash_test() {
+        assert_eq!(KeyMap::program_hash(b"123456789"), 0xcbf43926);
+    }
+
+    #[test]
+    fn parse_error_test() {
+        assert!(matches!(KeyMap::parse("[keys]\n10 = [\"q\"]"), Err(KeyMapError::InvalidKey(key)) if key == "10"));
+        assert!(matches!(KeyMap::parse("[keys]\nG = [\"q\"]"), Err(KeyMapError::InvalidKey(_))));
+        assert!(matches!(KeyMap::parse("[[overrides]]\nhash = \"xyz\"\nkeys = {}"), Err(KeyMapError::InvalidHash(_))));
+        assert!(matches!(KeyMap::parse("[keys"), Err(KeyMapError::ParseToml(_))));
+        assert!(matches!(KeyMap::parse("{ \"keys\": 1 }"), Err(KeyMapError::ParseJson(_))));
+    }
+}
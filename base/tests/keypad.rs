@@ -0,0 +1,91 @@
+mod common;
+
+#[cfg(test)]
+mod keypad_tests {
+    use chip8_base::{KeyLatch, Quirks, SaveState};
+    use chip8_traits::KeyEvent;
+
+    use crate::common::{MockKeypad, MockRandom, TestInterpreter, new_interpreter_with, scripted_keypad};
+
+    /// Waits for a key into V1, then stays put
+    const PROGRAM: [u8; 4] = [
+        0xf1, 0x0a, // V1 = wait for key
+        0x12, 0x02, // jump to self
+    ];
+
+    fn new_interpreter(keypad: MockKeypad, quirks: Quirks) -> TestInterpreter {
+        let mut interpreter = new_interpreter_with(keypad, MockRandom::new(), &PROGRAM);
+        interpreter.set_quirks(quirks);
+        interpreter
+    }
+
+    fn update_frames(interpreter: &mut TestInterpreter, count: usize) {
+        for _ in 0..count {
+            chip8_traits::Interpreter::update_frame(interpreter).unwrap();
+        }
+    }
+
+    #[test]
+    fn key_release_quirk_test() {
+        let events = vec![vec![], vec![KeyEvent::Pressed(5)], vec![], vec![KeyEvent::Released(5)]];
+        let held = vec![None, Some(5), Some(5), None];
+
+        let mut interpreter = new_interpreter(scripted_keypad(events.clone(), held.clone()), Quirks::cosmac_vip());
+        update_frames(&mut interpreter, 4);
+        assert_eq!(interpreter.dump_program_counter(), 0x200);
+        update_frames(&mut interpreter, 1);
+        assert_eq!(interpreter.dump_program_counter(), 0x202);
+        assert_eq!(interpreter.variable_registers().get(1), Some(5));
+
+        // Without the quirk the key counts as soon as it is down
+        let mut interpreter = new_interpreter(scripted_keypad(events, held), Quirks::chip48());
+        update_frames(&mut interpreter, 3);
+        assert_eq!(interpreter.dump_program_counter(), 0x202);
+        assert_eq!(interpreter.variable_registers().get(1), Some(5));
+    }
+
+    #[test]
+    fn press_and_release_in_one_frame_test() {
+        let events = vec![vec![], vec![KeyEvent::Pressed(3), KeyEvent::Released(3)]];
+
+        let mut interpreter = new_interpreter(scripted_keypad(events, vec![]), Quirks::cosmac_vip());
+        update_frames(&mut interpreter, 3);
+        assert_eq!(interpreter.variable_registers().get(1), Some(3));
+    }
+
+    #[test]
+    fn latch_test() {
+        // A key held over from before doesn't count until it is released
+        let mut latch = KeyLatch::new();
+        let mut held = [false; 16];
+        held[0xa] = true;
+        latch.update(&[], held);
+
+        latch.arm();
+        assert_eq!(latch.take_released(), None);
+        latch.update(&[], [false; 16]);
+        assert_eq!(latch.take_released(), Some(0xa));
+        assert_eq!(latch.take_released(), None);
+
+        // Only the first key pressed after arming counts
+        let mut state = [false; 16];
+        state[1] = true;
+        latch.arm();
+        latch.update(&[KeyEvent::Pressed(1), KeyEvent::Pressed(2), KeyEvent::Released(2)], state);
+        assert_eq!(latch.take_released(), None);
+        assert_eq!(KeyLatch::from_bytes(latch.to_bytes()), latch);
+    }
+
+    #[test]
+    fn save_state_test() {
+        let events = vec![vec![], vec![KeyEvent::Pressed(5)]];
+        let held = vec![None, Some(5)];
+
+        let mut interpreter = new_interpreter(scripted_keypad(events, held), Quirks::cosmac_vip());
+        update_frames(&mut interpreter, 2);
+
+        let state = SaveState::from_bytes(&interpreter.save_state()).unwrap();
+        assert_ne!(state.key_latch, KeyLatch::new());
+        assert!(state.key_latch.is_held(5));
+    }
+}
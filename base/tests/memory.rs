@@ -0,0 +1,80 @@
+mod common;
+
+#[cfg(test)]
+mod memory_tests {
+    use chip8_base::{Error, ErrorContext, Memory, MemoryPolicy};
+    use chip8_traits::Memory as _;
+
+    use crate::common::new_interpreter;
+
+    fn new_memory(policy: MemoryPolicy) -> Memory {
+        let mut memory = Memory::new(0x100);
+        memory.set_policy(policy);
+        memory
+    }
+
+    #[test]
+    fn error_policy_test() {
+        let mut memory = new_memory(MemoryPolicy::Error);
+
+        let error = Error::MemoryOutOfRange { address: 0x100, context: ErrorContext::default() };
+        assert_eq!(memory.get(0x100), Err(error.clone()));
+        assert_eq!(memory.set(0x100, 1), Err(error.clone()));
+        assert_eq!(memory.get_range(0xfe, 3), Err(error));
+        assert_eq!(memory.get_range(0xfe, 2), Ok(vec![0, 0]));
+        assert_eq!(memory.instruction(0xff), None);
+    }
+
+    #[test]
+    fn wrap_policy_test() {
+        let mut memory = new_memory(MemoryPolicy::Wrap);
+
+        memory.set(0x101, 0x12).unwrap();
+        assert_eq!(memory.get(0x01), Ok(0x12));
+        assert_eq!(memory.get_range(0xff, 3), Ok(vec![0, 0, 0x12]));
+
+        memory.set(0x00, 0x34).unwrap();
+        assert_eq!(memory.instruction(0xff).map(|instruction| instruction.word()), Some(0x0034));
+    }
+
+    #[test]
+    fn ignore_policy_test() {
+        let mut memory = new_memory(MemoryPolicy::Ignore);
+        memory.set(0x00, 0x12).unwrap();
+
+        memory.set(0x100, 0x34).unwrap();
+        assert_eq!(memory.get(0x100), Ok(0));
+        assert_eq!(memory.get_range(0xff, 2), Ok(vec![0, 0]));
+        assert_eq!(memory.dump().iter().filter(|value| **value != 0).count(), 1);
+    }
+
+    #[test]
+    fn interpreter_test() {
+        // I = 0xFFE, save V0..V3
+        let program = vec![0xaf, 0xfe, 0xf3, 0x55];
+
+        let mut interpreter = new_interpreter(&program);
+        chip8_traits::Interpreter::update(&mut interpreter).unwrap();
+        let result = chip8_traits::Interpreter::update(&mut interpreter);
+        assert_eq!(result.err(), Some(Error::MemoryOutOfRange {
+            address: 0x1000,
+            context: ErrorContext { program_counter: 0x202, opcode: 0xf355, cycle: 2 },
+        }));
+
+        interpreter.set_memory_policy(MemoryPolicy::Wrap);
+        chip8_traits::Interpreter::load(&mut interpreter, program, 0x200);
+        chip8_traits::Interpreter::update(&mut interpreter).unwrap();
+        chip8_traits::Interpreter::update(&mut interpreter).unwrap();
+    }
+
+    #[test]
+    fn sprite_test() {
+        // I = 0xFFF, draw 15 rows
+        let program = vec![0xaf, 0xff, 0xd0, 0x0f];
+
+        let mut interpreter = new_interpreter(&program);
+        interpreter.set_memory_policy(MemoryPolicy::Ignore);
+        chip8_traits::Interpreter::update(&mut interpreter).unwrap();
+        chip8_traits::Interpreter::update(&mut interpreter).unwrap();
+    }
+}
@@ -0,0 +1,93 @@
+mod common;
+
+#[cfg(test)]
+mod movie_tests {
+    use chip8_base::{Interpreter, Movie, MovieError, MovieFrame, MoviePlayer, MovieRecorder, Quirks, SeededRandom};
+    use chip8_traits::KeyEvent;
+
+    use crate::common::{MockRenderer, new_interpreter_with, scripted_keypad};
+
+    /// Waits for a key, then mixes the key and a random number into V3
+    const PROGRAM: [u8; 8] = [
+        0xf1, 0x0a, // V1 = wait for key
+        0xc2, 0xff, // V2 = random
+        0x83, 0x14, // V3 += V1
+        0x83, 0x23, // V3 ^= V2
+    ];
+
+    fn new_interpreter<Keypad: chip8_traits::Keypad>(keypad: Keypad, seed: u64) -> Interpreter<MockRenderer, Keypad, SeededRandom> {
+        let mut program = PROGRAM.to_vec();
+        program.extend_from_slice(&[0x12, 0x00]); // jump to the start
+        let mut interpreter = new_interpreter_with(keypad, SeededRandom::new(seed), &program);
+        interpreter.set_quirks(Quirks::cosmac_vip());
+        interpreter
+    }
+
+    fn update_frames<Keypad: chip8_traits::Keypad>(interpreter: &mut Interpreter<MockRenderer, Keypad, SeededRandom>, count: usize) {
+        for _ in 0..count {
+            chip8_traits::Interpreter::update_frame(interpreter).unwrap();
+        }
+    }
+
+    /// Record `frames` frames of a session pressing and releasing a few keys
+    fn record(frames: usize) -> (Movie, Vec<u8>) {
+        let events = vec![
+            vec![], vec![KeyEvent::Pressed(5)], vec![], vec![KeyEvent::Released(5)],
+            vec![KeyEvent::Pressed(2), KeyEvent::Released(2)], vec![], vec![KeyEvent::Pressed(0xe)],
+            vec![], vec![], vec![KeyEvent::Released(0xe)],
+        ];
+        let held = vec![None, Some(5), Some(5), None, None, None, Some(0xe), Some(0xe), Some(0xe), None];
+
+        let mut interpreter = new_interpreter(MovieRecorder::new(scripted_keypad(events, held)), 42);
+        update_frames(&mut interpreter, 2);
+
+        // Start recording partway through
+        interpreter.keypad_mut().restart();
+        let start_state = interpreter.save_state();
+        update_frames(&mut interpreter, frames);
+
+        let movie = interpreter.keypad().movie(start_state, interpreter.instructions_per_frame());
+        (movie, interpreter.save_state())
+    }
+
+    #[test]
+    fn replay_test() {
+        let (movie, end_state) = record(12);
+        assert_eq!(movie.seed().len(), 8);
+        // Key 5 was already held when recording started, so the first change is its release
+        assert_eq!(movie.frames.first(), Some(&MovieFrame { frame: 1, events: vec![KeyEvent::Released(5)], keys: 0 }));
+
+        // A different seed and keypad are replaced by the movie's
+        let player = MoviePlayer::new(&movie);
+        let mut interpreter = new_interpreter(player, 7);
+        interpreter.set_instructions_per_frame(1);
+        interpreter.start_replay(&movie).unwrap();
+        update_frames(&mut interpreter, 12);
+
+        assert!(interpreter.keypad().is_finished());
+        assert_eq!(interpreter.save_state(), end_state);
+        assert_ne!(interpreter.variable_registers().get(3), Some(0));
+    }
+
+    #[test]
+    fn bytes_test() {
+        let (movie, _) = record(12);
+        let bytes = movie.to_bytes();
+        assert_eq!(Movie::from_bytes(&bytes), Ok(movie));
+
+        let mut corrupt = bytes.clone();
+        corrupt[12] ^= 0xff;
+        assert_eq!(Movie::from_bytes(&corrupt), Err(MovieError::ChecksumMismatch));
+        assert_eq!(Movie::from_bytes(b"C8SS...."), Err(MovieError::BadMagic));
+        assert_eq!(Movie::from_bytes(&bytes[0..8]), Err(MovieError::Truncated));
+    }
+
+    #[test]
+    fn many_events_test() {
+        let (mut movie, _) = record(2);
+        let events = (0..300).map(|index| if index % 2 == 0 { KeyEvent::Pressed(3) } else { KeyEvent::Released(3) }).collect();
+        movie.frames.push(MovieFrame { frame: 2, events, keys: 0 });
+
+        assert_eq!(Movie::from_bytes(&movie.to_bytes()), Ok(movie));
+    }
+}
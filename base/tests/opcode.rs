@@ -0,0 +1,44 @@
+#[cfg(test)]
+mod opcode_tests {
+    use chip8_base::{Count16, Count8, Instruction, Opcode};
+
+    #[test]
+    fn decode_test() {
+        assert_eq!(Opcode::decode(0x00e0), Some(Opcode::Clear));
+        assert_eq!(Opcode::decode(0x00c3), Some(Opcode::ScrollDown { n: 3 }));
+        assert_eq!(Opcode::decode(0x2abc), Some(Opcode::Call { nnn: 0xabc }));
+        assert_eq!(Opcode::decode(0x7a05), Some(Opcode::AddImm { x: 0xa, nn: 0x05 }));
+        assert_eq!(Opcode::decode(0x812e), Some(Opcode::ShiftLeft { x: 1, y: 2 }));
+        assert_eq!(Opcode::decode(0xb123), Some(Opcode::JumpOffset { x: 1, nnn: 0x123 }));
+        assert_eq!(Opcode::decode(0xd12f), Some(Opcode::Draw { x: 1, y: 2, n: 0xf }));
+        assert_eq!(Opcode::decode(0xf301), Some(Opcode::SelectPlanes { n: 3 }));
+        assert_eq!(Opcode::decode(0xf000), Some(Opcode::SetIndexLong));
+        assert_eq!(Opcode::decode(0xf000).unwrap().width(), 4);
+        assert_eq!(Opcode::decode(0xf265), Some(Opcode::Load { x: 2 }));
+    }
+
+    #[test]
+    fn unsupported_test() {
+        for opcode in [0x0123, 0x01e0, 0x5121, 0x8128, 0x9121, 0xe1ff, 0xf100, 0xf102, 0xf1ff] {
+            assert_eq!(Opcode::decode(opcode), None, "{:#06x} should not decode", opcode);
+        }
+    }
+
+    #[test]
+    fn instruction_adapter_test() {
+        let instruction = Instruction::new_long(0xf0, 0x00, 0x12, 0x34);
+        assert_eq!(instruction.opcode(), Some(Opcode::SetIndexLong));
+        assert_eq!(instruction.extension(), 0x1234);
+        assert_eq!(chip8_traits::Instruction::nnnn(&instruction).count16(), 0x1234);
+        assert_eq!(chip8_traits::Instruction::width(&instruction), 4);
+
+        let instruction = Instruction::new(0xd1, 0x2f);
+        assert_eq!(chip8_traits::Instruction::w(&instruction).count8(), 0xd);
+        assert_eq!(chip8_traits::Instruction::x(&instruction).count8(), 0x1);
+        assert_eq!(chip8_traits::Instruction::y(&instruction).count8(), 0x2);
+        assert_eq!(chip8_traits::Instruction::n(&instruction).count8(), 0xf);
+        assert_eq!(chip8_traits::Instruction::nn(&instruction).count8(), 0x2f);
+        assert_eq!(chip8_traits::Instruction::nnn(&instruction).count16(), 0x12f);
+        assert_eq!(instruction.to_string(), "D12F");
+    }
+}
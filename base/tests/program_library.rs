@@ -0,0 +1,75 @@
+#[cfg(test)]
+mod program_library_tests {
+    use chip8_base::{ProgramLibrary, Quirks};
+
+    const PROGRAMS_JSON: &str = include_str!("../../programs/programs.json");
+
+    #[test]
+    fn parse_programs_test() {
+        let library = ProgramLibrary::parse(PROGRAMS_JSON).expect("programs.json should parse");
+
+        assert_eq!(library.len(), 92);
+
+        let entry = library.find_by_title("AIRPLANE").expect("AIRPLANE should be listed");
+        assert_eq!(entry.file, "Airplane.ch8");
+        assert!(!entry.description.is_empty());
+    }
+
+    #[test]
+    fn find_by_file_test() {
+        let library = ProgramLibrary::parse(PROGRAMS_JSON).expect("programs.json should parse");
+
+        let entry = library.find_by_file("programs/Astro Dodge [Revival Studios, 2008].ch8").expect("Astro Dodge should be listed");
+        assert_eq!(entry.title, "ASTRO DODGE");
+
+        assert!(library.find_by_file("programs/Not A Program.ch8").is_none());
+    }
+
+    #[test]
+    fn quirks_test() {
+        let library = ProgramLibrary::parse(PROGRAMS_JSON).expect("programs.json should parse");
+
+        let quirks = library.find_by_title("ASTRO DODGE").expect("ASTRO DODGE should be listed").quirks();
+        assert!(quirks.load_store);
+        assert!(!quirks.shift);
+
+        let quirks = library.find_by_title("BLINKY").expect("BLINKY should be listed").quirks();
+        assert!(quirks.load_store);
+        assert!(quirks.shift);
+
+        // Listing a program without quirks runs it the same as an unlisted one
+        let quirks = library.find_by_title("AIRPLANE").expect("AIRPLANE should be listed").quirks();
+        assert_eq!(quirks, Quirks::default());
+    }
+
+    #[test]
+    fn quirk_keys_test() {
+        let json = r#"[{
+            "title": "EVERYTHING",
+            "file": "Everything.ch8",
+            "quirks": {
+                "shift": true, "loadStore": false, "jump": true, "vfReset": true, "clipping": false,
+                "displayWait": true, "addIndexOverflow": true, "collisionRows": true, "keyRelease": true
+            }
+        }]"#;
+        let library = ProgramLibrary::parse(json).unwrap();
+
+        let quirks = library.find_by_title("EVERYTHING").unwrap().quirks();
+        assert_eq!(quirks, Quirks {
+            shift: true,
+            load_store: false,
+            jump: true,
+            vf_reset: true,
+            clipping: false,
+            display_wait: true,
+            add_index_overflow: true,
+            collision_rows: true,
+            key_release: true,
+        });
+    }
+
+    #[test]
+    fn parse_error_test() {
+        assert!(ProgramLibrary::parse("{ \"title\": \"not a list\" }").is_err());
+    }
+}
@@ -0,0 +1,66 @@
+mod common;
+
+#[cfg(test)]
+mod random_tests {
+    use chip8_base::{Interpreter, SeededRandom};
+    use chip8_traits::Random;
+
+    use crate::common::{MockKeypad, MockRenderer, idle_keypad, new_interpreter_with};
+
+    /// Fills V0 to VF with random numbers, over and over
+    fn program() -> Vec<u8> {
+        let mut program: Vec<u8> = (0..16).flat_map(|x| [0xc0 | x, 0xff]).collect();
+        program.extend_from_slice(&[0x12, 0x00]); // jump to the start
+        program
+    }
+
+    fn new_interpreter(seed: u64) -> Interpreter<MockRenderer, MockKeypad, SeededRandom> {
+        new_interpreter_with(idle_keypad(), SeededRandom::new(seed), &program())
+    }
+
+    fn values(random: &mut SeededRandom, count: usize) -> Vec<u8> {
+        (0..count).map(|_| random.value()).collect()
+    }
+
+    #[test]
+    fn seed_test() {
+        assert_eq!(values(&mut SeededRandom::new(1234), 32), values(&mut SeededRandom::new(1234), 32));
+        assert_ne!(values(&mut SeededRandom::new(1234), 32), values(&mut SeededRandom::new(1235), 32));
+        // Seed 0 isn't a fixed point
+        assert!(values(&mut SeededRandom::new(0), 32).iter().any(|value| *value != 0));
+    }
+
+    #[test]
+    fn state_test() {
+        let mut random = SeededRandom::new(99);
+        values(&mut random, 5);
+        let state = random.state();
+        let expected = values(&mut random, 16);
+
+        let mut restored = SeededRandom::new(1);
+        restored.set_state(&state);
+        assert_eq!(values(&mut restored, 16), expected);
+
+        // A state from some other generator is ignored
+        let state = restored.state();
+        restored.set_state(&[1, 2, 3]);
+        assert_eq!(restored.state(), state);
+    }
+
+    #[test]
+    fn interpreter_test() {
+        let mut first = new_interpreter(7);
+        chip8_traits::Interpreter::update_frame(&mut first).unwrap();
+        let mut second = new_interpreter(7);
+        chip8_traits::Interpreter::update_frame(&mut second).unwrap();
+        assert_eq!(first.variable_registers().get_all(), second.variable_registers().get_all());
+
+        // Loading a save state carries on from the same point in the sequence
+        let mut other = new_interpreter(8);
+        other.load_state(&first.save_state()).unwrap();
+        chip8_traits::Interpreter::update_frame(&mut first).unwrap();
+        chip8_traits::Interpreter::update_frame(&mut other).unwrap();
+        assert_eq!(other.variable_registers().get_all(), first.variable_registers().get_all());
+        assert_eq!(other.save_state(), first.save_state());
+    }
+}
@@ -0,0 +1,94 @@
+mod common;
+
+#[cfg(test)]
+mod rewind_tests {
+    use chip8_base::RewindBuffer;
+
+    use crate::common::TestInterpreter;
+
+    /// Counts frames in V1 by waiting on the delay timer, storing the count at 0x300
+    const PROGRAM: [u8; 14] = [
+        0x71, 0x01, // V1 += 1
+        0xa3, 0x00, // I = 0x300
+        0xf1, 0x55, // Memory[I..I + 1] = V0..V1
+        0x60, 0x01, // V0 = 1
+        0xf0, 0x15, // delay timer = V0
+        0xf0, 0x07, // V0 = delay timer
+        0x30, 0x00, // skip if V0 == 0
+    ];
+
+    fn new_interpreter() -> TestInterpreter {
+        let mut program = PROGRAM.to_vec();
+        program.extend_from_slice(&[0x12, 0x0a, 0x12, 0x00]); // jump back to the timer check, or to the start once it runs out
+        crate::common::new_interpreter(&program)
+    }
+
+    fn frame_count(interpreter: &TestInterpreter) -> u8 {
+        chip8_traits::Interpreter::dump_memory(interpreter)[0x301]
+    }
+
+    #[test]
+    fn buffer_test() {
+        let mut buffer = RewindBuffer::new(3);
+        for value in 0..5u8 {
+            buffer.push(value as u64 * 10, vec![value, 0, value]);
+        }
+
+        assert_eq!(buffer.len(), 3);
+        assert_eq!(buffer.get(0), Some((20, vec![2, 0, 2])));
+        assert_eq!(buffer.get(2), Some((40, vec![4, 0, 4])));
+        assert_eq!(buffer.find(35), Some(1));
+        assert_eq!(buffer.find(5), None);
+
+        buffer.truncate(1);
+        buffer.push(50, vec![5, 5, 5]);
+        assert_eq!(buffer.get(1), Some((50, vec![5, 5, 5])));
+    }
+
+    #[test]
+    fn rewind_test() {
+        let mut interpreter = new_interpreter();
+        interpreter.set_rewind_frames(120);
+
+        for _ in 0..200 {
+            let _ = chip8_traits::Interpreter::update_frame(&mut interpreter);
+        }
+        let count = frame_count(&interpreter);
+        assert_eq!(interpreter.rewind_len(), 120);
+
+        let saved = interpreter.save_state();
+
+        assert!(interpreter.rewind(100));
+        assert!(frame_count(&interpreter) < count);
+
+        for _ in 0..100 {
+            let _ = chip8_traits::Interpreter::update_frame(&mut interpreter);
+        }
+        assert_eq!(interpreter.save_state(), saved);
+
+        assert!(interpreter.rewind(1000));
+        assert!(!interpreter.rewind(0));
+    }
+
+    #[test]
+    fn reverse_step_test() {
+        let mut interpreter = new_interpreter();
+        interpreter.set_rewind_frames(10);
+        assert!(!interpreter.reverse_step());
+
+        for _ in 0..3 {
+            let _ = chip8_traits::Interpreter::update_frame(&mut interpreter);
+        }
+
+        let mut states = vec![];
+        for _ in 0..20 {
+            states.push(interpreter.save_state());
+            let _ = chip8_traits::Interpreter::update(&mut interpreter);
+        }
+
+        for state in states.iter().rev() {
+            assert!(interpreter.reverse_step());
+            assert_eq!(&interpreter.save_state(), state);
+        }
+    }
+}
@@ -0,0 +1,92 @@
+mod common;
+
+#[cfg(test)]
+mod save_state_tests {
+    use chip8_base::{KeyLatch, Quirks, SaveState, SaveStateError};
+
+    use crate::common::new_interpreter;
+
+    /// Draws a sprite, calls a subroutine and counts up in V1 forever
+    const PROGRAM: [u8; 16] = [
+        0x60, 0x08, // V0 = 8
+        0xf0, 0x29, // I = font character 8
+        0xd0, 0x05, // display (V0, V0)
+        0x22, 0x0c, // call 0x20c
+        0x71, 0x01, // V1 += 1
+        0x12, 0x08, // jump 0x208
+        0x12, 0x0c, // jump 0x20c, never returning
+        0x00, 0x00,
+    ];
+
+    #[test]
+    fn round_trip_test() {
+        let mut interpreter = new_interpreter(&PROGRAM);
+        interpreter.set_quirks(Quirks::cosmac_vip());
+        for _ in 0..3 {
+            let _ = chip8_traits::Interpreter::update_frame(&mut interpreter);
+        }
+
+        let saved = interpreter.save_state();
+        let state = SaveState::from_bytes(&saved).expect("saved state should load");
+        assert_eq!(state.stack, vec![0x208]);
+        assert_eq!(state.quirks, Quirks::cosmac_vip());
+        assert!(state.screen.iter().any(|pixel| *pixel != 0));
+        assert_eq!(state.to_bytes(), saved);
+
+        let mut restored = new_interpreter(&[]);
+        restored.load_state(&saved).expect("saved state should load");
+
+        assert_eq!(restored.save_state(), saved);
+        assert_eq!(restored.quirks(), Quirks::cosmac_vip());
+        assert_eq!(chip8_traits::Interpreter::dump_memory(&restored), chip8_traits::Interpreter::dump_memory(&interpreter));
+    }
+
+    #[test]
+    fn invalid_state_test() {
+        let mut interpreter = new_interpreter(&PROGRAM);
+        let saved = interpreter.save_state();
+
+        assert_eq!(interpreter.load_state(b"not a state"), Err(SaveStateError::BadMagic));
+
+        let mut corrupted = saved.clone();
+        corrupted[100] ^= 0xff;
+        assert_eq!(interpreter.load_state(&corrupted), Err(SaveStateError::ChecksumMismatch));
+
+        let mut future = saved.clone();
+        future[4] = 0xff;
+        let checksum_start = future.len() - 4;
+        let checksum = chip8_base::save_state::crc32(&future[..checksum_start]);
+        future[checksum_start..].copy_from_slice(&checksum.to_le_bytes());
+        assert_eq!(interpreter.load_state(&future), Err(SaveStateError::UnsupportedVersion(0xff)));
+    }
+
+    #[test]
+    fn version_1_test() {
+        let mut interpreter = new_interpreter(&PROGRAM);
+        for _ in 0..3 {
+            let _ = chip8_traits::Interpreter::update_frame(&mut interpreter);
+        }
+        let saved = interpreter.save_state();
+
+        // Version 1 is version 2 without the key latch section, which is written last
+        let key_latch_start = saved.len() - 4 - (4 + 4 + 5);
+        assert_eq!(&saved[key_latch_start..key_latch_start + 4], b"KLAT");
+        let mut old = saved[..key_latch_start].to_vec();
+        old[4..6].copy_from_slice(&1u16.to_le_bytes());
+        let checksum = chip8_base::save_state::crc32(&old);
+        old.extend_from_slice(&checksum.to_le_bytes());
+
+        let state = SaveState::from_bytes(&old).expect("version 1 state should load");
+        assert_eq!(state.key_latch, KeyLatch::new());
+        assert_eq!(state.memory, SaveState::from_bytes(&saved).unwrap().memory);
+
+        let mut restored = new_interpreter(&[]);
+        restored.load_state(&old).expect("version 1 state should load");
+        assert_eq!(restored.save_state(), saved);
+    }
+
+    #[test]
+    fn crc32_test() {
+        assert_eq!(chip8_base::save_state::crc32(b"123456789"), 0xcbf4_3926);
+    }
+}
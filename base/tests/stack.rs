@@ -0,0 +1,92 @@
+mod common;
+
+#[cfg(test)]
+mod stack_tests {
+    use chip8_base::{CallFrame, Debugger, Error, Stack};
+    use chip8_base::stack::{MAX_RETURN_ADDRESS, SCHIP_DEPTH, VIP_DEPTH, VIP_STACK_ADDRESS};
+
+    use crate::common::{TestInterpreter, new_interpreter};
+
+    /// Calls itself forever
+    const RECURSE: [u8; 2] = [0x22, 0x00];
+
+    fn update(interpreter: &mut TestInterpreter, count: usize) -> Result<(), Error> {
+        for _ in 0..count {
+            chip8_traits::Interpreter::update(interpreter)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn depth_test() {
+        for depth in [VIP_DEPTH, SCHIP_DEPTH] {
+            let mut interpreter = new_interpreter(&RECURSE);
+            interpreter.set_stack_depth(Some(depth));
+
+            update(&mut interpreter, depth).unwrap();
+            let error = update(&mut interpreter, 1).unwrap_err();
+            assert!(matches!(error, Error::StackOverflow(_)));
+            assert_eq!(error.context().program_counter, 0x200);
+            assert_eq!(error.context().cycle, depth as u64 + 1);
+        }
+
+        let mut interpreter = new_interpreter(&RECURSE);
+        update(&mut interpreter, 1000).unwrap();
+        assert_eq!(interpreter.stack().len(), 1000);
+    }
+
+    #[test]
+    fn return_address_test() {
+        let mut stack = Stack::new();
+        chip8_traits::Stack::push(&mut stack, MAX_RETURN_ADDRESS).unwrap();
+        assert!(matches!(chip8_traits::Stack::push(&mut stack, MAX_RETURN_ADDRESS + 1), Err(Error::ProgramCounterOutOfRange(_))));
+        assert_eq!(stack.values(), [MAX_RETURN_ADDRESS]);
+    }
+
+    #[test]
+    fn in_memory_test() {
+        let program = [
+            0x22, 0x04, // call 0x204
+            0x12, 0x02, // jump to self
+            0x22, 0x08, // call 0x208
+            0x00, 0xee, // return
+            0x00, 0xee, // return
+        ];
+        let mut interpreter = new_interpreter(&program);
+        interpreter.set_stack_in_memory(true);
+
+        update(&mut interpreter, 2).unwrap();
+        let memory = chip8_traits::Interpreter::dump_memory(&interpreter);
+        assert_eq!(memory[VIP_STACK_ADDRESS - 2..VIP_STACK_ADDRESS], [0x02, 0x02]);
+        assert_eq!(memory[VIP_STACK_ADDRESS - 4..VIP_STACK_ADDRESS - 2], [0x02, 0x06]);
+        assert_eq!(interpreter.call_stack(), vec![0x202, 0x206]);
+
+        // A program that rewrites its return address returns there
+        chip8_traits::Memory::set(interpreter.memory_mut(), VIP_STACK_ADDRESS - 3, 0x08).unwrap();
+        assert_eq!(interpreter.call_stack(), vec![0x202, 0x208]);
+        update(&mut interpreter, 1).unwrap();
+        assert_eq!(interpreter.dump_program_counter(), 0x208);
+
+        update(&mut interpreter, 1).unwrap();
+        assert_eq!(interpreter.dump_program_counter(), 0x202);
+        assert!(interpreter.stack().is_empty());
+    }
+
+    #[test]
+    fn call_stack_test() {
+        let program = [
+            0x22, 0x04, // call 0x204
+            0x12, 0x02, // jump to self
+            0x22, 0x08, // call 0x208
+            0x00, 0xee, // return
+            0x12, 0x08, // jump to self
+        ];
+        let mut debugger = Debugger::new(new_interpreter(&program));
+        update(debugger.interpreter_mut(), 3).unwrap();
+
+        assert_eq!(debugger.call_stack(), vec![
+            CallFrame { call_address: 0x200, target: Some(0x204), return_address: 0x202 },
+            CallFrame { call_address: 0x204, target: Some(0x208), return_address: 0x206 },
+        ]);
+    }
+}
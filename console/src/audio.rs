@@ -0,0 +1,28 @@
+use std::io::{self, Write};
+
+/// Rings the terminal bell each time the buzzer starts
+///
+/// The console has no audio output of its own, so this is only a stand-in for the tone: it can't follow the sound
+/// timer's length or an XO-CHIP pattern. Off unless asked for with --bell.
+pub struct Bell {}
+
+impl Bell {
+    pub fn new() -> Bell {
+        Bell {}
+    }
+}
+
+impl chip8_traits::Audio for Bell {
+    fn set_playing(&mut self, playing: bool) -> Result<(), chip8_traits::Error> {
+        if playing {
+            print!("\x07");
+            io::stdout().flush().map_err(|error| chip8_traits::Error::audio(error.to_string()))?;
+        }
+
+        Ok(())
+    }
+
+    fn set_pattern(&mut self, _pattern: [u8; 16], _sample_rate: f64) -> Result<(), chip8_traits::Error> {
+        Ok(())
+    }
+}
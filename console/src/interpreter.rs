@@ -1,6 +1,8 @@
 use chip8_base::{DelayTimer, Font, Memory, ProgramCounter, ScreenMemory, SoundTimer, Stack};
 
-pub fn new() -> chip8_base::Interpreter<crate::renderer::Renderer, crate::keypad::Keypad, crate::random::Random> {
+pub type Interpreter<Keypad> = chip8_base::Interpreter<crate::renderer::Renderer, Keypad, crate::random::Random>;
+
+pub fn new<Keypad: chip8_traits::Keypad>(keypad: Keypad, seed: u64) -> Interpreter<Keypad> {
     chip8_base::Interpreter::new(
         Memory::new_chip8(),
 
@@ -14,11 +16,11 @@ pub fn new() -> chip8_base::Interpreter<crate::renderer::Renderer, crate::keypad
 
         SoundTimer::new(),
 
-        crate::keypad::Keypad::new(),
+        keypad,
 
         ProgramCounter::new(),
 
-        crate::random::Random::new(),
+        crate::random::Random::new(seed),
 
         Font::new(),
     )
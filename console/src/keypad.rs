@@ -1,19 +1,219 @@
+use std::{io::{self, Read}, sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}}, thread, time::{Duration, Instant}};
+
+use chip8_base::KeyMap;
+use chip8_traits::KeyEvent;
+use termion::{event::Key, input::TermRead};
+
+/// How long a key counts as held after the terminal last reported it, long enough to bridge the keyboard's repeat delay
+pub const DEFAULT_HOLD_DURATION: Duration = Duration::from_millis(200);
+
+/// Keys seen by the background thread
+#[derive(Default)]
+struct Keys {
+    /// When each hex key was last reported, None once it has been released
+    pressed_at: [Option<Instant>; 16],
+    /// Not yet taken by the interpreter
+    events: Vec<KeyEvent>,
+}
+
+impl Keys {
+    /// Release keys that haven't been reported for the hold duration
+    fn release_expired(&mut self, hold_duration: Duration) {
+        for (index, pressed_at) in self.pressed_at.iter_mut().enumerate() {
+            if pressed_at.is_some_and(|time| time.elapsed() >= hold_duration) {
+                *pressed_at = None;
+                self.events.push(KeyEvent::Released(index));
+            }
+        }
+    }
+
+    /// Hold the hex keys a terminal key is bound to, reporting those that weren't held already
+    fn press(&mut self, key_indexes: Vec<usize>) {
+        for index in key_indexes {
+            if self.pressed_at[index].is_none() {
+                self.events.push(KeyEvent::Pressed(index));
+            }
+            self.pressed_at[index] = Some(Instant::now());
+        }
+    }
+}
+
+/// Reads the terminal on a background thread
+///
+/// Terminals only report presses, repeating them while a key is held, so a key is released once it
+/// hasn't been reported for the hold duration
 pub struct Keypad {
+    keys: Arc<Mutex<Keys>>,
+    hold_duration: Duration,
+    /// Set when Esc or Ctrl-C is pressed, raw mode stops the terminal turning Ctrl-C into a signal
+    quit: Arc<AtomicBool>,
 }
 
 impl Keypad {
-    pub fn new() -> Keypad {
+    /// Read keys bound in `key_map`, Esc and Ctrl-C always quit
+    pub fn new(key_map: KeyMap, hold_duration: Duration) -> Keypad {
+        let keypad = Keypad::unread(hold_duration);
+
+        let keys = keypad.keys.clone();
+        let quit = keypad.quit.clone();
+        thread::spawn(move || read_keys(io::stdin(), &quit, |name| press(&keys, &key_map, hold_duration, &name)));
+
+        keypad
+    }
+
+    /// Nothing reading keys into it yet
+    fn unread(hold_duration: Duration) -> Keypad {
         Keypad {
+            keys: Arc::new(Mutex::new(Keys::default())),
+            hold_duration,
+            quit: Arc::new(AtomicBool::new(false)),
         }
     }
+
+    /// Shared flag for the player asking to quit
+    pub fn quit_flag(&self) -> Arc<AtomicBool> {
+        self.quit.clone()
+    }
+}
+
+/// Read only Esc and Ctrl-C from the terminal, for when nothing takes the other keys, returning the flag they set
+pub fn quit_flag() -> Arc<AtomicBool> {
+    read_quit(io::stdin())
+}
+
+fn read_quit<Input: Read + Send + 'static>(input: Input) -> Arc<AtomicBool> {
+    let quit = Arc::new(AtomicBool::new(false));
+
+    let thread_quit = quit.clone();
+    thread::spawn(move || read_keys(input, &thread_quit, |_| ()));
+
+    quit
+}
+
+/// Pass the name of each key typed on `input`, as a terminal in raw mode sends them, to `press` until it ends or fails
+///
+/// Esc and Ctrl-C set `quit` instead
+fn read_keys<Input: Read>(input: Input, quit: &AtomicBool, mut press: impl FnMut(String)) {
+    for key in input.keys() {
+        let name = match key {
+            Ok(Key::Esc) | Ok(Key::Ctrl('c')) => {
+                quit.store(true, Ordering::Relaxed);
+                continue;
+            },
+            Ok(key) => match key_name(key) {
+                Some(name) => name,
+                None => continue,
+            },
+            Err(_) => break,
+        };
+
+        press(name);
+    }
+}
+
+/// Hold the hex keys bound to the terminal key `name`
+fn press(keys: &Mutex<Keys>, key_map: &KeyMap, hold_duration: Duration, name: &str) {
+    let mut keys = keys.lock().unwrap();
+    keys.release_expired(hold_duration);
+    keys.press(key_map.key_indexes(name));
+}
+
+/// The key's name in a key map
+fn key_name(key: Key) -> Option<String> {
+    let name = match key {
+        Key::Char(' ') => "Space".to_string(),
+        Key::Char(character) => character.to_string(),
+        Key::Up => "ArrowUp".to_string(),
+        Key::Down => "ArrowDown".to_string(),
+        Key::Left => "ArrowLeft".to_string(),
+        Key::Right => "ArrowRight".to_string(),
+        _ => return None,
+    };
+
+    Some(name)
 }
 
 impl chip8_traits::Keypad for Keypad {
     fn state(&self) -> [bool; 16] {
-        [false; 16]
+        let keys = self.keys.lock().unwrap();
+        let mut state = [false; 16];
+        for (held, pressed_at) in state.iter_mut().zip(keys.pressed_at.iter()) {
+            *held = pressed_at.is_some_and(|time| time.elapsed() < self.hold_duration);
+        }
+
+        state
     }
 
-    fn key_state(&self, _key_index: usize) -> bool {
-        return false;
+    fn key_state(&self, key_index: usize) -> bool {
+        self.state().get(key_index).copied().unwrap_or(false)
     }
-}
\ No newline at end of file
+
+    fn take_events(&mut self) -> Vec<KeyEvent> {
+        let mut keys = self.keys.lock().unwrap();
+        keys.release_expired(self.hold_duration);
+
+        keys.events.drain(..).collect()
+    }
+}
+
+#[cfg(test)]
+mod keypad_tests {
+    use std::{sync::atomic::Ordering, thread::sleep, time::{Duration, Instant}};
+
+    use chip8_base::KeyMap;
+    use chip8_traits::{KeyEvent, Keypad as _};
+
+    use super::{Keypad, press, read_keys, read_quit};
+
+    /// A keypad that has read everything in `input`
+    fn keypad_reading(input: &[u8], key_map: &KeyMap, hold_duration: Duration) -> Keypad {
+        let keypad = Keypad::unread(hold_duration);
+        read_keys(input, &keypad.quit, |name| press(&keypad.keys, key_map, hold_duration, &name));
+
+        keypad
+    }
+
+    #[test]
+    fn raw_mode_test() {
+        let key_map = KeyMap::parse("[keys]\n5 = [\"w\", \"ArrowUp\"]").unwrap();
+        // x, 1, then the escape sequence for arrow up followed by w, both bound to 5
+        let mut keypad = keypad_reading(b"x1\x1b[Aw", &key_map, Duration::from_secs(60));
+
+        assert_eq!(keypad.take_events(), vec![KeyEvent::Pressed(0x0), KeyEvent::Pressed(0x1), KeyEvent::Pressed(0x5)]);
+        assert!(keypad.key_state(0x0) && keypad.key_state(0x1) && keypad.key_state(0x5));
+        assert!(!keypad.key_state(0x2));
+        assert!(!keypad.quit_flag().load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn hold_test() {
+        let mut keypad = keypad_reading(b"qqq", &KeyMap::new(), Duration::from_millis(50));
+        assert!(keypad.key_state(0x4));
+        // Repeats while held aren't new presses
+        assert_eq!(keypad.take_events(), vec![KeyEvent::Pressed(0x4)]);
+
+        sleep(Duration::from_millis(60));
+        assert!(!keypad.key_state(0x4));
+        assert_eq!(keypad.take_events(), vec![KeyEvent::Released(0x4)]);
+        assert_eq!(keypad.take_events(), vec![]);
+    }
+
+    #[test]
+    fn quit_test() {
+        for input in [&b"\x1b"[..], &b"\x03"[..]] {
+            let mut keypad = keypad_reading(input, &KeyMap::new(), Duration::from_secs(60));
+            assert!(keypad.quit_flag().load(Ordering::Relaxed));
+            assert_eq!(keypad.take_events(), vec![]);
+        }
+    }
+    #[test]
+    fn quit_only_test() {
+        let quit = read_quit(&b"wq\x1b"[..]);
+
+        let start = Instant::now();
+        while !quit.load(Ordering::Relaxed) {
+            assert!(start.elapsed() < Duration::from_secs(5), "Esc should set the quit flag");
+            sleep(Duration::from_millis(1));
+        }
+    }
+}
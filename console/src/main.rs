@@ -1,19 +1,40 @@
-use std::{env, time::Duration};
+use std::{env, fs, io, path::Path, sync::atomic::{AtomicBool, Ordering}, time::Duration};
 
+use chip8_base::{KeyMap, Movie, MoviePlayer, MovieRecorder, ProgramLibrary, program_library::ProgramEntry};
 use chip8_traits::Interpreter;
+use termion::raw::IntoRawMode;
 
 const DEFAULT_PROGRAM_START: usize = 0x200;
-const MAIN_LOOP_FREQUENCY: Duration = Duration::from_millis(1);
+const PROGRAM_LIBRARY_FILE_NAME: &str = "programs.json";
+const KEY_MAP_FILE_NAME: &str = "keymap.toml";
 
+mod audio;
 mod renderer;
 mod keypad;
 mod interpreter;
 mod random;
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().collect();
 
-    let mut interpreter = interpreter::new();
+    // --gdb <address> waits for a debugger to attach instead of running straight away
+    let gdb_address = take_option(&mut args, "--gdb");
+    // --disassemble prints a listing of the program and --octo prints it as Octo source, instead of running it
+    let disassemble = take_flag(&mut args, "--disassemble");
+    let octo = take_flag(&mut args, "--octo");
+    // --key-hold <milliseconds> sets how long a key stays down after the terminal last reported it
+    let hold_duration = take_option(&mut args, "--key-hold")
+        .and_then(|milliseconds| milliseconds.parse().ok())
+        .map_or(keypad::DEFAULT_HOLD_DURATION, Duration::from_millis);
+    // --keys <file> reads key bindings from a TOML or JSON file instead of the keymap.toml alongside the program
+    let key_map_file_name = take_option(&mut args, "--keys");
+    // --record <file> saves a movie of the session, --replay <file> plays one back instead of running a program
+    let record_file_name = take_option(&mut args, "--record");
+    let replay_file_name = take_option(&mut args, "--replay");
+    // --seed <number> makes random numbers repeat between runs
+    let seed_option = take_option(&mut args, "--seed");
+    // --bell rings the terminal bell when the buzzer starts, otherwise the console is silent
+    let bell = take_flag(&mut args, "--bell");
 
     let load_file_name = {
         if args.len() > 1 {
@@ -22,13 +43,73 @@ fn main() {
             "programs/Puzzle.ch8"
         }
     };
+    if disassemble || octo {
+        match fs::read(load_file_name) {
+            Ok(program) => {
+                let disassembly = chip8_base::Disassembly::new(&program, DEFAULT_PROGRAM_START);
+                print!("{}", if octo { disassembly.octo() } else { disassembly.listing() });
+            },
+            Err(error) => println!("Error: while loading file {}: {}", load_file_name, error),
+        }
+        return;
+    }
+
+    if let Some(replay_file_name) = replay_file_name {
+        let quit = keypad::quit_flag();
+        match replay(&replay_file_name, &quit, bell) {
+            Ok(_) => println!("Finishing"),
+            Err(error) => println!("Error: while replaying {}: {}", replay_file_name, error),
+        }
+        return;
+    }
+
+    let seed = match seed_option.as_deref().map(str::parse) {
+        Some(Ok(seed)) => seed,
+        Some(Err(error)) => {
+            println!("Error: while reading seed: {}", error);
+            return;
+        },
+        None => random::entropy_seed(),
+    };
+
+    let program_entry = find_program_entry(load_file_name);
+    let key_map = match load_key_map(key_map_file_name.as_deref(), load_file_name) {
+        Ok(key_map) => key_map,
+        Err(error) => {
+            println!("Error: while loading key map: {}", error);
+            return;
+        }
+    };
+    let program_hash = KeyMap::program_hash(&fs::read(load_file_name).unwrap_or_default());
+    let key_map = key_map.for_program(program_hash, program_entry.as_ref().map(|entry| entry.title.as_str()));
+
+    let keypad = MovieRecorder::new(keypad::Keypad::new(key_map, hold_duration));
+    let quit = keypad.keypad().quit_flag();
+    let mut interpreter = interpreter::new(keypad, seed);
+    if bell {
+        interpreter.set_audio(Box::new(audio::Bell::new()));
+    }
+
+    if chip8_base::program_library::is_xo_chip_file(load_file_name) {
+        interpreter.set_memory_size(chip8_base::memory::XO_CHIP_SIZE);
+        interpreter.set_quirks(chip8_base::Quirks::xo_chip());
+    } else if let Some(entry) = &program_entry {
+        interpreter.set_quirks(entry.quirks());
+    }
+
     let result = interpreter.load_file(load_file_name, DEFAULT_PROGRAM_START);
     match result {
         Ok(_) => {
-            let result = interpreter.run(MAIN_LOOP_FREQUENCY);
+            let result = match gdb_address {
+                Some(address) => debug(interpreter, &address, load_file_name),
+                None => run_recording(&mut interpreter, &quit, record_file_name.as_deref()),
+            };
             match result {
                 Ok(_) => {
                     println!("Finishing");
+                    if seed_option.is_none() {
+                        println!("Random seed was {}, pass --seed {} to repeat it", seed, seed);
+                    }
                 }
                 Err(error) => {
                     println!("Error: while running {}", error);
@@ -40,3 +121,102 @@ fn main() {
         }
     }
 }
+
+/// Remove `flag` from the arguments, returning whether it was there
+fn take_flag(args: &mut Vec<String>, flag: &str) -> bool {
+    match args.iter().position(|arg| arg == flag) {
+        Some(index) => {
+            args.remove(index);
+            true
+        },
+        None => false,
+    }
+}
+
+/// Remove `option` and the value following it from the arguments, returning the value
+fn take_option(args: &mut Vec<String>, option: &str) -> Option<String> {
+    let index = args.iter().position(|arg| arg == option)?;
+    if index + 1 >= args.len() {
+        return None;
+    }
+    let value = args.remove(index + 1);
+    args.remove(index);
+
+    Some(value)
+}
+
+/// Run frames until the program exits or the player presses Esc or Ctrl-C, keeping pace with the wall clock
+///
+/// The terminal is in raw mode meanwhile so keys arrive as they're pressed instead of a line at a time,
+/// when stdout isn't a terminal the program runs without it
+fn run<Keypad: chip8_traits::Keypad>(interpreter: &mut interpreter::Interpreter<Keypad>, quit: &AtomicBool) -> Result<(), String> {
+    let _raw_terminal = io::stdout().into_raw_mode().ok();
+
+    interpreter.run(&mut || quit.load(Ordering::Relaxed)).map_err(|error| error.to_string())
+}
+
+/// Run the program, saving a movie of the session to `movie_file_name` if there is one, even when it fails
+fn run_recording(
+    interpreter: &mut interpreter::Interpreter<MovieRecorder<keypad::Keypad>>,
+    quit: &AtomicBool,
+    movie_file_name: Option<&str>
+) -> Result<(), String> {
+    let start_state = interpreter.save_state();
+    let result = run(interpreter, quit);
+
+    if let Some(movie_file_name) = movie_file_name {
+        let movie = interpreter.keypad().movie(start_state, interpreter.instructions_per_frame());
+        fs::write(movie_file_name, movie.to_bytes()).map_err(|error| error.to_string())?;
+    }
+
+    result
+}
+
+/// Play a movie back from the state it was recorded from
+fn replay(movie_file_name: &str, quit: &AtomicBool, bell: bool) -> Result<(), String> {
+    let bytes = fs::read(movie_file_name).map_err(|error| error.to_string())?;
+    let movie = Movie::from_bytes(&bytes).map_err(|error| error.to_string())?;
+
+    // The movie's start state replaces the seed
+    let mut interpreter = interpreter::new(MoviePlayer::new(&movie), 0);
+    if bell {
+        interpreter.set_audio(Box::new(audio::Bell::new()));
+    }
+    interpreter.start_replay(&movie).map_err(|error| error.to_string())?;
+
+    run(&mut interpreter, quit)
+}
+
+/// Serve a GDB remote debugger on `address` until it detaches
+///
+/// Symbols the assembler wrote alongside the program, if any, add its `:breakpoint`s
+fn debug<Keypad: chip8_traits::Keypad>(interpreter: interpreter::Interpreter<Keypad>, address: &str, load_file_name: &str) -> Result<(), String> {
+    println!("Waiting for a debugger on {}", address);
+
+    let mut debugger = chip8_base::Debugger::new(interpreter);
+    if let Ok(text) = fs::read_to_string(Path::new(load_file_name).with_extension("sym")) {
+        debugger.set_symbols(chip8_base::SymbolMap::parse(&text)?);
+    }
+    chip8_base::gdb::serve(&mut debugger, address).map_err(|error| error.to_string())
+}
+
+/// Look up the program in the catalog kept alongside it, if there is one
+fn find_program_entry(load_file_name: &str) -> Option<ProgramEntry> {
+    let library_file_name = Path::new(load_file_name).with_file_name(PROGRAM_LIBRARY_FILE_NAME);
+    let library = ProgramLibrary::load_file(library_file_name.to_str()?).ok()?;
+
+    library.find_by_file(load_file_name).cloned()
+}
+
+/// The key map given, or the one kept alongside the program, or QWERTY when there is neither
+fn load_key_map(key_map_file_name: Option<&str>, load_file_name: &str) -> Result<KeyMap, chip8_base::KeyMapError> {
+    if let Some(key_map_file_name) = key_map_file_name {
+        return KeyMap::load_file(key_map_file_name);
+    }
+
+    let key_map_file_name = Path::new(load_file_name).with_file_name(KEY_MAP_FILE_NAME);
+    if !key_map_file_name.exists() {
+        return Ok(KeyMap::new());
+    }
+    KeyMap::load_file(&key_map_file_name.to_string_lossy())
+}
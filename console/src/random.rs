@@ -1,19 +1,8 @@
 use nanorand::Rng;
 
-pub struct Random {
-    internal: nanorand::WyRand
-}
+pub type Random = chip8_base::SeededRandom;
 
-impl Random {
-    pub fn new() -> Random {
-        Random {
-            internal: nanorand::WyRand::new()
-        }
-    }
+/// A seed from the system's entropy, for when none is given
+pub fn entropy_seed() -> u64 {
+    nanorand::WyRand::new().generate::<u64>()
 }
-
-impl chip8_traits::Random for Random {
-    fn value(&mut self) -> u8 {
-        self.internal.generate::<u8>()
-    }
-}
\ No newline at end of file
@@ -1,27 +1,34 @@
 use std::slice;
 
+/// Characters for each combination of the two bitplanes
+const PIXEL_CHARACTERS: [&str; 4] = [" ", "🁢", "░", "▓"];
+
 pub struct Renderer {
+    /// Dimensions of the last render, a resolution change leaves stale characters behind unless cleared
+    dimensions: (usize, usize),
 }
 
 impl Renderer {
     pub fn new() -> Renderer {
         Renderer {
+            dimensions: (0, 0),
         }
     }
 }
 
 impl chip8_traits::Renderer for Renderer {
-    fn render(&mut self, memory: slice::Iter<Vec<bool>>) -> Result<(), &'static str> {
+    fn render(&mut self, memory: slice::Iter<Vec<u8>>) -> Result<(), chip8_traits::Error> {
         // print!("{}[2J", 27 as char);
         // print!("{esc}[2J{esc}[1;1H", esc = 27 as char); // TODO: change to just move, we're overwriting everyone anyway
+        let dimensions = (memory.as_slice().first().map_or(0, |row| row.len()), memory.len());
+        if dimensions != self.dimensions {
+            print!("{esc}[2J", esc = 27 as char);
+            self.dimensions = dimensions;
+        }
         print!("\x33{esc}[1;1H", esc = 27 as char);
         for row in memory {
             for value in row.iter() {
-                if *value {
-                    print!("🁢");
-                } else {
-                    print!(" ");
-                }
+                print!("{}", PIXEL_CHARACTERS[(*value & 0x03) as usize]);
             }
             print!("\n\r");
         }
@@ -0,0 +1,11 @@
+pub trait Audio {
+    /// Start or stop the tone, only called when the sound timer turns on or off
+    fn set_playing(&mut self, playing: bool) -> Result<(), crate::Error>;
+    /// XO-CHIP audio pattern, 128 1-bit samples looped at `sample_rate` bits per second
+    fn set_pattern(&mut self, pattern: [u8; 16], sample_rate: f64) -> Result<(), crate::Error>;
+
+    /// Called once per 60 Hz frame, after any changes for that frame
+    fn update_frame(&mut self) -> Result<(), crate::Error> {
+        Ok(())
+    }
+}
@@ -0,0 +1,100 @@
+use std::fmt;
+
+/// Where the interpreter was when an error happened
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ErrorContext {
+    /// Address of the instruction being run
+    pub program_counter: usize,
+    /// The raw opcode at that address
+    pub opcode: u16,
+    /// Instructions executed since the program was loaded
+    pub cycle: u64,
+}
+
+/// Anything that stops the interpreter
+///
+/// Errors raised without knowing where the interpreter was, such as by a renderer, start with an empty context that the
+/// interpreter fills in with `with_context`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    UnsupportedOpcode(ErrorContext),
+    /// A call with the stack already full
+    StackOverflow(ErrorContext),
+    /// A return with nothing on the stack
+    StackUnderflow(ErrorContext),
+    MemoryOutOfRange { address: usize, context: ErrorContext },
+    /// A register past VF
+    RegisterOutOfRange { index: u8, context: ErrorContext },
+    /// The program counter points past the end of memory
+    ProgramCounterOutOfRange(ErrorContext),
+    Renderer { message: String, context: ErrorContext },
+    Audio { message: String, context: ErrorContext },
+}
+
+impl Error {
+    pub fn renderer(message: impl Into<String>) -> Error {
+        Error::Renderer {
+            message: message.into(),
+            context: ErrorContext::default(),
+        }
+    }
+
+    pub fn audio(message: impl Into<String>) -> Error {
+        Error::Audio {
+            message: message.into(),
+            context: ErrorContext::default(),
+        }
+    }
+
+    pub fn context(&self) -> &ErrorContext {
+        match self {
+            Error::UnsupportedOpcode(context)
+            | Error::StackOverflow(context)
+            | Error::StackUnderflow(context)
+            | Error::ProgramCounterOutOfRange(context) => context,
+            Error::MemoryOutOfRange { context, .. }
+            | Error::RegisterOutOfRange { context, .. }
+            | Error::Renderer { context, .. }
+            | Error::Audio { context, .. } => context,
+        }
+    }
+
+    fn context_mut(&mut self) -> &mut ErrorContext {
+        match self {
+            Error::UnsupportedOpcode(context)
+            | Error::StackOverflow(context)
+            | Error::StackUnderflow(context)
+            | Error::ProgramCounterOutOfRange(context) => context,
+            Error::MemoryOutOfRange { context, .. }
+            | Error::RegisterOutOfRange { context, .. }
+            | Error::Renderer { context, .. }
+            | Error::Audio { context, .. } => context,
+        }
+    }
+
+    /// Replace where the error happened
+    pub fn with_context(mut self, context: ErrorContext) -> Error {
+        *self.context_mut() = context;
+        self
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::UnsupportedOpcode(_) => write!(f, "Unsupported opcode")?,
+            Error::StackOverflow(_) => write!(f, "Stack overflow")?,
+            Error::StackUnderflow(_) => write!(f, "Return with an empty stack")?,
+            Error::MemoryOutOfRange { address, .. } => write!(f, "Memory address {:#06x} out of range", address)?,
+            Error::RegisterOutOfRange { index, .. } => write!(f, "Register V{:X} out of range", index)?,
+            Error::ProgramCounterOutOfRange(_) => write!(f, "Program counter out of range")?,
+            Error::Renderer { message, .. } => write!(f, "Renderer failed: {}", message)?,
+            Error::Audio { message, .. } => write!(f, "Audio failed: {}", message)?,
+        }
+
+        let context = self.context();
+        write!(f, " at {:#06x} (opcode {:04X}, cycle {})", context.program_counter, context.opcode, context.cycle)
+    }
+}
+
+impl std::error::Error for Error {}
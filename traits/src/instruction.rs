@@ -16,4 +16,8 @@ pub trait Instruction: Copy + fmt::Display {
     fn nn(&self) -> [bool; 8];
     // The second, third and fourth nibbles. A 12-bit immediate memory address
     fn nnn(&self) -> [bool; 12];
+    /// The 16-bit word following a double width instruction such as XO-CHIP's F000 NNNN, otherwise 0
+    fn nnnn(&self) -> [bool; 16];
+    /// Length of the instruction in bytes
+    fn width(&self) -> usize;
 }
\ No newline at end of file
@@ -1,23 +1,56 @@
-use std::{thread::sleep, time::Duration};
+use std::{thread::sleep, time::{Duration, Instant}};
+
+/// Timers count down and the screen is redrawn at 60 Hz
+pub const FRAMES_PER_SECOND: u32 = 60;
+pub const FRAME_DURATION: Duration = Duration::from_nanos(1_000_000_000 / FRAMES_PER_SECOND as u64);
+
+/// Call `frame` once per 60 Hz frame until it returns an output, keeping pace with the wall clock
+pub fn run_paced<Output, Error>(mut frame: impl FnMut() -> Result<Option<Output>, Error>) -> Result<Output, Error> {
+    let mut next_frame = Instant::now();
+
+    loop {
+        if let Some(output) = frame()? {
+            return Ok(output);
+        }
+
+        next_frame += FRAME_DURATION;
+        let now = Instant::now();
+        if next_frame > now {
+            sleep(next_frame - now);
+        } else if now - next_frame > FRAME_DURATION {
+            // Too far behind to catch up, for example after the process was suspended
+            next_frame = now;
+        }
+    }
+}
 
 pub trait Interpreter<T> {
     fn load(&mut self, program: Vec<u8>, start_position: usize);
     // TODO: deprecate load_file in favor of load
     fn load_file(&mut self, file_name: &str, start_position: usize) -> Result<(), std::io::Error>;
 
-    fn update(&mut self) -> Result<T, String>;
+    /// Execute a single instruction
+    fn update(&mut self) -> Result<T, crate::Error>;
+    /// Run a 60 Hz frame: a batch of instructions, then one timer tick and one render
+    fn update_frame(&mut self) -> Result<(), crate::Error>;
+
+    fn render(&mut self) -> Result<(), crate::Error>;
 
     fn clear_screen(&mut self);
 
     fn dump_memory(&self) -> Vec<u8>;
 
-    fn run(&mut self, frequency: Duration) -> Result<(), String> {
-        loop {
-            let result = self.update();
-            if let Err(error) = result {
-                return Err(error);
+    /// The program has finished, for example with SUPER-CHIP's 00FD
+    fn has_exited(&self) -> bool;
+
+    /// Run frames until the program exits or `stop` returns true, keeping pace with the wall clock
+    fn run(&mut self, stop: &mut dyn FnMut() -> bool) -> Result<(), crate::Error> {
+        run_paced(|| {
+            if self.has_exited() || stop() {
+                return Ok(Some(()));
             }
-            sleep(frequency);
-        }
+
+            self.update_frame().map(|_| None)
+        })
     }
 }
\ No newline at end of file
@@ -1,4 +1,19 @@
+/// A hex key going down or up, by key index
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyEvent {
+    Pressed(usize),
+    Released(usize),
+}
+
 pub trait Keypad {
     fn state(&self) -> [bool; 16];
     fn key_state(&self, key_index: usize) -> bool;
-}
\ No newline at end of file
+
+    /// Presses and releases since the last call, oldest first, taken once per frame
+    ///
+    /// Keypads that only know the current state can leave this empty, the interpreter then finds edges by comparing
+    /// `state` between frames and misses a press and release within the same frame
+    fn take_events(&mut self) -> Vec<KeyEvent> {
+        Vec::new()
+    }
+}
@@ -1,3 +1,7 @@
+pub mod audio;
+pub use self::audio::Audio;
+pub mod error;
+pub use self::error::{Error, ErrorContext};
 pub mod font;
 pub use self::font::Font;
 pub mod instruction;
@@ -5,7 +9,7 @@ pub use self::instruction::Instruction;
 pub mod interpreter;
 pub use self::interpreter::Interpreter;
 pub mod keypad;
-pub use self::keypad::Keypad;
+pub use self::keypad::{KeyEvent, Keypad};
 pub mod memory;
 pub use self::memory::Memory;
 pub mod program_counter;
@@ -15,7 +19,7 @@ pub use self::random::Random;
 pub mod renderer;
 pub use self::renderer::Renderer;
 pub mod screen_memory;
-pub use self::screen_memory::ScreenMemory;
+pub use self::screen_memory::{Collision, ScreenMemory};
 pub mod stack;
 pub use self::stack::Stack;
 pub mod timer;
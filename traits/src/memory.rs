@@ -1,11 +1,18 @@
-use std::slice::Iter;
-
 pub trait Memory {
     fn set_size(&mut self, size: usize);
 
-    fn set(&mut self, location: usize, value: u8);
-    fn get(&self, location: usize) -> u8;
-    fn get_iter(&self, location: usize) -> Iter<u8>;
+    /// Fails with `Error::MemoryOutOfRange` past the end of memory, unless the memory wraps or ignores the write
+    fn set(&mut self, location: usize, value: u8) -> Result<(), crate::Error>;
+    /// Fails with `Error::MemoryOutOfRange` past the end of memory, unless the memory wraps or reads zero
+    fn get(&self, location: usize) -> Result<u8, crate::Error>;
+    /// Like `get`, for the interpreter looking ahead at an instruction it isn't running, which isn't a program access
+    fn peek(&self, location: usize) -> Result<u8, crate::Error> {
+        self.get(location)
+    }
+    /// `length` bytes starting at `location`
+    fn get_range(&self, location: usize, length: usize) -> Result<Vec<u8>, crate::Error> {
+        (location..location + length).map(|location| self.get(location)).collect()
+    }
 
     fn dump(&self) -> Vec<u8>;
-}
\ No newline at end of file
+}
@@ -4,6 +4,7 @@ pub trait ProgramCounter {
     fn get_position(&self) -> usize;
     fn set_position(&mut self, new_position: usize);
 
-    fn skip(&mut self);
+    /// Move past the instruction at the current position, which is 4 bytes long for XO-CHIP's F000 NNNN and 2 otherwise
+    fn skip(&mut self, memory: &dyn crate::Memory) -> Result<(), crate::Error>;
     fn go_back(&mut self);
 }
\ No newline at end of file
@@ -1,3 +1,9 @@
 pub trait Random {
     fn value(&mut self) -> u8;
+
+    /// Internal state for save states, empty when the generator can't be restored
+    fn state(&self) -> Vec<u8> {
+        vec![]
+    }
+    fn set_state(&mut self, _state: &[u8]) {}
 }
\ No newline at end of file
@@ -2,5 +2,6 @@ use std::slice::Iter;
 
 pub trait Renderer {
     // TODO: think up way to do without mutable
-    fn render(&mut self, memory: Iter<Vec<bool>>) -> Result<(), &'static str> ;
+    /// Draw rows of pixels, each pixel a mask of the bitplanes it is set in (0 to 3)
+    fn render(&mut self, memory: Iter<Vec<u8>>) -> Result<(), crate::Error>;
 }
\ No newline at end of file
@@ -1,8 +1,42 @@
 use std::slice::{Iter};
 
+/// What drawing a sprite ran into
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Collision {
+    /// Sprite rows that turned at least one pixel off
+    pub rows: usize,
+    /// Sprite rows clipped off the bottom of the screen
+    pub clipped_rows: usize,
+}
+
+impl Collision {
+    /// Whether any pixel was turned off
+    pub fn any(&self) -> bool {
+        self.rows > 0
+    }
+}
+
 pub trait ScreenMemory {
+    /// Clear the selected planes
     fn clear(&mut self);
 
-    // TODO: more explicit than bool
-    fn display(&mut self, x: u8, y: u8, memory: Iter<u8>, count: u8) -> bool;
-}
\ No newline at end of file
+    /// Draw `count` rows of sprite data, clipping rows and columns past the edges or wrapping them around when `clipping` is false
+    ///
+    /// When several planes are selected, the sprite data for each plane follows the previous one
+    fn display(&mut self, x: u8, y: u8, memory: Iter<u8>, count: u8, clipping: bool) -> Collision;
+    /// Draw a 16x16 sprite made of two bytes per row
+    fn display_large(&mut self, x: u8, y: u8, memory: Iter<u8>, clipping: bool) -> Collision;
+
+    fn is_high_resolution(&self) -> bool;
+    /// Switch between the regular and double width and height resolutions, clearing the screen
+    fn set_high_resolution(&mut self, high_resolution: bool);
+
+    /// Bit mask of the planes drawn, cleared and scrolled by the other operations
+    fn selected_planes(&self) -> u8;
+    fn select_planes(&mut self, planes: u8);
+
+    fn scroll_down(&mut self, rows: usize);
+    fn scroll_up(&mut self, rows: usize);
+    fn scroll_left(&mut self, columns: usize);
+    fn scroll_right(&mut self, columns: usize);
+}
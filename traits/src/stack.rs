@@ -1,8 +1,10 @@
 pub trait Stack {
-    fn push(&mut self, value: usize);
+    /// Fails with `Error::StackOverflow` when the stack is full, or `Error::ProgramCounterOutOfRange` when `value` is
+    /// too large to be an address
+    fn push(&mut self, value: usize) -> Result<(), crate::Error>;
 
-    // TODO: stack underflow
-    fn pop(&mut self) -> Option<usize>;
+    /// Fails with `Error::StackUnderflow` when the stack is empty
+    fn pop(&mut self) -> Result<usize, crate::Error>;
 
     fn clear(&mut self);
 }
\ No newline at end of file
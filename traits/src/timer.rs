@@ -2,6 +2,6 @@ pub trait Timer {
     fn get(&self) -> u8;
     fn set(&mut self, value: u8);
 
-    fn update(&mut self) -> Result<(), String>;
+    fn update(&mut self) -> Result<(), crate::Error>;
 }
 
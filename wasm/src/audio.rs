@@ -0,0 +1,48 @@
+use std::{cell::{RefCell, RefMut}, rc::Rc};
+
+/// Buzzer state shared with the page, which plays it with the Web Audio API
+pub struct AudioState {
+    pub playing: bool,
+    pub pattern: [u8; 16],
+    pub sample_rate: f64,
+}
+
+impl AudioState {
+    pub fn new() -> AudioState {
+        AudioState {
+            playing: false,
+            pattern: chip8_base::sound_timer::DEFAULT_PATTERN,
+            sample_rate: chip8_base::sound_timer::DEFAULT_SAMPLE_RATE,
+        }
+    }
+}
+
+pub struct Audio {
+    state: Rc<RefCell<AudioState>>
+}
+
+impl Audio {
+    pub fn new(state: Rc<RefCell<AudioState>>) -> Audio {
+        Audio {
+            state
+        }
+    }
+
+    fn state(&self) -> Result<RefMut<'_, AudioState>, chip8_traits::Error> {
+        self.state.try_borrow_mut().map_err(|_| chip8_traits::Error::audio("Audio state is in use by the page"))
+    }
+}
+
+impl chip8_traits::Audio for Audio {
+    fn set_playing(&mut self, playing: bool) -> Result<(), chip8_traits::Error> {
+        self.state()?.playing = playing;
+        Ok(())
+    }
+
+    fn set_pattern(&mut self, pattern: [u8; 16], sample_rate: f64) -> Result<(), chip8_traits::Error> {
+        let mut state = self.state()?;
+        state.pattern = pattern;
+        state.sample_rate = sample_rate;
+        Ok(())
+    }
+}
@@ -3,17 +3,29 @@ use wasm_bindgen::prelude::*;
 use std::{borrow::{Borrow}, cell::{RefCell}, fmt, rc::Rc};
 use serde::{Serialize, Deserialize};
 
-use crate::{console_log_unsafe, renderer::fmt_rendered_memory, utility::{js_value_as_usize, set_panic_hook}};
+use crate::{renderer::fmt_rendered_memory, utility::{js_value_as_usize, set_panic_hook}};
 
 #[wasm_bindgen]
 pub struct Index {
-    rendered_memory: Rc<RefCell<Vec<Vec<bool>>>>,
+    rendered_memory: Rc<RefCell<Vec<Vec<u8>>>>,
     keypad_state: Rc<RefCell<[bool; 16]>>,
+    key_events: Rc<RefCell<Vec<chip8_traits::KeyEvent>>>,
+    audio_state: Rc<RefCell<crate::audio::AudioState>>,
 
-    interpreter: chip8_base::Interpreter<crate::renderer::Renderer, crate::keypad::Keypad, crate::random::Random>,
+    debugger: chip8_base::Debugger<crate::renderer::Renderer, crate::keypad::Keypad, crate::random::Random>,
+
+    program_library: chip8_base::ProgramLibrary,
+
+    key_map: chip8_base::KeyMap,
+    /// `key_map` with any override for the loaded program applied
+    program_key_map: chip8_base::KeyMap,
+    /// Identify the loaded program to the key map's overrides
+    program_hash: u32,
+    program_title: Option<String>,
 }
 
 const DEFAULT_PROGRAM_START: usize = 0x200;
+const REWIND_SECONDS: usize = 10;
 // const MAIN_LOOP_FREQUENCY: Duration = Duration::from_millis(1);
 
 #[wasm_bindgen]
@@ -25,31 +37,224 @@ impl Index {
         let renderer = crate::renderer::Renderer::new(Rc::clone(&rendered_memory));
 
         let keypad_state = Rc::new(RefCell::new([false; 16]));
-        let keypad = crate::keypad::Keypad::new(Rc::clone(&keypad_state));
+        let key_events = Rc::new(RefCell::new(vec![]));
+        let keypad = crate::keypad::Keypad::new(Rc::clone(&keypad_state), Rc::clone(&key_events));
         
-        let interpreter = crate::interpreter::new(renderer, keypad);
+        let mut interpreter = crate::interpreter::new(renderer, keypad);
+
+        let audio_state = Rc::new(RefCell::new(crate::audio::AudioState::new()));
+        interpreter.set_audio(Box::new(crate::audio::Audio::new(Rc::clone(&audio_state))));
+        interpreter.set_rewind_frames(REWIND_SECONDS * chip8_traits::interpreter::FRAMES_PER_SECOND as usize);
 
         Index {
             rendered_memory,
             keypad_state,
+            key_events,
+            audio_state,
+
+            debugger: chip8_base::Debugger::new(interpreter),
+
+            program_library: chip8_base::ProgramLibrary::new(),
+
+            key_map: chip8_base::KeyMap::new(),
+            program_key_map: chip8_base::KeyMap::new(),
+            program_hash: 0,
+            program_title: None,
+        }
+    }
+
+    fn interpreter(&mut self) -> &mut chip8_base::Interpreter<crate::renderer::Renderer, crate::keypad::Keypad, crate::random::Random> {
+        self.debugger.interpreter_mut()
+    }
+
+    pub fn set_program_library(&mut self, json: String) -> bool {
+        match chip8_base::ProgramLibrary::parse(&json) {
+            Ok(program_library) => {
+                self.program_library = program_library;
+                true
+            },
+            Err(error) => {
+                crate::console_log_unsafe!("Error: while setting program library: {}", error);
+                false
+            }
+        }
+    }
+
+    /// Make CXNN produce the same numbers on every run from here on
+    pub fn set_random_seed(&mut self, seed: u32) {
+        *self.interpreter().random_mut() = crate::random::Random::new(seed as u64);
+    }
 
-            interpreter,
+    /// Key bindings as TOML, or JSON, see `chip8_base::KeyMap`
+    pub fn set_key_map(&mut self, text: String) -> bool {
+        match chip8_base::KeyMap::parse(&text) {
+            Ok(key_map) => {
+                self.key_map = key_map;
+                self.program_key_map = self.key_map.for_program(self.program_hash, self.program_title.as_deref());
+                true
+            },
+            Err(error) => {
+                crate::console_log_unsafe!("Error: while setting key map: {}", error);
+                false
+            }
         }
     }
 
+    /// Load a program, running it with the quirks listed for it in the program library
+    pub fn load_program(&mut self, file: String, program: Vec<u8>) {
+        if chip8_base::program_library::is_xo_chip_file(&file) {
+            self.interpreter().set_memory_size(chip8_base::memory::XO_CHIP_SIZE);
+            self.interpreter().set_quirks(chip8_base::Quirks::xo_chip());
+        } else {
+            let quirks = match self.program_library.find_by_file(&file) {
+                Some(entry) => entry.quirks(),
+                None => chip8_base::Quirks::default()
+            };
+            self.interpreter().set_memory_size(chip8_base::memory::CHIP8_SIZE);
+            self.interpreter().set_quirks(quirks);
+        }
+
+        let title = self.program_library.find_by_file(&file).map(|entry| entry.title.clone());
+        self.load_titled(program, title);
+    }
+
     pub fn load(&mut self, program: Vec<u8>) {
+        self.load_titled(program, None);
+    }
+
+    fn load_titled(&mut self, program: Vec<u8>, title: Option<String>) {
+        self.program_hash = chip8_base::KeyMap::program_hash(&program);
+        self.program_title = title;
+        self.program_key_map = self.key_map.for_program(self.program_hash, self.program_title.as_deref());
+
         let program_length = program.len();
-        chip8_traits::Interpreter::load(&mut self.interpreter, program, DEFAULT_PROGRAM_START);
+        chip8_traits::Interpreter::load(self.interpreter(), program, DEFAULT_PROGRAM_START);
         crate::console_log_unsafe!("Loaded program {} bytes", program_length);
-        chip8_traits::Interpreter::clear_screen(&mut self.interpreter);
+        chip8_traits::Interpreter::clear_screen(self.interpreter());
+        self.debugger.reset_frame();
     }
 
+    /// Execute a single instruction, for stepping through a program
     pub fn update(&mut self) {
-        match chip8_traits::Interpreter::update(&mut self.interpreter) {
-            Ok(result) => {
-                // console_log_unsafe!("Result: {}", result.instruction_disassembly.to_string());
+        let reason = self.debugger.step_into();
+        self.log_stop(reason);
+        self.render();
+    }
+
+    /// Run a single instruction, running subroutine calls through to their return
+    pub fn step_over(&mut self) {
+        let reason = self.debugger.step_over();
+        self.log_stop(reason);
+        self.render();
+    }
+
+    /// Run until the current subroutine returns
+    pub fn step_out(&mut self) {
+        let reason = self.debugger.step_out();
+        self.log_stop(reason);
+        self.render();
+    }
+
+    /// Run one 60 Hz frame, returning true when a breakpoint or error stopped it so the player can pause
+    pub fn update_frame(&mut self) -> bool {
+        match self.debugger.run_frame() {
+            Some(reason) => {
+                self.log_stop(reason);
+                self.render();
+                true
+            },
+            None => false
+        }
+    }
+
+    fn log_stop(&self, reason: chip8_base::StopReason) {
+        match reason {
+            chip8_base::StopReason::Step | chip8_base::StopReason::Exited => {},
+            chip8_base::StopReason::Error(error) => crate::console_log_unsafe!("Error: while updating: {}", error),
+            reason => crate::console_log_unsafe!("Stopped: {:?}", reason),
+        }
+    }
+
+    /// Break at `address`, only when `condition` such as `V3 == 0x10` holds if it isn't empty
+    pub fn add_breakpoint(&mut self, address: usize, condition: String) -> bool {
+        let condition = {
+            if condition.trim().is_empty() {
+                None
+            } else {
+                match chip8_base::debugger::Condition::parse(&condition) {
+                    Ok(condition) => Some(condition),
+                    Err(error) => {
+                        crate::console_log_unsafe!("Error: while adding breakpoint: {}", error);
+                        return false;
+                    }
+                }
+            }
+        };
+        self.debugger.add_breakpoint(chip8_base::debugger::Breakpoint { address, condition });
+
+        true
+    }
+
+    pub fn remove_breakpoint(&mut self, address: usize) -> bool {
+        self.debugger.remove_breakpoint(address)
+    }
+
+    /// Break on any instruction matching a pattern such as `DXYN`
+    pub fn add_opcode_breakpoint(&mut self, pattern: String) -> bool {
+        match chip8_base::debugger::OpcodeBreakpoint::parse(&pattern) {
+            Ok(breakpoint) => {
+                self.debugger.add_opcode_breakpoint(breakpoint);
+                true
+            },
+            Err(error) => {
+                crate::console_log_unsafe!("Error: while adding breakpoint: {}", error);
+                false
+            }
+        }
+    }
+
+    pub fn clear_breakpoints(&mut self) {
+        self.debugger.clear_breakpoints();
+        self.debugger.clear_opcode_breakpoints();
+    }
+
+    pub fn save_state(&self) -> Vec<u8> {
+        self.debugger.interpreter().save_state()
+    }
+
+    pub fn load_state(&mut self, state: Vec<u8>) -> bool {
+        match self.interpreter().load_state(&state) {
+            Ok(_) => {
+                self.debugger.reset_frame();
+                self.render();
+                true
             },
-            Err(error) => crate::console_log_unsafe!("Error: while updating: {}", error)
+            Err(error) => {
+                crate::console_log_unsafe!("Error: while loading state: {}", error);
+                false
+            }
+        }
+    }
+
+    /// Go back `frames` frames, returning false when there is nothing to rewind
+    pub fn rewind(&mut self, frames: usize) -> bool {
+        let result = self.interpreter().rewind(frames);
+        self.debugger.reset_frame();
+        self.render();
+        result
+    }
+
+    /// Undo the last instruction
+    pub fn reverse_step(&mut self) -> bool {
+        let result = self.interpreter().reverse_step();
+        self.debugger.reset_frame();
+        self.render();
+        result
+    }
+
+    fn render(&mut self) {
+        if let Err(error) = chip8_traits::Interpreter::render(self.interpreter()) {
+            crate::console_log_unsafe!("Error: while rendering: {}", error);
         }
     }
 
@@ -57,18 +262,43 @@ impl Index {
         self.to_string()
     }
 
+    pub fn is_sound_playing(&self) -> bool {
+        (*self.audio_state).borrow().playing
+    }
+
+    /// 128 1-bit samples, most significant bit first
+    pub fn sound_pattern(&self) -> Vec<u8> {
+        (*self.audio_state).borrow().pattern.to_vec()
+    }
+
+    /// Bits of the sound pattern played per second
+    pub fn sound_sample_rate(&self) -> f64 {
+        (*self.audio_state).borrow().sample_rate
+    }
+
     fn set_key_state(&mut self, js_index: JsValue, state: bool) -> bool {
         match js_value_as_usize(js_index) {
-            Some(index) => {
-                let mut keypad_state = *(*self.keypad_state).borrow_mut();
-                keypad_state[index as usize] = state;
-
-                true
-            },
+            Some(index) => self.set_key_index_state(index, state),
             None => false
         }
     }
 
+    fn set_key_index_state(&mut self, index: usize, state: bool) -> bool {
+        if index >= 16 {
+            return false;
+        }
+
+        // Held keys repeat keydown, only changes are events
+        let mut keypad_state = (*self.keypad_state).borrow_mut();
+        if keypad_state[index] != state {
+            keypad_state[index] = state;
+            let event = if state { chip8_traits::KeyEvent::Pressed(index) } else { chip8_traits::KeyEvent::Released(index) };
+            (*self.key_events).borrow_mut().push(event);
+        }
+
+        true
+    }
+
     pub fn keydown(&mut self, js_index: JsValue) -> bool {
         self.set_key_state(js_index, true)
     }
@@ -77,20 +307,38 @@ impl Index {
         self.set_key_state(js_index, false)
     }
 
+    /// A keyboard key went down, by its `KeyboardEvent.key`, returning whether it is bound
+    pub fn key_name_down(&mut self, name: String) -> bool {
+        self.set_key_name_state(&name, true)
+    }
+
+    pub fn key_name_up(&mut self, name: String) -> bool {
+        self.set_key_name_state(&name, false)
+    }
+
+    fn set_key_name_state(&mut self, name: &str, state: bool) -> bool {
+        let indexes = self.program_key_map.key_indexes(name);
+        for index in indexes.iter() {
+            self.set_key_index_state(*index, state);
+        }
+
+        !indexes.is_empty()
+    }
+
     pub fn key_state(&self) -> Box<[JsValue]> {
         let keypad_state = *(*self.keypad_state).borrow();
         keypad_state.iter().map(|value| JsValue::from_bool(*value)).collect()
     }
 
     pub fn dump_memory(&self) -> String {
-        match String::from_utf8(self.interpreter.dump_memory()) {
+        match String::from_utf8(self.debugger.interpreter().dump_memory()) {
             Ok(result) => result.to_string(),
             Err(error) => error.to_string()
         }
     }
 
     pub fn dump_program_counter(&self) -> String {
-        self.interpreter.dump_program_counter().to_string()
+        self.debugger.interpreter().dump_program_counter().to_string()
     }
 }
 
@@ -112,10 +360,11 @@ pub struct InterpreterSnapshot {
 impl InterpreterSnapshot {
     #[wasm_bindgen(getter)]
     pub fn variable_register_values(&self) -> js_sys::Uint8Array {
-        return js_sys::Uint8Array::from(&self.variable_register_values[..]);
+        js_sys::Uint8Array::from(&self.variable_register_values[..])
     }
 
     #[wasm_bindgen(getter)]
+    #[allow(deprecated)]
     pub fn partial_disassemble(&self) -> JsValue {
         // TODO: remove unwrap and properly handle
         JsValue::from_serde(&self.partial_disassemble).unwrap()
@@ -147,7 +396,7 @@ impl Index {
             delay_timer_value, 
             sound_timer_value,
             partial_disassemble
-        } = self.interpreter.create_snapshot(chip8_base::interpreter::PartialDisassembleOptions{
+        } = self.interpreter().create_snapshot(chip8_base::interpreter::PartialDisassembleOptions{
                 count_before,
                 count_after,
                 fix_misalignment: true,
@@ -173,7 +422,7 @@ impl Index {
 impl fmt::Display for Index {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 
-        let memory: &RefCell<Vec<Vec<bool>>> = self.rendered_memory.borrow();
+        let memory: &RefCell<Vec<Vec<u8>>> = self.rendered_memory.borrow();
         fmt_rendered_memory(&(*memory).borrow(), f)
     }
 } 
\ No newline at end of file
@@ -18,7 +18,7 @@ pub fn new(renderer: crate::renderer::Renderer, keypad: crate::keypad::Keypad) -
 
         ProgramCounter::new(),
 
-        crate::random::Random::new(),
+        crate::random::Random::new(crate::random::entropy_seed()),
 
         Font::new(),
     )
@@ -1,20 +1,25 @@
 use std::{cell::RefCell, rc::Rc};
 
+use chip8_traits::KeyEvent;
+
 pub struct Keypad {
-    key_pressed: Rc<RefCell<[bool; 16]>>
+    key_pressed: Rc<RefCell<[bool; 16]>>,
+    /// Key changes from the page since the interpreter last took them
+    key_events: Rc<RefCell<Vec<KeyEvent>>>,
 }
 
 impl Keypad {
-    pub fn new(key_pressed: Rc<RefCell<[bool; 16]>>) -> Keypad {
+    pub fn new(key_pressed: Rc<RefCell<[bool; 16]>>, key_events: Rc<RefCell<Vec<KeyEvent>>>) -> Keypad {
         Keypad {
-            key_pressed
+            key_pressed,
+            key_events,
         }
     }
 }
 
 impl chip8_traits::Keypad for Keypad {
     fn state(&self) -> [bool; 16] {
-        return (self.key_pressed.borrow()).clone();
+        return *(self.key_pressed.borrow());
     }
 
     fn key_state(&self, key_index: usize) -> bool {
@@ -23,6 +28,10 @@ impl chip8_traits::Keypad for Keypad {
             return key_pressed[key_index];
         }
         
-        return false;
+        false
+    }
+
+    fn take_events(&mut self) -> Vec<KeyEvent> {
+        self.key_events.borrow_mut().drain(..).collect()
     }
-}
\ No newline at end of file
+}
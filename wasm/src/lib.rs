@@ -1,3 +1,4 @@
+mod audio;
 mod renderer;
 mod interpreter;
 mod random;
@@ -1,20 +1,9 @@
 use js_sys::Math::random;
 
-pub struct Random {
-}
+pub type Random = chip8_base::SeededRandom;
 
-impl Random {
-    pub fn new() -> Random {
-        Random {
-        }
-    }
+/// A seed from the browser's generator, for when none is given
+pub fn entropy_seed() -> u64 {
+    let half = || (random() * u32::MAX as f64) as u64;
+    (half() << 32) | half()
 }
-
-impl chip8_traits::Random for Random {
-    fn value(&mut self) -> u8 {
-        #[allow(unused_unsafe)] // Currently unsafe not properly recognized by analyzer
-        unsafe {
-            random().to_be_bytes()[3]
-        }
-    }
-}
\ No newline at end of file
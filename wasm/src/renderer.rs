@@ -1,11 +1,14 @@
 use std::{cell::RefCell, fmt, rc::Rc, slice};
 
+/// Characters for each combination of the two bitplanes
+const PIXEL_CHARACTERS: [char; 4] = [' ', '◼', '◻', '▣'];
+
 pub struct Renderer {
-    rendered_memory: Rc<RefCell<Vec<Vec<bool>>>>
+    rendered_memory: Rc<RefCell<Vec<Vec<u8>>>>
 }
 
 impl Renderer {
-    pub fn new(rendered_memory: Rc<RefCell<Vec<Vec<bool>>>>) -> Renderer {
+    pub fn new(rendered_memory: Rc<RefCell<Vec<Vec<u8>>>>) -> Renderer {
         Renderer {
             rendered_memory
         }
@@ -13,8 +16,10 @@ impl Renderer {
 }
 
 impl chip8_traits::Renderer for Renderer {
-    fn render(&mut self, memory: slice::Iter<Vec<bool>>) -> Result<(), &'static str> {
-        let mut rendered_contents = self.rendered_memory.borrow_mut();
+    fn render(&mut self, memory: slice::Iter<Vec<u8>>) -> Result<(), chip8_traits::Error> {
+        let mut rendered_contents = self.rendered_memory.try_borrow_mut()
+            .map_err(|_| chip8_traits::Error::renderer("Rendered memory is in use by the page"))?;
+        let row_count = memory.len();
         for (row_index, row) in memory.enumerate() {
             if row_index == rendered_contents.len() {
                 rendered_contents.push(vec![]);
@@ -22,15 +27,13 @@ impl chip8_traits::Renderer for Renderer {
 
             for (column_index, value) in row.iter().enumerate() {
                 if column_index == rendered_contents[row_index].len() {
-                    rendered_contents[row_index].push(false);
-                }
-                if *value {
-                    rendered_contents[row_index][column_index] = true;
-                } else {
-                    rendered_contents[row_index][column_index] = false;
+                    rendered_contents[row_index].push(0);
                 }
+                rendered_contents[row_index][column_index] = *value;
             }
+            rendered_contents[row_index].truncate(row.len());
         }
+        rendered_contents.truncate(row_count);
 
         Ok(())
     }
@@ -42,19 +45,12 @@ impl fmt::Display for Renderer {
     }
 }
 
-pub fn fmt_rendered_memory(rendered_memory: &Vec<Vec<bool>>, f: &mut fmt::Formatter) -> fmt::Result {
+pub fn fmt_rendered_memory(rendered_memory: &[Vec<u8>], f: &mut fmt::Formatter) -> fmt::Result {
     for row in rendered_memory.iter() {
         for &value in row {
-            let character = {
-                if value {
-                    '◼'
-                } else {
-                    ' '
-                }
-            };
-            write!(f, "{}", character)?;
+            write!(f, "{}", PIXEL_CHARACTERS[(value & 0x03) as usize])?;
         }
-        write!(f, "\n")?;
+        writeln!(f)?;
     }
 
     Ok(())
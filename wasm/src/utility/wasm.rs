@@ -17,9 +17,8 @@ macro_rules! console_log {
 #[macro_export]
 macro_rules! console_log_unsafe {
     ( $( $t:tt )* ) => {
-        // #[allow(unused_unsafe)] // Currently unsafe not properly recognized by analyzer
-        unsafe {
-            crate::console_log!($( $t )*);
+        {
+            $crate::console_log!($( $t )*);
         }
     }
 }
@@ -37,10 +36,5 @@ pub fn set_panic_hook() {
 }
 
 pub fn js_value_as_usize(jsvalue: JsValue) -> Option<usize> {
-    match jsvalue.as_f64() {
-        Some(index_float) => {
-            Some(index_float as usize)
-        },
-        None => None
-    }
+    jsvalue.as_f64().map(|index_float| index_float as usize)
 }
\ No newline at end of file
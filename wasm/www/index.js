@@ -1,5 +1,4 @@
 import { Index, InterpreterSnapshot } from "../pkg/chip8_wasm";
-import { mapKeyEventCodeToKeypadIndex } from "./utility";
 
 const pre = document.getElementById("chip8_render-canvas");
 
@@ -7,6 +6,7 @@ let index = null;
 let indexReady = false;
 let programsList = null;
 let programsListReady = false;
+let keyMap = null;
 let isPaused = false;
 
 const updateElementById = (id, valueOrFunction) => {
@@ -40,14 +40,68 @@ const updateSnapshot = () => {
     });
 }
 
-const renderLoop = () => {
-    index.update();
+const FRAME_DURATION = 1000 / 60;
+// Frames to run at most per animation frame, so a backgrounded tab doesn't fast forward when it returns
+const MAX_FRAMES_BEHIND = 4;
+let lastFrameTime = null;
+
+// Rate the pattern buffer is created at, the playback rate scales it to the program's pitch
+const AUDIO_BUFFER_SAMPLE_RATE = 4000;
+const AUDIO_VOLUME = 0.25;
+let audioContext = null;
+let audioSource = null;
+
+const updateAudio = () => {
+    const playing = index.is_sound_playing() && !isPaused;
+    if (playing && audioSource === null) {
+        if (audioContext === null) {
+            audioContext = new AudioContext();
+        }
+
+        const pattern = index.sound_pattern();
+        const buffer = audioContext.createBuffer(1, pattern.length * 8, AUDIO_BUFFER_SAMPLE_RATE);
+        const data = buffer.getChannelData(0);
+        for (let bit = 0; bit < data.length; bit++) {
+            data[bit] = (pattern[bit >> 3] & (0x80 >> (bit & 7))) ? AUDIO_VOLUME : -AUDIO_VOLUME;
+        }
+
+        audioSource = audioContext.createBufferSource();
+        audioSource.buffer = buffer;
+        audioSource.loop = true;
+        audioSource.playbackRate.value = index.sound_sample_rate() / AUDIO_BUFFER_SAMPLE_RATE;
+        audioSource.connect(audioContext.destination);
+        audioSource.start();
+    } else if (!playing && audioSource !== null) {
+        audioSource.stop();
+        audioSource = null;
+    }
+}
+
+const draw = () => {
     pre.textContent = index.render_text();
 
     updateSnapshot();
+}
+
+const renderLoop = (time) => {
+    if (lastFrameTime === null || time - lastFrameTime > FRAME_DURATION * MAX_FRAMES_BEHIND) {
+        lastFrameTime = time - FRAME_DURATION;
+    }
+    while (time - lastFrameTime >= FRAME_DURATION) {
+        lastFrameTime += FRAME_DURATION;
+        // Stopped at a breakpoint
+        if (index.update_frame()) {
+            pause();
+            break;
+        }
+    }
+    draw();
+    updateAudio();
   
     if (!isPaused) {
         requestAnimationFrame(renderLoop); 
+    } else {
+        lastFrameTime = null;
     }
 };
 
@@ -59,24 +113,97 @@ const togglePause = (event) => {
     }
 }
 
+const pause = () => {
+    isPaused = true;
+    document.getElementById("play_pause").innerHTML = "Play";
+}
+
 const step = () => {
     if (!isPaused) {
         isPaused = true;
     } else {
-        renderLoop();
+        index.update();
+        draw();
+    }
+}
+
+let savedState = null;
+
+// Keep the state for quick loading, and download it so it can be kept or attached to a bug report
+const saveState = () => {
+    savedState = index.save_state();
+
+    const link = document.createElement("a");
+    link.href = URL.createObjectURL(new Blob([savedState], { type: "application/octet-stream" }));
+    link.download = "chip8.state";
+    link.click();
+    URL.revokeObjectURL(link.href);
+}
+
+const loadState = () => {
+    if (savedState !== null && index.load_state(savedState)) {
+        draw();
+    }
+}
+
+// Rewind a second at a time, pausing so the moment can be inspected
+const REWIND_FRAMES = 60;
+const rewind = () => {
+    if (!isPaused) {
+        document.getElementById("play_pause").click();
+    }
+    index.rewind(REWIND_FRAMES);
+    draw();
+}
+
+const stepBack = () => {
+    if (!isPaused) {
+        isPaused = true;
+    } else {
+        index.reverse_step();
+        draw();
+    }
+}
+
+const stepOver = () => {
+    if (!isPaused) {
+        pause();
+    } else {
+        index.step_over();
+        draw();
+    }
+}
+
+const stepOut = () => {
+    if (!isPaused) {
+        pause();
+    } else {
+        index.step_out();
+        draw();
+    }
+}
+
+// Either an address with an optional condition, such as "0x2a4" or "0x2a4 if V3 == 0x10", or an opcode pattern such as "DXYN"
+const addBreakpoint = () => {
+    const text = document.getElementById("breakpoint").value.trim();
+    const [address, condition] = text.split(/\s+if\s+/i);
+    // Opcode patterns are marked so an address like 02a4 isn't mistaken for one
+    const opcode = address.match(/^op:\s*(\S+)$/i);
+
+    const added = opcode
+        ? index.add_opcode_breakpoint(opcode[1])
+        : /^(0x)?[0-9a-f]+$/i.test(address) && index.add_breakpoint(parseInt(address, 16), condition || "");
+    if (!added) {
+        console.error("Invalid breakpoint: ", text);
     }
 }
 
 const handleKeydownEvent = (event) => {
-    const key = event.key;
-    const key_index = mapKeyEventCodeToKeypadIndex(key);
-    const result = index.keydown(key_index);
+    index.key_name_down(event.key);
 }
 
 const handleKeyupEvent = (event) => {
-    const key = event.key;
-    const key_index = mapKeyEventCodeToKeypadIndex(key);
-    const result = index.keyup(key_index);
+    index.key_name_up(event.key);
 }
 
 const sizeRenderCanvasContainer = (element) => {
@@ -115,6 +242,18 @@ const setIndex = (newIndex) => {
     index = newIndex;
     indexReady = true;
 
+    if (programsListReady) {
+        index.set_program_library(JSON.stringify(programsList));
+    }
+    if (keyMap !== null) {
+        index.set_key_map(keyMap);
+    }
+    // ?seed=<number> makes random numbers repeat between runs
+    const seed = new URLSearchParams(window.location.search).get('seed');
+    if (seed !== null) {
+        index.set_random_seed(Number(seed) >>> 0);
+    }
+
     document.addEventListener('keydown', handleKeydownEvent);
     document.addEventListener('keyup', handleKeyupEvent);
 
@@ -124,6 +263,15 @@ const setIndex = (newIndex) => {
     const stepElement = document.getElementById("step");
     stepElement.onclick = step;
 
+    document.getElementById("save_state").onclick = saveState;
+    document.getElementById("load_state").onclick = loadState;
+    document.getElementById("rewind").onclick = rewind;
+    document.getElementById("step_back").onclick = stepBack;
+    document.getElementById("step_over").onclick = stepOver;
+    document.getElementById("step_out").onclick = stepOut;
+    document.getElementById("add_breakpoint").onclick = addBreakpoint;
+    document.getElementById("clear_breakpoints").onclick = () => index.clear_breakpoints();
+
     window.addEventListener('resize', renderCanvasContainerResize);
     setTimeout(() => {
         const renderCanvasContainer = document.getElementById("chip8_render-canvas_container");
@@ -156,7 +304,7 @@ const loadProgram = (fileName) => {
         })
         .then(() => {
             let bytes = result.split(',');
-            index.load(bytes);
+            index.load_program(fileName, bytes);
         })
         .catch((error) => {
             console.error('While loading loading program: ', error);
@@ -210,6 +358,10 @@ const setProgramsList = (newProgramsList) => {
     programsList = newProgramsList;
     programsListReady = true;
 
+    if (indexReady) {
+        index.set_program_library(JSON.stringify(programsList));
+    }
+
     programsListElementSelectedIndex(programsListElement.selectedIndex);
 }
 
@@ -231,7 +383,28 @@ const loadProgramList = () => {
         });
 }
 
+const loadKeyMap = () => {
+    return fetch('./keymap.toml')
+        .then((response) => {
+            if (response.status !== 200) {
+                throw new Error('Looks like there was a problem. Status Code: ' + response.status);
+            }
+
+            return response.text();
+        })
+        .then((text) => {
+            keyMap = text;
+            if (indexReady) {
+                index.set_key_map(keyMap);
+            }
+        })
+        .catch((error) => {
+            console.error("While loading key map: ", error);
+        });
+}
+
 loadProgramList();
+loadKeyMap();
 const newValue = Index.new();
 if (newValue && typeof newValue.then == 'function') {
     newValue